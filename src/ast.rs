@@ -0,0 +1,652 @@
+//! Howser's own model of a CommonMark syntax tree.
+//!
+//! Parsing backends translate whatever representation their parser produces into a `Tree`, and
+//! everything downstream of parsing (`document`, `validator`, `errors`) works exclusively with
+//! the `Node` handles that a `Tree` hands out.
+
+use std::fmt::{Debug, Display, Error, Formatter};
+use std::ops::Range;
+
+/// Index of a node within the `Tree` that owns it.
+pub type NodeId = usize;
+
+/// The id of the document node in every `Tree`.
+pub const ROOT_ID: NodeId = 0;
+
+/// CommonMark list flavors.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ListType {
+    Bullet,
+    Ordered,
+}
+
+/// Location of a node in its source text.
+///
+/// Lines and columns are 1-based. A value of zero means that the backend did not report a
+/// position for that node.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct Span {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    /// Returns a new `Span`.
+    pub fn new(start_line: usize, start_column: usize, end_line: usize, end_column: usize) -> Self {
+        Span {
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+
+    /// Determines if the backend reported a position for this span.
+    pub fn is_known(&self) -> bool {
+        self.start_line != 0
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        if !self.is_known() {
+            write!(f, "unknown location")
+        } else if self.start_column == 0 {
+            write!(f, "line {}", self.start_line)
+        } else if self.start_line != self.end_line {
+            write!(
+                f,
+                "lines {}:{}-{}:{}",
+                self.start_line, self.start_column, self.end_line, self.end_column
+            )
+        } else if self.start_column != self.end_column {
+            write!(
+                f,
+                "line {}, columns {}-{}",
+                self.start_line, self.start_column, self.end_column
+            )
+        } else {
+            write!(f, "line {}, column {}", self.start_line, self.start_column)
+        }
+    }
+}
+
+/// The type of a node along with any content that belongs to it directly.
+#[derive(PartialEq, Clone, Debug)]
+pub enum NodeKind {
+    Document,
+    BlockQuote,
+    List(ListType),
+    Item,
+    CodeBlock { info: String, literal: String },
+    HtmlBlock(String),
+    Paragraph,
+    Heading(u32),
+    ThematicBreak,
+    Text(String),
+    SoftBreak,
+    LineBreak,
+    Code(String),
+    HtmlInline(String),
+    Emph,
+    Strong,
+    Link { url: String, title: String },
+    Image { url: String, title: String },
+}
+
+impl NodeKind {
+    /// Returns the CommonMark name for this kind of node.
+    pub fn type_string(&self) -> &'static str {
+        match self {
+            &NodeKind::Document => "document",
+            &NodeKind::BlockQuote => "block_quote",
+            &NodeKind::List(_) => "list",
+            &NodeKind::Item => "item",
+            &NodeKind::CodeBlock { .. } => "code_block",
+            &NodeKind::HtmlBlock(_) => "html_block",
+            &NodeKind::Paragraph => "paragraph",
+            &NodeKind::Heading(_) => "heading",
+            &NodeKind::ThematicBreak => "thematic_break",
+            &NodeKind::Text(_) => "text",
+            &NodeKind::SoftBreak => "softbreak",
+            &NodeKind::LineBreak => "linebreak",
+            &NodeKind::Code(_) => "code",
+            &NodeKind::HtmlInline(_) => "html_inline",
+            &NodeKind::Emph => "emph",
+            &NodeKind::Strong => "strong",
+            &NodeKind::Link { .. } => "link",
+            &NodeKind::Image { .. } => "image",
+        }
+    }
+
+    /// Determines if this kind is a block level element.
+    pub fn is_block(&self) -> bool {
+        match self {
+            &NodeKind::Document
+            | &NodeKind::BlockQuote
+            | &NodeKind::List(_)
+            | &NodeKind::Item
+            | &NodeKind::CodeBlock { .. }
+            | &NodeKind::HtmlBlock(_)
+            | &NodeKind::Paragraph
+            | &NodeKind::Heading(_)
+            | &NodeKind::ThematicBreak => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    kind: NodeKind,
+    span: Span,
+    parent: Option<NodeId>,
+    first_child: Option<NodeId>,
+    last_child: Option<NodeId>,
+    prev_sibling: Option<NodeId>,
+    next_sibling: Option<NodeId>,
+}
+
+impl Entry {
+    fn new(kind: NodeKind, span: Span) -> Self {
+        Entry {
+            kind,
+            span,
+            parent: None,
+            first_child: None,
+            last_child: None,
+            prev_sibling: None,
+            next_sibling: None,
+        }
+    }
+}
+
+/// An owned CommonMark syntax tree.
+///
+/// Nodes are stored in an arena and addressed by `NodeId`. Unlinked nodes keep their slot so ids
+/// stay stable for the lifetime of the tree.
+#[derive(Clone, Debug)]
+pub struct Tree {
+    entries: Vec<Entry>,
+}
+
+impl Tree {
+    /// Returns a new `Tree` containing only an empty document node.
+    pub fn new() -> Self {
+        Tree {
+            entries: vec![Entry::new(NodeKind::Document, Span::default())],
+        }
+    }
+
+    /// Returns the document node of the tree.
+    pub fn root(&self) -> Node {
+        self.node(ROOT_ID)
+    }
+
+    /// Returns a handle to the node with the given id.
+    pub fn node(&self, id: NodeId) -> Node {
+        Node { tree: self, id }
+    }
+
+    /// Appends a new node as the last child of `parent` and returns its id.
+    ///
+    /// Adjacent text nodes are merged so that every backend presents runs of text the same way.
+    pub fn append(&mut self, parent: NodeId, kind: NodeKind, span: Span) -> NodeId {
+        if let NodeKind::Text(ref content) = kind {
+            if let Some(last) = self.entries[parent].last_child {
+                if let NodeKind::Text(ref mut existing) = self.entries[last].kind {
+                    existing.push_str(content);
+                    if span.is_known() {
+                        if !self.entries[last].span.is_known() {
+                            self.entries[last].span = span;
+                        } else {
+                            self.entries[last].span.end_line = span.end_line;
+                            self.entries[last].span.end_column = span.end_column;
+                        }
+                    }
+                    return last;
+                }
+            }
+        }
+
+        let id = self.entries.len();
+        let mut entry = Entry::new(kind, span);
+        entry.parent = Some(parent);
+        entry.prev_sibling = self.entries[parent].last_child;
+        self.entries.push(entry);
+
+        match self.entries[parent].last_child {
+            Some(last) => self.entries[last].next_sibling = Some(id),
+            None => self.entries[parent].first_child = Some(id),
+        }
+        self.entries[parent].last_child = Some(id);
+
+        id
+    }
+
+    /// Appends a copy of `node` and its descendants, which may belong to another tree, as the
+    /// last child of `parent` and returns the id of the copy.
+    ///
+    /// Spans are not copied, since they refer to the source of the other tree.
+    pub fn append_copy(&mut self, parent: NodeId, node: &Node) -> NodeId {
+        let id = self.append(parent, node.kind().clone(), Span::default());
+        for child in node.children() {
+            self.append_copy(id, &child);
+        }
+        id
+    }
+
+    /// Detaches a node, along with its descendants, from the tree.
+    pub fn unlink(&mut self, id: NodeId) {
+        let (parent, prev, next) = {
+            let entry = &self.entries[id];
+            (entry.parent, entry.prev_sibling, entry.next_sibling)
+        };
+
+        match prev {
+            Some(prev) => self.entries[prev].next_sibling = next,
+            None => {
+                if let Some(parent) = parent {
+                    self.entries[parent].first_child = next;
+                }
+            }
+        }
+        match next {
+            Some(next) => self.entries[next].prev_sibling = prev,
+            None => {
+                if let Some(parent) = parent {
+                    self.entries[parent].last_child = prev;
+                }
+            }
+        }
+
+        let entry = &mut self.entries[id];
+        entry.parent = None;
+        entry.prev_sibling = None;
+        entry.next_sibling = None;
+    }
+
+    /// Returns a mutable reference to the kind of the given node.
+    pub fn kind_mut(&mut self, id: NodeId) -> &mut NodeKind {
+        &mut self.entries[id].kind
+    }
+
+    /// Sets the source location of the given node.
+    pub fn set_span(&mut self, id: NodeId, span: Span) {
+        self.entries[id].span = span;
+    }
+}
+
+/// A lightweight handle to a node within a `Tree`.
+#[derive(Clone, Copy)]
+pub struct Node<'t> {
+    tree: &'t Tree,
+    id: NodeId,
+}
+
+impl<'t> Node<'t> {
+    /// Returns the id of this node, unique within its tree.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Returns the tree this node belongs to.
+    pub fn tree(&self) -> &'t Tree {
+        self.tree
+    }
+
+    /// Returns the kind of this node.
+    pub fn kind(&self) -> &'t NodeKind {
+        &self.entry().kind
+    }
+
+    /// Returns the source location of this node.
+    pub fn span(&self) -> Span {
+        self.entry().span
+    }
+
+    /// Returns the source location of a byte range within the content of a text or code node.
+    ///
+    /// The content is located relative to the end of the node because Rx annotations are removed
+    /// from the start of text. The span of the whole node is returned for other kinds of nodes and
+    /// for content that does not sit on a single line.
+    pub fn content_span(&self, range: Range<usize>) -> Span {
+        let span = self.span();
+        let (content, closing_delimiter) = match self.kind() {
+            &NodeKind::Text(ref content) => (content, 0),
+            &NodeKind::Code(ref content) => (content, 1),
+            _ => return span,
+        };
+        let is_single_line = span.start_line == span.end_line && !content.contains('\n');
+        let is_valid_range = range.start <= range.end && range.end <= content.len()
+            && content.is_char_boundary(range.start)
+            && content.is_char_boundary(range.end);
+        if !span.is_known() || !is_single_line || !is_valid_range {
+            return span;
+        }
+
+        let content_end = span.end_column.saturating_sub(closing_delimiter);
+        let content_start = (content_end + 1).saturating_sub(content.chars().count());
+        let start_column = content_start + content[..range.start].chars().count();
+        let length = content[range].chars().count();
+
+        let end_column = (start_column + length.max(1) - 1).min(span.end_column);
+
+        Span::new(
+            span.start_line,
+            start_column.max(span.start_column).min(end_column),
+            span.end_line,
+            end_column,
+        )
+    }
+
+    /// Returns the CommonMark name for the type of this node.
+    pub fn type_string(&self) -> &'static str {
+        self.kind().type_string()
+    }
+
+    pub fn parent(&self) -> Option<Node<'t>> {
+        self.entry().parent.map(|id| self.tree.node(id))
+    }
+
+    pub fn first_child(&self) -> Option<Node<'t>> {
+        self.entry().first_child.map(|id| self.tree.node(id))
+    }
+
+    pub fn last_child(&self) -> Option<Node<'t>> {
+        self.entry().last_child.map(|id| self.tree.node(id))
+    }
+
+    pub fn next_sibling(&self) -> Option<Node<'t>> {
+        self.entry().next_sibling.map(|id| self.tree.node(id))
+    }
+
+    pub fn prev_sibling(&self) -> Option<Node<'t>> {
+        self.entry().prev_sibling.map(|id| self.tree.node(id))
+    }
+
+    /// Returns an iterator over the direct children of this node.
+    pub fn children(&self) -> Children<'t> {
+        Children {
+            next: self.first_child(),
+        }
+    }
+
+    /// Returns a pre-order iterator over this node and all of its descendants.
+    pub fn descendants(&self) -> Descendants<'t> {
+        Descendants {
+            root: *self,
+            next: Some(*self),
+        }
+    }
+
+    /// Renders this node and its descendants as CommonMark.
+    pub fn render_commonmark(&self) -> String {
+        let mut out = String::new();
+        render_node(self, &mut out);
+        out
+    }
+
+    fn entry(&self) -> &'t Entry {
+        &self.tree.entries[self.id]
+    }
+}
+
+impl<'t> PartialEq for Node<'t> {
+    fn eq(&self, other: &Node<'t>) -> bool {
+        self.id == other.id && self.tree as *const Tree == other.tree as *const Tree
+    }
+}
+
+impl<'t> Debug for Node<'t> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "<Node {}: {:?} at {:?}>", self.id, self.kind(), self.span())
+    }
+}
+
+/// Iterator over the children of a `Node`.
+pub struct Children<'t> {
+    next: Option<Node<'t>>,
+}
+
+impl<'t> Iterator for Children<'t> {
+    type Item = Node<'t>;
+
+    fn next(&mut self) -> Option<Node<'t>> {
+        let current = self.next;
+        self.next = current.and_then(|node| node.next_sibling());
+        current
+    }
+}
+
+/// Pre-order iterator over a `Node` and its descendants.
+pub struct Descendants<'t> {
+    root: Node<'t>,
+    next: Option<Node<'t>>,
+}
+
+impl<'t> Iterator for Descendants<'t> {
+    type Item = Node<'t>;
+
+    fn next(&mut self) -> Option<Node<'t>> {
+        let current = self.next?;
+
+        self.next = match current.first_child() {
+            Some(child) => Some(child),
+            None => {
+                let mut ancestor = Some(current);
+                let mut following = None;
+                while let Some(node) = ancestor {
+                    if node == self.root {
+                        break;
+                    }
+                    if let Some(sibling) = node.next_sibling() {
+                        following = Some(sibling);
+                        break;
+                    }
+                    ancestor = node.parent();
+                }
+                following
+            }
+        };
+
+        Some(current)
+    }
+}
+
+/// Writes the CommonMark form of `node` to `out`.
+fn render_node(node: &Node, out: &mut String) {
+    match node.kind() {
+        &NodeKind::Document => render_blocks(node, "\n\n", out),
+        &NodeKind::BlockQuote => {
+            let mut inner = String::new();
+            render_blocks(node, "\n\n", &mut inner);
+            let quoted: Vec<String> = inner
+                .lines()
+                .map(|line| match line.is_empty() {
+                    true => ">".to_string(),
+                    false => format!("> {}", line),
+                })
+                .collect();
+            out.push_str(&quoted.join("\n"));
+        }
+        &NodeKind::List(ref list_type) => {
+            let items: Vec<String> = node.children()
+                .enumerate()
+                .map(|(i, item)| {
+                    let marker = match list_type {
+                        &ListType::Bullet => "* ".to_string(),
+                        &ListType::Ordered => format!("{}. ", i + 1),
+                    };
+                    let indent = vec![" "; marker.len()].join("");
+                    let mut inner = String::new();
+                    render_blocks(&item, "\n\n", &mut inner);
+                    let mut lines = inner.lines();
+                    let mut rendered = format!("{}{}", marker, lines.next().unwrap_or(""));
+                    for line in lines {
+                        rendered.push('\n');
+                        if !line.is_empty() {
+                            rendered.push_str(&indent);
+                            rendered.push_str(line);
+                        }
+                    }
+                    rendered
+                })
+                .collect();
+            out.push_str(&items.join("\n"));
+        }
+        &NodeKind::Item => render_blocks(node, "\n\n", out),
+        &NodeKind::CodeBlock {
+            ref info,
+            ref literal,
+        } => {
+            out.push_str("```");
+            out.push_str(info);
+            out.push('\n');
+            out.push_str(literal);
+            if !literal.is_empty() && !literal.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str("```");
+        }
+        &NodeKind::HtmlBlock(ref literal) => out.push_str(literal.trim_right()),
+        &NodeKind::Paragraph => render_inlines(node, out),
+        &NodeKind::Heading(level) => {
+            out.push_str(&vec!["#"; level as usize].join(""));
+            out.push(' ');
+            render_inlines(node, out);
+        }
+        &NodeKind::ThematicBreak => out.push_str("-----"),
+        &NodeKind::Text(ref content) => out.push_str(content),
+        &NodeKind::SoftBreak => out.push('\n'),
+        &NodeKind::LineBreak => out.push_str("\\\n"),
+        &NodeKind::Code(ref content) => {
+            let fence = match content.contains('`') {
+                true => "``",
+                false => "`",
+            };
+            out.push_str(fence);
+            out.push_str(content);
+            out.push_str(fence);
+        }
+        &NodeKind::HtmlInline(ref literal) => out.push_str(literal),
+        &NodeKind::Emph => {
+            out.push('*');
+            render_inlines(node, out);
+            out.push('*');
+        }
+        &NodeKind::Strong => {
+            out.push_str("**");
+            render_inlines(node, out);
+            out.push_str("**");
+        }
+        &NodeKind::Link { ref url, ref title } => {
+            out.push('[');
+            render_inlines(node, out);
+            out.push_str("](");
+            render_destination(url, title, out);
+            out.push(')');
+        }
+        &NodeKind::Image { ref url, ref title } => {
+            out.push_str("![");
+            render_inlines(node, out);
+            out.push_str("](");
+            render_destination(url, title, out);
+            out.push(')');
+        }
+    }
+}
+
+fn render_blocks(parent: &Node, separator: &str, out: &mut String) {
+    let rendered: Vec<String> = parent
+        .children()
+        .map(|child| {
+            let mut block = String::new();
+            render_node(&child, &mut block);
+            block
+        })
+        .collect();
+    out.push_str(&rendered.join(separator));
+}
+
+fn render_inlines(parent: &Node, out: &mut String) {
+    for child in parent.children() {
+        render_node(&child, out);
+    }
+}
+
+fn render_destination(url: &str, title: &str, out: &mut String) {
+    out.push_str(url);
+    if !title.is_empty() {
+        out.push_str(&format!(" \"{}\"", title.replace('"', "\\\"")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NodeKind, Span, Tree, ROOT_ID};
+
+    #[test]
+    fn test_adjacent_text_is_merged() {
+        let mut tree = Tree::new();
+        let paragraph = tree.append(ROOT_ID, NodeKind::Paragraph, Span::default());
+        let first = tree.append(paragraph, NodeKind::Text("-".to_string()), Span::default());
+        let second = tree.append(paragraph, NodeKind::Text("!!-".to_string()), Span::default());
+
+        assert_eq!(first, second);
+        assert_eq!(
+            tree.node(first).kind(),
+            &NodeKind::Text("-!!-".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unlink_preserves_siblings() {
+        let mut tree = Tree::new();
+        let first = tree.append(ROOT_ID, NodeKind::Paragraph, Span::default());
+        let second = tree.append(ROOT_ID, NodeKind::ThematicBreak, Span::default());
+        let third = tree.append(ROOT_ID, NodeKind::Paragraph, Span::default());
+
+        tree.unlink(second);
+
+        let ids: Vec<_> = tree.root().children().map(|node| node.id()).collect();
+        assert_eq!(ids, vec![first, third]);
+        assert!(tree.node(second).parent().is_none());
+    }
+
+    #[test]
+    fn test_descendants_are_preorder() {
+        let mut tree = Tree::new();
+        let quote = tree.append(ROOT_ID, NodeKind::BlockQuote, Span::default());
+        let paragraph = tree.append(quote, NodeKind::Paragraph, Span::default());
+        let text = tree.append(paragraph, NodeKind::Text("a".to_string()), Span::default());
+        let rule = tree.append(ROOT_ID, NodeKind::ThematicBreak, Span::default());
+
+        let ids: Vec<_> = tree.root().descendants().map(|node| node.id()).collect();
+        assert_eq!(ids, vec![ROOT_ID, quote, paragraph, text, rule]);
+    }
+
+    #[test]
+    fn test_span_display() {
+        assert_eq!(Span::new(3, 5, 3, 9).to_string(), "line 3, columns 5-9");
+        assert_eq!(Span::new(3, 5, 3, 5).to_string(), "line 3, column 5");
+        assert_eq!(Span::new(3, 5, 4, 2).to_string(), "lines 3:5-4:2");
+        assert_eq!(Span::default().to_string(), "unknown location");
+    }
+
+    #[test]
+    fn test_content_span_is_relative_to_node_end() {
+        let mut tree = Tree::new();
+        let paragraph = tree.append(ROOT_ID, NodeKind::Paragraph, Span::new(2, 1, 2, 14));
+        let text = tree.append(
+            paragraph,
+            NodeKind::Text("quick brown".to_string()),
+            Span::new(2, 4, 2, 14),
+        );
+
+        assert_eq!(tree.node(text).content_span(6..11), Span::new(2, 10, 2, 14));
+        assert_eq!(tree.node(paragraph).content_span(0..1), Span::new(2, 1, 2, 14));
+    }
+}
@@ -0,0 +1,200 @@
+//! Backend built on libcmark via doogie.
+
+use ast::{ListType, NodeId, NodeKind, Span, Tree, ROOT_ID};
+use doogie;
+use doogie::constants::ListType as CMarkListType;
+use doogie::Node as CMarkNode;
+use errors::HowserResult;
+use super::LineIndex;
+
+/// Parses Markdown text into a `Tree` using libcmark.
+pub fn parse_document(text: &str) -> HowserResult<Tree> {
+    trace!("cmark::parse_document()");
+    let lines = LineIndex::new(text);
+    let root = doogie::parse_document(&text.to_string());
+    let mut tree = Tree::new();
+
+    tree.set_span(ROOT_ID, span_of(&root, &lines));
+    append_children(&root, ROOT_ID, &mut tree, &lines)?;
+    locate_inlines(&mut tree, &lines);
+
+    Ok(tree)
+}
+
+/// Recursively copies the children of a cmark node into the tree under `parent_id`.
+fn append_children(
+    parent: &CMarkNode,
+    parent_id: NodeId,
+    tree: &mut Tree,
+    lines: &LineIndex,
+) -> HowserResult<()> {
+    let mut current_child = parent.first_child()?;
+
+    while let Some(node) = current_child {
+        if let Some(kind) = kind_of(&node)? {
+            let id = tree.append(parent_id, kind, span_of(&node, lines));
+            append_children(&node, id, tree, lines)?;
+        }
+        current_child = node.next_sibling()?;
+    }
+
+    Ok(())
+}
+
+/// Returns the `NodeKind` equivalent of a cmark node.
+///
+/// Custom nodes are never produced by the cmark parser and have no equivalent.
+fn kind_of(node: &CMarkNode) -> HowserResult<Option<NodeKind>> {
+    let kind = match node {
+        CMarkNode::Document(_) => NodeKind::Document,
+        CMarkNode::BlockQuote(_) => NodeKind::BlockQuote,
+        CMarkNode::List(ref list) => match list.get_list_type()? {
+            CMarkListType::CMarkOrderedList => NodeKind::List(ListType::Ordered),
+            _ => NodeKind::List(ListType::Bullet),
+        },
+        CMarkNode::Item(_) => NodeKind::Item,
+        CMarkNode::CodeBlock(ref code_block) => NodeKind::CodeBlock {
+            info: code_block.get_fence_info()?,
+            literal: code_block.get_content()?,
+        },
+        CMarkNode::HtmlBlock(ref html) => NodeKind::HtmlBlock(html.get_content()?),
+        CMarkNode::Paragraph(_) => NodeKind::Paragraph,
+        CMarkNode::Heading(ref heading) => NodeKind::Heading(heading.get_level() as u32),
+        CMarkNode::ThematicBreak(_) => NodeKind::ThematicBreak,
+        CMarkNode::Text(ref text) => NodeKind::Text(text.get_content()?),
+        CMarkNode::SoftBreak(_) => NodeKind::SoftBreak,
+        CMarkNode::LineBreak(_) => NodeKind::LineBreak,
+        CMarkNode::Code(ref code) => NodeKind::Code(code.get_content()?),
+        CMarkNode::HtmlInline(ref html) => NodeKind::HtmlInline(html.get_content()?),
+        CMarkNode::Emph(_) => NodeKind::Emph,
+        CMarkNode::Strong(_) => NodeKind::Strong,
+        CMarkNode::Link(ref link) => NodeKind::Link {
+            url: link.get_url()?,
+            title: link.get_title()?,
+        },
+        CMarkNode::Image(ref image) => NodeKind::Image {
+            url: image.get_url()?,
+            title: image.get_title()?,
+        },
+        CMarkNode::CustomBlock(_) | CMarkNode::CustomInline(_) => return Ok(None),
+    };
+
+    Ok(Some(kind))
+}
+
+/// Returns the source position cmark reported for the node.
+///
+/// cmark counts columns in bytes, so they are converted to the character columns of `Span`.
+fn span_of(node: &CMarkNode, lines: &LineIndex) -> Span {
+    let start_line = node.get_start_line() as usize;
+    if start_line == 0 {
+        return Span::default();
+    }
+    let start = lines.offset(start_line, 1) + (node.get_start_column() as usize).saturating_sub(1);
+    let end = lines.offset(node.get_end_line() as usize, 1) + node.get_end_column() as usize;
+
+    lines.span(&(start..end))
+}
+
+/// Fills in the spans of inline nodes, which cmark does not report.
+///
+/// The content of each inline leaf is searched for in the source of its enclosing paragraph or
+/// heading, in document order. Inline containers then span their located children. Content that
+/// cannot be found verbatim, such as text containing backslash escapes, keeps an unknown span.
+fn locate_inlines(tree: &mut Tree, lines: &LineIndex) {
+    let blocks: Vec<(NodeId, Span)> = tree.root()
+        .descendants()
+        .filter(|node| match node.kind() {
+            &NodeKind::Paragraph | &NodeKind::Heading(_) => node.span().is_known(),
+            _ => false,
+        })
+        .map(|node| (node.id(), node.span()))
+        .collect();
+
+    for (block, span) in blocks {
+        let inlines: Vec<(NodeId, Option<String>, bool)> = tree.node(block)
+            .descendants()
+            .skip(1)
+            .map(|node| {
+                let content = match node.kind() {
+                    &NodeKind::Text(ref content)
+                    | &NodeKind::Code(ref content)
+                    | &NodeKind::HtmlInline(ref content) => Some(content.clone()),
+                    &NodeKind::SoftBreak | &NodeKind::LineBreak => Some("\n".to_string()),
+                    _ => None,
+                };
+                let is_code = match node.kind() {
+                    &NodeKind::Code(_) => true,
+                    _ => false,
+                };
+                (node.id(), content, is_code)
+            })
+            .collect();
+        let block_start = lines.offset(span.start_line, span.start_column);
+        let end = lines.offset(span.end_line, span.end_column + 1);
+        let mut cursor = block_start;
+
+        for &(id, ref content, is_code) in inlines.iter() {
+            if let &Some(ref content) = content {
+                if cursor > end {
+                    break;
+                }
+                if let Some(found) = lines.text[cursor..end].find(content.as_str()) {
+                    let mut start = cursor + found;
+                    cursor = start + content.len();
+                    // Code spans include their backtick delimiters, as they do in other backends.
+                    let source = lines.text.as_bytes();
+                    while is_code && start > block_start && cursor < end
+                        && source[start - 1] == b'`' && source[cursor] == b'`'
+                    {
+                        start -= 1;
+                        cursor += 1;
+                    }
+                    tree.set_span(id, lines.span(&(start..cursor)));
+                }
+            }
+        }
+
+        for &(id, ref content, _) in inlines.iter().rev() {
+            if content.is_none() {
+                let container_span = {
+                    let node = tree.node(id);
+                    let first = node.children().map(|child| child.span()).find(Span::is_known);
+                    let last = node.children()
+                        .map(|child| child.span())
+                        .filter(Span::is_known)
+                        .last();
+                    match (first, last) {
+                        (Some(first), Some(last)) => Some(Span::new(
+                            first.start_line,
+                            first.start_column,
+                            last.end_line,
+                            last.end_column,
+                        )),
+                        _ => None,
+                    }
+                };
+                if let Some(container_span) = container_span {
+                    tree.set_span(id, container_span);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_document;
+    use ast::Span;
+
+    #[test]
+    fn test_inline_nodes_are_located() {
+        let tree = parse_document("# Title\n\nSome `code` text").unwrap();
+        let paragraph = tree.root().last_child().unwrap();
+        let text = paragraph.first_child().unwrap();
+        let code = text.next_sibling().unwrap();
+
+        assert_eq!(text.span(), Span::new(3, 1, 3, 5));
+        assert_eq!(code.span(), Span::new(3, 6, 3, 11));
+    }
+}
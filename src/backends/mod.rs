@@ -0,0 +1,99 @@
+//! Markdown parsing backends.
+//!
+//! A backend turns Markdown source text into an `ast::Tree`. Which backends are available is
+//! decided by cargo feature:
+//!
+//! * `cmark` (default) parses with libcmark through the doogie bindings.
+//! * `pure-rust` parses with pulldown-cmark and needs no native libraries, which makes it suitable
+//!   for static binaries.
+//!
+//! When both are enabled, `parse_document` uses `cmark`.
+
+#[cfg(feature = "cmark")]
+pub mod cmark;
+#[cfg(feature = "pure-rust")]
+pub mod pure_rust;
+
+#[cfg(feature = "cmark")]
+pub use self::cmark::parse_document;
+#[cfg(all(feature = "pure-rust", not(feature = "cmark")))]
+pub use self::pure_rust::parse_document;
+
+#[cfg(not(any(feature = "cmark", feature = "pure-rust")))]
+compile_error!("Howser needs a Markdown backend. Enable the `cmark` or `pure-rust` feature.");
+
+use ast::Span;
+use std::ops::Range;
+
+/// Maps between byte offsets in the source text and the lines and columns used by `Span`.
+///
+/// Columns count characters, not bytes.
+struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(text: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        for (offset, c) in text.char_indices() {
+            if c == '\n' {
+                line_starts.push(offset + 1);
+            }
+        }
+
+        LineIndex { text, line_starts }
+    }
+
+    /// Returns the 1-based line and column of the character at `offset`.
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.text.len());
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let line_start = self.line_starts[line];
+        let column = self.text[line_start..offset].chars().count() + 1;
+
+        (line + 1, column)
+    }
+
+    /// Returns the byte offset of the character at a 1-based line and column.
+    #[cfg(feature = "cmark")]
+    fn offset(&self, line: usize, column: usize) -> usize {
+        let line_start = match self.line_starts.get(line.saturating_sub(1)) {
+            Some(&line_start) => line_start,
+            None => return self.text.len(),
+        };
+
+        self.text[line_start..]
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map(|(offset, _)| line_start + offset)
+            .unwrap_or(self.text.len())
+    }
+
+    /// Rounds a byte offset up to the nearest character boundary.
+    fn boundary(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset += 1;
+        }
+        offset
+    }
+
+    /// Returns the span covering a range of bytes.
+    fn span(&self, range: &Range<usize>) -> Span {
+        let start = self.boundary(range.start);
+        let end = self.text[..self.boundary(range.end)]
+            .trim_right_matches('\n')
+            .len();
+        let (start_line, start_column) = self.position(start);
+        let (end_line, end_column) = match end > start {
+            true => self.position(end - 1),
+            false => (start_line, start_column),
+        };
+
+        Span::new(start_line, start_column, end_line, end_column)
+    }
+}
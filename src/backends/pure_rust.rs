@@ -0,0 +1,303 @@
+//! Backend built on the pure Rust pulldown-cmark parser.
+
+extern crate pulldown_cmark;
+
+use self::pulldown_cmark::{CodeBlockKind, Event, LinkType, Parser, Tag};
+use ast::{ListType, NodeId, NodeKind, Span, Tree, ROOT_ID};
+use errors::HowserResult;
+use super::LineIndex;
+
+/// An element that has been opened by a start event and not yet closed.
+struct Frame {
+    /// The node created for the element, or `None` for elements Howser does not model.
+    id: Option<NodeId>,
+    /// Byte offset of the start of the element.
+    start: usize,
+    /// Paragraphs pulldown-cmark leaves out of tight list items are opened implicitly.
+    implicit: bool,
+}
+
+/// Parses Markdown text into a `Tree` using pulldown-cmark.
+///
+/// pulldown-cmark is a pull parser with a slightly different model than cmark. Its events are
+/// folded into the same tree shape that the cmark backend produces: text inside tight list items is
+/// wrapped in a paragraph, code content is stored on the code node, and consecutive html lines are
+/// joined into a single html block.
+pub fn parse_document(text: &str) -> HowserResult<Tree> {
+    trace!("pure_rust::parse_document()");
+    let lines = LineIndex::new(text);
+    let mut tree = Tree::new();
+    let mut stack = vec![Frame {
+        id: Some(ROOT_ID),
+        start: 0,
+        implicit: false,
+    }];
+    let mut previous_was_html_block = false;
+
+    for (event, range) in Parser::new(text).into_offset_iter() {
+        let span = lines.span(&range);
+        let mut is_html_block = false;
+
+        match event {
+            Event::Start(tag) => {
+                let kind = kind_of(&tag);
+                let parent = match kind {
+                    Some(ref kind) if kind.is_block() => {
+                        close_implicit_paragraph(&mut stack, &mut tree, &lines, range.start);
+                        current_parent(&stack)
+                    }
+                    Some(_) => inline_parent(&mut stack, &mut tree, range.start),
+                    None => current_parent(&stack),
+                };
+                let id = kind.map(|kind| tree.append(parent, kind, span));
+                stack.push(Frame {
+                    id,
+                    start: range.start,
+                    implicit: false,
+                });
+            }
+            Event::End(_) => {
+                close_implicit_paragraph(&mut stack, &mut tree, &lines, range.end);
+                close_frame(&mut stack, &mut tree, &lines, range.end);
+            }
+            Event::Text(content) => {
+                let parent = current_parent(&stack);
+                let is_code_block = match tree.node(parent).kind() {
+                    &NodeKind::CodeBlock { .. } => true,
+                    _ => false,
+                };
+                if is_code_block {
+                    if let &mut NodeKind::CodeBlock {
+                        ref mut literal, ..
+                    } = tree.kind_mut(parent)
+                    {
+                        literal.push_str(&content);
+                    }
+                } else {
+                    let parent = inline_parent(&mut stack, &mut tree, range.start);
+                    tree.append(parent, NodeKind::Text(content.to_string()), span);
+                }
+            }
+            Event::Code(content) => {
+                let parent = inline_parent(&mut stack, &mut tree, range.start);
+                tree.append(parent, NodeKind::Code(content.to_string()), span);
+            }
+            Event::Html(content) => {
+                let parent = current_parent(&stack);
+                if accepts_inlines(tree.node(parent).kind()) {
+                    tree.append(parent, NodeKind::HtmlInline(content.to_string()), span);
+                } else {
+                    let last_child = tree.node(parent).last_child().map(|node| node.id());
+                    match last_child {
+                        Some(last) if previous_was_html_block => {
+                            if let &mut NodeKind::HtmlBlock(ref mut literal) = tree.kind_mut(last) {
+                                literal.push_str(&content);
+                            }
+                            let mut merged = tree.node(last).span();
+                            merged.end_line = span.end_line;
+                            merged.end_column = span.end_column;
+                            tree.set_span(last, merged);
+                        }
+                        _ => {
+                            tree.append(parent, NodeKind::HtmlBlock(content.to_string()), span);
+                        }
+                    }
+                    is_html_block = true;
+                }
+            }
+            Event::FootnoteReference(name) => {
+                let parent = inline_parent(&mut stack, &mut tree, range.start);
+                tree.append(parent, NodeKind::Text(format!("[^{}]", name)), span);
+            }
+            Event::TaskListMarker(checked) => {
+                let marker = match checked {
+                    true => "[x] ",
+                    false => "[ ] ",
+                };
+                let parent = inline_parent(&mut stack, &mut tree, range.start);
+                tree.append(parent, NodeKind::Text(marker.to_string()), span);
+            }
+            Event::SoftBreak => {
+                let parent = inline_parent(&mut stack, &mut tree, range.start);
+                tree.append(parent, NodeKind::SoftBreak, span);
+            }
+            Event::HardBreak => {
+                let parent = inline_parent(&mut stack, &mut tree, range.start);
+                tree.append(parent, NodeKind::LineBreak, span);
+            }
+            Event::Rule => {
+                close_implicit_paragraph(&mut stack, &mut tree, &lines, range.start);
+                let parent = current_parent(&stack);
+                tree.append(parent, NodeKind::ThematicBreak, span);
+            }
+        }
+
+        previous_was_html_block = is_html_block;
+    }
+
+    tree.set_span(ROOT_ID, lines.span(&(0..text.len())));
+
+    Ok(tree)
+}
+
+/// Returns the `NodeKind` equivalent of a pulldown-cmark tag.
+///
+/// Returns `None` for extension elements that have no CommonMark equivalent.
+fn kind_of(tag: &Tag) -> Option<NodeKind> {
+    match tag {
+        &Tag::Paragraph => Some(NodeKind::Paragraph),
+        &Tag::Heading(level) => Some(NodeKind::Heading(level)),
+        &Tag::BlockQuote => Some(NodeKind::BlockQuote),
+        &Tag::CodeBlock(CodeBlockKind::Fenced(ref info)) => Some(NodeKind::CodeBlock {
+            info: info.to_string(),
+            literal: String::new(),
+        }),
+        &Tag::CodeBlock(CodeBlockKind::Indented) => Some(NodeKind::CodeBlock {
+            info: String::new(),
+            literal: String::new(),
+        }),
+        &Tag::List(Some(_)) => Some(NodeKind::List(ListType::Ordered)),
+        &Tag::List(None) => Some(NodeKind::List(ListType::Bullet)),
+        &Tag::Item => Some(NodeKind::Item),
+        &Tag::Emphasis => Some(NodeKind::Emph),
+        &Tag::Strong => Some(NodeKind::Strong),
+        &Tag::Link(LinkType::Email, ref url, ref title) => Some(NodeKind::Link {
+            url: format!("mailto:{}", url),
+            title: title.to_string(),
+        }),
+        &Tag::Link(_, ref url, ref title) => Some(NodeKind::Link {
+            url: url.to_string(),
+            title: title.to_string(),
+        }),
+        &Tag::Image(_, ref url, ref title) => Some(NodeKind::Image {
+            url: url.to_string(),
+            title: title.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+/// Determines if inline content can be appended directly to a node of the given kind.
+fn accepts_inlines(kind: &NodeKind) -> bool {
+    match kind {
+        &NodeKind::Paragraph
+        | &NodeKind::Heading(_)
+        | &NodeKind::Emph
+        | &NodeKind::Strong
+        | &NodeKind::Link { .. }
+        | &NodeKind::Image { .. } => true,
+        _ => false,
+    }
+}
+
+/// Returns the id of the innermost open node.
+fn current_parent(stack: &Vec<Frame>) -> NodeId {
+    stack
+        .iter()
+        .rev()
+        .filter_map(|frame| frame.id)
+        .next()
+        .unwrap_or(ROOT_ID)
+}
+
+/// Returns the id of a node that inline content can be appended to, opening an implicit
+/// paragraph if the innermost open node is a container block.
+fn inline_parent(stack: &mut Vec<Frame>, tree: &mut Tree, offset: usize) -> NodeId {
+    let parent = current_parent(stack);
+    if accepts_inlines(tree.node(parent).kind()) {
+        return parent;
+    }
+
+    let id = tree.append(parent, NodeKind::Paragraph, Span::default());
+    stack.push(Frame {
+        id: Some(id),
+        start: offset,
+        implicit: true,
+    });
+    id
+}
+
+/// Closes the innermost open element if it is an implicit paragraph.
+fn close_implicit_paragraph(
+    stack: &mut Vec<Frame>,
+    tree: &mut Tree,
+    lines: &LineIndex,
+    offset: usize,
+) {
+    let is_implicit = match stack.last() {
+        Some(frame) => frame.implicit,
+        None => false,
+    };
+    if is_implicit {
+        close_frame(stack, tree, lines, offset);
+    }
+}
+
+/// Closes the innermost open element and records its full span.
+fn close_frame(stack: &mut Vec<Frame>, tree: &mut Tree, lines: &LineIndex, offset: usize) {
+    if let Some(frame) = stack.pop() {
+        if let Some(id) = frame.id {
+            tree.set_span(id, lines.span(&(frame.start..offset)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_document;
+    use ast::{ListType, NodeKind, Span};
+
+    #[test]
+    fn test_tight_list_items_contain_paragraphs() {
+        let tree = parse_document("* Foo\n* Bar").unwrap();
+        let list = tree.root().first_child().unwrap();
+        let item = list.first_child().unwrap();
+        let paragraph = item.first_child().unwrap();
+
+        assert_eq!(list.kind(), &NodeKind::List(ListType::Bullet));
+        assert_eq!(paragraph.kind(), &NodeKind::Paragraph);
+        assert_eq!(
+            paragraph.first_child().unwrap().kind(),
+            &NodeKind::Text("Foo".to_string())
+        );
+    }
+
+    #[test]
+    fn test_prompts_are_single_text_nodes() {
+        let tree = parse_document("The quick brown fox -!!- over-??-.").unwrap();
+        let paragraph = tree.root().first_child().unwrap();
+        let text = paragraph.first_child().unwrap();
+
+        assert_eq!(
+            text.kind(),
+            &NodeKind::Text("The quick brown fox -!!- over-??-.".to_string())
+        );
+        assert!(text.next_sibling().is_none());
+    }
+
+    #[test]
+    fn test_code_block_content_is_collected() {
+        let tree = parse_document("```-??-\nlet a = 1;\n```").unwrap();
+        let code_block = tree.root().first_child().unwrap();
+
+        assert_eq!(
+            code_block.kind(),
+            &NodeKind::CodeBlock {
+                info: "-??-".to_string(),
+                literal: "let a = 1;\n".to_string(),
+            }
+        );
+        assert_eq!(code_block.span().start_line, 1);
+    }
+
+    #[test]
+    fn test_inline_nodes_have_spans() {
+        let tree = parse_document("# Title\n\nSome *emphasized* text").unwrap();
+        let paragraph = tree.root().last_child().unwrap();
+        let emph = paragraph.first_child().unwrap().next_sibling().unwrap();
+
+        assert_eq!(paragraph.span(), Span::new(3, 1, 3, 22));
+        assert_eq!(emph.span(), Span::new(3, 6, 3, 17));
+        assert_eq!(emph.first_child().unwrap().span(), Span::new(3, 7, 3, 16));
+    }
+}
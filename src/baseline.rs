@@ -0,0 +1,213 @@
+//! Records of known problems, so that only new problems fail validation.
+
+extern crate toml;
+
+use self::toml::value::Table;
+use self::toml::Value;
+use errors::{ErrorCode, HowserError, HowserResult, ValidationProblem};
+use helpers::cli;
+use std::fmt;
+
+/// A known problem, identified without reference to line numbers.
+///
+/// Problems are fingerprinted by their code, the document they were found in, and the first line
+/// of source of the prescription element they were reported against. Edits elsewhere in either
+/// file leave the fingerprint unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BaselineEntry {
+    pub document: String,
+    pub prescription: String,
+    pub element: String,
+    pub code: ErrorCode,
+}
+
+impl BaselineEntry {
+    /// Returns the fingerprint of a problem found in `document_name`, where `rx_source` is the
+    /// text of the prescription it was validated against.
+    pub fn new(problem: &ValidationProblem, rx_source: &str, document_name: &str) -> Self {
+        let document = match problem.document_location() {
+            Some(location) => location.file,
+            None => document_name.to_string(),
+        };
+        let (prescription, element) = match problem.prescription_location() {
+            Some(location) => {
+                let element = match location.span.is_known() {
+                    true => cli::source_lines(rx_source, &location.span)
+                        .lines()
+                        .map(|line| line.trim())
+                        .find(|line| !line.is_empty())
+                        .unwrap_or("")
+                        .to_string(),
+                    false => String::new(),
+                };
+                (location.file, element)
+            }
+            None => (String::new(), String::new()),
+        };
+
+        BaselineEntry {
+            document,
+            prescription,
+            element,
+            code: problem.code(),
+        }
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        let field = |name: &str| value.get(name).and_then(|field| field.as_str());
+
+        Some(BaselineEntry {
+            document: field("document")?.to_string(),
+            prescription: field("prescription").unwrap_or("").to_string(),
+            element: field("element").unwrap_or("").to_string(),
+            code: ErrorCode::parse(field("code")?)?,
+        })
+    }
+
+    fn to_value(&self) -> Value {
+        let mut table = Table::new();
+        table.insert("code".to_string(), Value::String(self.code.to_string()));
+        table.insert("document".to_string(), Value::String(self.document.clone()));
+        if !self.prescription.is_empty() {
+            table.insert(
+                "prescription".to_string(),
+                Value::String(self.prescription.clone()),
+            );
+            table.insert("element".to_string(), Value::String(self.element.clone()));
+        }
+
+        Value::Table(table)
+    }
+}
+
+impl fmt::Display for BaselineEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.prescription.is_empty() {
+            true => write!(f, "{} in {}", self.code, self.document),
+            false => write!(
+                f,
+                "{} in {} at \"{}\" from {}",
+                self.code, self.document, self.element, self.prescription
+            ),
+        }
+    }
+}
+
+/// A set of known problems.
+///
+/// Each entry accounts for one occurrence of a problem, so a document that gains a second
+/// problem with the same fingerprint still reports it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Baseline {
+    entries: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Returns a new `Baseline` holding the given entries.
+    pub fn new(mut entries: Vec<BaselineEntry>) -> Self {
+        entries.sort();
+        Baseline { entries }
+    }
+
+    /// Parses a baseline from the contents of a baseline file.
+    pub fn from_toml(filename: &str, text: &str) -> HowserResult<Self> {
+        let value = text.parse::<Value>()?;
+        let problems = match value.get("Problems") {
+            Some(problems) => problems.as_array().ok_or(HowserError::RuntimeError(format!(
+                "Error parsing baseline file {}. Problems must be an array of tables.",
+                filename
+            )))?,
+            None => return Ok(Baseline::new(Vec::new())),
+        };
+
+        let entries = problems
+            .iter()
+            .map(|problem| {
+                BaselineEntry::from_value(problem).ok_or(HowserError::RuntimeError(format!(
+                    "Error parsing baseline file {}. Each problem needs a code and a document.",
+                    filename
+                )))
+            })
+            .collect::<HowserResult<Vec<BaselineEntry>>>()?;
+
+        Ok(Baseline::new(entries))
+    }
+
+    /// Returns the contents of a baseline file holding these entries.
+    pub fn to_toml(&self) -> HowserResult<String> {
+        let mut table = Table::new();
+        table.insert(
+            "Problems".to_string(),
+            Value::Array(self.entries.iter().map(|entry| entry.to_value()).collect()),
+        );
+
+        toml::to_string(&Value::Table(table))
+            .map_err(|e| HowserError::RuntimeError(format!("Unable to write baseline: {}", e)))
+    }
+
+    /// Returns the entries that have not been taken.
+    pub fn entries(&self) -> &[BaselineEntry] {
+        &self.entries
+    }
+
+    /// Determines if an entry matching `entry` has not been taken.
+    pub fn contains(&self, entry: &BaselineEntry) -> bool {
+        self.entries.contains(entry)
+    }
+
+    /// Removes an entry matching `entry`, returning whether there was one.
+    pub fn take(&mut self, entry: &BaselineEntry) -> bool {
+        match self.entries.iter().position(|known| known == entry) {
+            Some(index) => {
+                self.entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Baseline, BaselineEntry};
+    use document::{Document, Prescription};
+    use errors::ErrorCode;
+    use validator::Validator;
+
+    const RX: &str = "# Title\n\n## Usage\n\n-!!-\n";
+
+    fn fingerprint(text: &str) -> BaselineEntry {
+        let rx = Prescription::from_text(RX, Some("README.rx".to_string())).unwrap();
+        let document = Document::from_text(text, Some("README.md".to_string())).unwrap();
+        let problems = Validator::new(rx, document).validate().unwrap();
+
+        BaselineEntry::new(&problems[0], RX, "README.md")
+    }
+
+    #[test]
+    fn test_fingerprints_ignore_line_numbers() {
+        let entry = fingerprint("# Title\n\n### Usage\n\nRun it.\n");
+        let moved = fingerprint("# Title\n\n\n\n\n### Usage\n\nRun it.\n");
+
+        assert_eq!(entry.code, ErrorCode::TypeMismatch);
+        assert_eq!(entry.document, "README.md");
+        assert_eq!(entry.prescription, "README.rx");
+        assert_eq!(entry.element, "## Usage");
+        assert_eq!(entry, moved);
+    }
+
+    #[test]
+    fn test_baseline_round_trip_and_take() {
+        let entry = fingerprint("# Title\n\n### Usage\n\nRun it.\n");
+        let baseline = Baseline::new(vec![entry.clone(), entry.clone()]);
+        let text = baseline.to_toml().unwrap();
+        assert!(text.contains("[[Problems]]\ncode = \"HX0005\"\n"), "{}", text);
+
+        let mut parsed = Baseline::from_toml("baseline.toml", &text).unwrap();
+        assert_eq!(parsed, baseline);
+        assert!(parsed.take(&entry));
+        assert!(parsed.take(&entry));
+        assert!(!parsed.take(&entry));
+        assert!(parsed.entries().is_empty());
+    }
+}
@@ -1,23 +1,39 @@
 #[macro_use]
 extern crate clap;
-extern crate doogie;
 extern crate env_logger;
 extern crate howser;
+extern crate serde_json;
+extern crate termion;
 extern crate toml;
 
 use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
-use doogie::parse_document;
-use howser::document::Document;
-use howser::errors::{HowserError, HowserResult, ValidationProblem};
-use howser::reporters::{make_cli_report, CLIOption};
+use howser::baseline::{Baseline, BaselineEntry};
+use howser::cache::{CachedProblem, ValidationCache};
+use howser::document::{Document, Prescription};
+use howser::extract::Extractor;
+use howser::fixer::fix_document;
+use howser::errors::{ErrorCode, HowserError, HowserResult, Severity, ValidationProblem};
+use howser::helpers::diff::unified_diff;
+use howser::helpers::git::{absolute_path, changed_files};
+use howser::helpers::pool::map_ordered;
+use howser::helpers::watch::FileWatcher;
+use howser::infer::infer_prescription;
+use howser::linter::lint;
+use howser::lsp::Server;
+use howser::pharmacy::{self, Pharmacy, SpecOptions};
+use howser::reporters::{make_alignment_json, make_alignment_report, make_cli_report, CLIOption};
+use howser::scaffold::Scaffolder;
 use howser::validator::Validator;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
 use std::str;
-use toml::Value;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use termion::{clear, color, cursor, style};
 
 fn main() {
     env_logger::init();
@@ -25,24 +41,55 @@ fn main() {
     let app = make_app();
     let matches = app.get_matches();
 
-    if let Err(e) = run(&matches) {
-        println!("{}", e.description());
-        let mut inner_err = e.cause();
-        while let Some(error) = inner_err {
-            println!("{}", error.description());
-            inner_err = error.cause();
+    match run(&matches) {
+        Err(e) => {
+            println!("{}", error_message(&e));
+            std::process::exit(1);
         }
-        std::process::exit(1);
-    } else {
-        std::process::exit(0);
+        Ok(status) => std::process::exit(status),
+    }
+}
+
+/// Returns the description of an error followed by the descriptions of its causes, one per line.
+fn error_message(e: &HowserError) -> String {
+    let mut lines = vec![e.description().to_string()];
+    let mut inner_err = e.cause();
+    while let Some(error) = inner_err {
+        lines.push(error.description().to_string());
+        inner_err = error.cause();
     }
+
+    lines.join("\n")
 }
 
-fn run(args: &ArgMatches) -> HowserResult<()> {
-    let (issues, options) = match args.subcommand() {
+/// The file name that stands for stdin.
+const STDIN_FILE_NAME: &str = "-";
+/// The name that content read from stdin is reported under.
+const STDIN_SOURCE_NAME: &str = "<stdin>";
+
+/// The pharmacy file used unless another is given.
+const DEFAULT_PHARMACY_FILE: &str = "Pharmacy.toml";
+/// The manifest whose metadata is the pharmacy when there is no pharmacy file.
+const CARGO_MANIFEST: &str = "Cargo.toml";
+
+/// Directory holding cached validation results unless another is given.
+const DEFAULT_CACHE_DIR: &str = ".howser-cache";
+
+/// Milliseconds between checks for changed files in watch mode.
+const WATCH_INTERVAL_MS: u64 = 250;
+
+/// Exit status when errors were found.
+const EXIT_ERRORS: i32 = 2;
+/// Exit status when only warnings were found and warnings are denied.
+const EXIT_DENIED_WARNINGS: i32 = 3;
+
+/// Runs the subcommand given by `args` and returns the exit status.
+fn run(args: &ArgMatches) -> HowserResult<i32> {
+    let (issues, mut options) = match args.subcommand() {
         ("check", Some(sub_m)) => {
             let options = vec![CLIOption::VerboseMode(sub_m.is_present("verbose"))];
-            let filename = args.value_of("prescription")
+            let filename = sub_m
+                .value_of("prescription")
                 .ok_or(HowserError::RuntimeError(
                     "Error parsing prescription filename.".to_string(),
                 ))?;
@@ -56,85 +103,316 @@ fn run(args: &ArgMatches) -> HowserResult<()> {
                 .ok_or(HowserError::RuntimeError(
                     "Unable to parse the name of the prescription file.".to_string(),
                 ))?;
-            let document_name = sub_m.value_of("document").ok_or(HowserError::RuntimeError(
-                "Unable to parse the name of the document file.".to_string(),
-            ))?;
+            let document_names: Vec<&str> = sub_m
+                .values_of("document")
+                .ok_or(HowserError::RuntimeError(
+                    "Unable to parse the names of the document files.".to_string(),
+                ))?
+                .collect();
+            let stdin_count = document_names
+                .iter()
+                .chain(Some(&rx_name))
+                .filter(|name| is_stdin(name))
+                .count();
+            if stdin_count > 1 {
+                return Err(HowserError::Usage(
+                    "Only one file can be read from stdin.".to_string(),
+                ));
+            }
+
+            let trace = match sub_m.is_present("trace") {
+                true => Some(sub_m.value_of("trace").unwrap_or("text")),
+                false => None,
+            };
+
+            if sub_m.is_present("watch") {
+                if stdin_count > 0 {
+                    return Err(HowserError::Usage("Stdin cannot be watched.".to_string()));
+                }
+                let mut options = options;
+                options.push(CLIOption::DenyWarnings(args.is_present("deny-warnings")));
+                let mut spec_to_targets = BTreeMap::new();
+                spec_to_targets.insert(
+                    PathBuf::from(rx_name),
+                    document_names.iter().map(PathBuf::from).collect(),
+                );
+                let pharmacy = Pharmacy {
+                    spec_to_targets,
+                    spec_options: BTreeMap::new(),
+                    sources: Vec::new(),
+                };
+                return watch_pharmacy(pharmacy, None, 1, None, None, &options);
+            }
 
-            (validate(rx_name, document_name)?, options)
+            if document_names.len() > 1 {
+                let mut options = options;
+                let deny_warnings = args.is_present("deny-warnings");
+                options.push(CLIOption::DenyWarnings(deny_warnings));
+                let problems = validate_documents(rx_name, &document_names, trace, &options)?;
+                return Ok(exit_status(&problems, deny_warnings));
+            }
+
+            (validate(rx_name, document_names[0], trace, false)?, options)
         }
         ("pharmacy", Some(sub_m)) => match sub_m.subcommand() {
             ("check", Some(sub_m)) => {
                 let options = vec![CLIOption::VerboseMode(sub_m.is_present("verbose"))];
                 let fail_early = sub_m.is_present("fail-early");
-                let filename = sub_m.value_of("pharmacy").ok_or(HowserError::RuntimeError(
-                    "Pharmacy filename could not be parsed from the argument string.".to_string(),
-                ))?;
-                let pharmacy = parse_pharmacy_file(filename)?;
-                (check_pharmacy(&pharmacy, fail_early)?, options)
+                let jobs = parse_jobs(sub_m)?;
+                let filename = pharmacy_file(sub_m);
+                let pharmacy = tagged_pharmacy(Pharmacy::from_file(filename)?, sub_m);
+                (check_pharmacy(&pharmacy, fail_early, jobs)?, options)
             }
-            ("validate", Some(sub_m)) => {
+            ("lint", Some(sub_m)) => {
                 let options = vec![CLIOption::VerboseMode(sub_m.is_present("verbose"))];
+                let filename = pharmacy_file(sub_m);
+                let problems = pharmacy::lint(filename)?
+                    .into_iter()
+                    .map(|problem| Box::new(problem) as ValidationProblem)
+                    .collect();
+                (problems, options)
+            }
+            ("validate", Some(sub_m)) => {
+                let mut options = vec![CLIOption::VerboseMode(sub_m.is_present("verbose"))];
                 let fail_early = sub_m.is_present("fail-early");
-                let filename = sub_m.value_of("pharmacy").ok_or(HowserError::RuntimeError(
-                    "Pharmacy filename could not be parsed from the argument string.".to_string(),
-                ))?;
-                let pharmacy = parse_pharmacy_file(filename)?;
-                (validate_pharmacy(&pharmacy, fail_early)?, options)
+                let jobs = parse_jobs(sub_m)?;
+                let filename = pharmacy_file(sub_m);
+                let mut pharmacy = tagged_pharmacy(Pharmacy::from_file(filename)?, sub_m);
+                if let Some(rev) = sub_m.value_of("changed-since") {
+                    let changed = changed_files(".", rev)?.into_iter().collect();
+                    pharmacy = changed_pharmacy(&pharmacy, &changed)?;
+                }
+
+                if let Some(baseline_name) = sub_m.value_of("write-baseline") {
+                    let count = write_baseline(&pharmacy, baseline_name)?;
+                    options.push(CLIOption::SuccessMessage(format!(
+                        "Recorded {} {} in {}",
+                        count,
+                        match count {
+                            1 => "problem",
+                            _ => "problems",
+                        },
+                        baseline_name
+                    )));
+                    (Vec::new(), options)
+                } else {
+                    let baseline = match sub_m.value_of("baseline") {
+                        Some(baseline_name) => Some(Baseline::from_toml(
+                            baseline_name,
+                            &get_file_contents(baseline_name)?,
+                        )?),
+                        None => None,
+                    };
+                    // Problems recorded for documents that are not validated cannot be fixed.
+                    let baseline = match sub_m.is_present("changed-since") {
+                        true => baseline.map(|baseline| pharmacy_baseline(&pharmacy, &baseline)),
+                        false => baseline,
+                    };
+                    let cache = match sub_m.is_present("no-cache") {
+                        true => None,
+                        false => Some(ValidationCache::open(
+                            sub_m.value_of("cache-dir").unwrap_or(DEFAULT_CACHE_DIR),
+                        )?),
+                    };
+                    if sub_m.is_present("watch") {
+                        options.push(CLIOption::DenyWarnings(args.is_present("deny-warnings")));
+                        return watch_pharmacy(
+                            pharmacy,
+                            Some(filename),
+                            jobs,
+                            baseline,
+                            cache,
+                            &options,
+                        );
+                    }
+                    (
+                        validate_pharmacy(&pharmacy, fail_early, jobs, baseline, cache)?,
+                        options,
+                    )
+                }
             }
             _ => return Err(HowserError::Usage(args.usage().to_string())),
         },
+        ("infer", Some(sub_m)) => {
+            let options = vec![CLIOption::VerboseMode(sub_m.is_present("verbose"))];
+            let document_names: Vec<&str> = sub_m
+                .values_of("document")
+                .ok_or(HowserError::RuntimeError(
+                    "Unable to parse the names of the document files.".to_string(),
+                ))?
+                .collect();
+            let problems = infer(&document_names, sub_m.value_of("output"))?;
+            if problems.is_empty() {
+                return Ok(0);
+            }
+
+            (problems, options)
+        }
+        ("extract", Some(sub_m)) => {
+            let options = vec![CLIOption::VerboseMode(sub_m.is_present("verbose"))];
+            let rx_name = sub_m
+                .value_of("prescription")
+                .ok_or(HowserError::RuntimeError(
+                    "Unable to parse the name of the prescription file.".to_string(),
+                ))?;
+            let document_name = sub_m.value_of("document").ok_or(HowserError::RuntimeError(
+                "Unable to parse the name of the document file.".to_string(),
+            ))?;
+            let format = sub_m.value_of("format").unwrap_or("json");
+            let problems = extract(rx_name, document_name, format)?;
+            if problems.is_empty() {
+                return Ok(0);
+            }
+
+            (problems, options)
+        }
+        ("fix", Some(sub_m)) => {
+            let mut options = vec![CLIOption::VerboseMode(sub_m.is_present("verbose"))];
+            let rx_name = sub_m
+                .value_of("prescription")
+                .ok_or(HowserError::RuntimeError(
+                    "Unable to parse the name of the prescription file.".to_string(),
+                ))?;
+            let document_name = sub_m.value_of("document").ok_or(HowserError::RuntimeError(
+                "Unable to parse the name of the document file.".to_string(),
+            ))?;
+
+            if is_stdin(rx_name) && is_stdin(document_name) {
+                return Err(HowserError::Usage(
+                    "Only one file can be read from stdin.".to_string(),
+                ));
+            }
+            if is_stdin(document_name) && sub_m.is_present("in-place") {
+                return Err(HowserError::Usage(
+                    "A document read from stdin cannot be fixed in place.".to_string(),
+                ));
+            }
+
+            let (problems, placeholders) =
+                fix(rx_name, document_name, sub_m.is_present("in-place"))?;
+            if placeholders > 0 {
+                options.push(CLIOption::SuccessMessage(format!(
+                    "{}Valid once {} inserted {} filled in{}",
+                    color::Fg(color::Yellow),
+                    placeholders,
+                    match placeholders {
+                        1 => "element is",
+                        _ => "elements are",
+                    },
+                    color::Fg(color::Reset)
+                )));
+            }
+
+            (problems, options)
+        }
+        ("scaffold", Some(sub_m)) => {
+            let rx_name = sub_m
+                .value_of("prescription")
+                .ok_or(HowserError::RuntimeError(
+                    "Unable to parse the name of the prescription file.".to_string(),
+                ))?;
+            print!("{}", scaffold(rx_name, sub_m.is_present("include-optional"))?);
+            return Ok(0);
+        }
+        ("explain", Some(sub_m)) => {
+            println!("{}", explain(sub_m.value_of("code"))?);
+            return Ok(0);
+        }
+        ("lsp", Some(sub_m)) => {
+            let pharmacy = match sub_m.value_of("pharmacy") {
+                Some(pharmacy_file) => Some(Pharmacy::from_file(pharmacy_file)?),
+                None if Path::new(DEFAULT_PHARMACY_FILE).is_file() => {
+                    Some(Pharmacy::from_file(DEFAULT_PHARMACY_FILE)?)
+                }
+                // Most crates keep no pharmacy in their manifest, so that is not an error here.
+                None => Pharmacy::from_file(CARGO_MANIFEST).ok(),
+            };
+            let prescriptions = match pharmacy {
+                Some(pharmacy) => document_prescriptions(&pharmacy)?,
+                None => BTreeMap::new(),
+            };
+            let stdin = std::io::stdin();
+            let stdout = std::io::stdout();
+            return Server::new(prescriptions).serve(stdin.lock(), stdout.lock());
+        }
         _ => return Err(HowserError::Usage(args.usage().to_string())),
     };
+    let deny_warnings = args.is_present("deny-warnings");
+    options.push(CLIOption::DenyWarnings(deny_warnings));
     let cli_report = make_cli_report(&issues, &options);
 
     println!("{}", cli_report);
 
-    Ok(())
+    Ok(exit_status(&issues, deny_warnings))
+}
+
+/// Returns the absolute path of the prescription of each document in the pharmacy. Documents
+/// listed under several prescriptions are validated against the first.
+fn document_prescriptions(pharmacy: &Pharmacy) -> HowserResult<BTreeMap<PathBuf, PathBuf>> {
+    let mut prescriptions = BTreeMap::new();
+    for (rx_file, doc_file) in pharmacy.pairs() {
+        let rx_file = absolute_path(rx_file)?;
+        prescriptions
+            .entry(absolute_path(doc_file)?)
+            .or_insert(rx_file);
+    }
+
+    Ok(prescriptions)
 }
 
-fn parse_pharmacy_file(filename: &str) -> HowserResult<Pharmacy> {
-    let pharmacy_contents = get_file_contents(filename)?;
-    parse_pharmacy_string(filename, pharmacy_contents)
+/// Returns the exit status for a run that reported the given problems.
+///
+/// Warnings alone leave the status at zero unless they are denied.
+fn exit_status(issues: &[ValidationProblem], deny_warnings: bool) -> i32 {
+    if has_errors(issues) {
+        EXIT_ERRORS
+    } else if deny_warnings && !issues.is_empty() {
+        EXIT_DENIED_WARNINGS
+    } else {
+        0
+    }
 }
 
-fn parse_pharmacy_string(filename: &str, pharmacy_file_contents: String) -> HowserResult<Pharmacy> {
-    let pharmacy = pharmacy_file_contents.parse::<Value>()?;
-    let ref specs = pharmacy["Specs"];
-    let prescription_pairs = specs.as_table().ok_or(HowserError::RuntimeError(format!(
-        "Error parsing pharmacy file {}.",
-        filename
-    )))?;
-    let mut spec_to_targets: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
-    for (key, value) in prescription_pairs {
-        let targets: Vec<PathBuf> = match *value {
-            Value::String(ref s) => vec![PathBuf::from(s)],
-            Value::Array(ref array) => array
-                .iter()
-                .map(|a| {
-                    if let Some(s) = a.as_str() {
-                        Ok(PathBuf::from(s))
-                    } else {
-                        Err(HowserError::RuntimeError(
-                        format!("Error parsing pharmacy file {}. Target value was not a string or array of strings", filename)))
-                    }
-                })
-                .collect::<Result<Vec<PathBuf>, HowserError>>()?,
-            _ => return Err(HowserError::RuntimeError(format!(
-                "Error parsing pharmacy file {}. Target value was not a string or array of strings",
-                filename
-            ))),
-        };
-        spec_to_targets.insert(PathBuf::from(key), targets);
+/// Determines if any of the given problems are errors rather than warnings.
+fn has_errors(problems: &[ValidationProblem]) -> bool {
+    problems
+        .iter()
+        .any(|problem| problem.severity() == Severity::Error)
+}
+
+/// Returns whether any of `problems` is an error not recorded in `known`, where `entries` are the
+/// baseline fingerprints of the problems.
+fn has_new_errors(
+    problems: &[ValidationProblem],
+    entries: &[BaselineEntry],
+    known: Option<&Baseline>,
+) -> bool {
+    problems.iter().enumerate().any(|(index, problem)| {
+        problem.severity() == Severity::Error && match known {
+            Some(known) => !known.contains(&entries[index]),
+            None => true,
+        }
+    })
+}
+
+/// Returns the part of `pharmacy` selected by the tags given to a pharmacy subcommand, if any.
+fn tagged_pharmacy(pharmacy: Pharmacy, args: &ArgMatches) -> Pharmacy {
+    match args.values_of("tag") {
+        Some(tags) => pharmacy.tagged(&tags.collect::<Vec<&str>>()),
+        None => pharmacy,
     }
-    Ok(Pharmacy { spec_to_targets })
 }
 
-/// Named wrapper around the mapping between the file locations
-/// for Rx spec files and their associated target documents intended
-/// for validation.
-#[derive(Clone, Debug, PartialEq)]
-struct Pharmacy {
-    spec_to_targets: BTreeMap<PathBuf, Vec<PathBuf>>,
+/// Parses the number of worker threads requested for a pharmacy subcommand.
+fn parse_jobs(args: &ArgMatches) -> HowserResult<usize> {
+    let jobs = args.value_of("jobs").unwrap_or("1");
+    match jobs.parse::<usize>() {
+        Ok(jobs) if jobs > 0 => Ok(jobs),
+        _ => Err(HowserError::Usage(format!(
+            "The number of jobs must be a positive integer, not '{}'.",
+            jobs
+        ))),
+    }
 }
 
 fn make_app<'a, 'b>() -> App<'a, 'b> {
@@ -165,6 +443,23 @@ fn make_app<'a, 'b>() -> App<'a, 'b> {
                 .long("verbose")
                 .help("Use verbose (multiline) output for errors and warnings."),
         )
+        .arg(
+            Arg::with_name("trace")
+                .long("trace")
+                .help("Print how prescription elements were aligned with the document to stderr.")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["text", "json"])
+                .value_name("FORMAT"),
+        )
+        .arg(
+            Arg::with_name("watch")
+                .short("w")
+                .long("watch")
+                .help("Revalidate whenever the prescription or the documents change.")
+                .conflicts_with("trace"),
+        )
         .arg(
             Arg::with_name("prescription")
                 .required_unless("pharmacy")
@@ -173,18 +468,21 @@ fn make_app<'a, 'b>() -> App<'a, 'b> {
         )
         .arg(
             Arg::with_name("document")
+                .help("Documents to validate, or - to read one from stdin.")
                 .required_unless("pharmacy")
                 .takes_value(true)
+                .multiple(true)
                 .value_name("DOCUMENT"),
         );
     let pharmacy_check = SubCommand::with_name("check")
         .about("Verifies that all the .rx files in the pharmacy file conform to the Rx spec.")
         .help_message("Prints help information.")
-        .setting(AppSettings::ArgRequiredElseHelp)
         .arg(
             Arg::with_name("pharmacy")
-                .required(true)
-                .help("The .toml file containing the documents to process.")
+                .help(
+                    "The .toml file containing the documents to process. Defaults to \
+                     Pharmacy.toml if it exists, or else Cargo.toml.",
+                )
                 .takes_value(true)
                 .value_name("PHARMACY"),
         )
@@ -199,15 +497,48 @@ fn make_app<'a, 'b>() -> App<'a, 'b> {
                 .short("-e")
                 .long("fail-early")
                 .help("Stop processing and exit after the first error."),
+        )
+        .arg(
+            Arg::with_name("jobs")
+                .short("j")
+                .long("jobs")
+                .help("Process this many files at once. Defaults to 1.")
+                .takes_value(true)
+                .value_name("N"),
+        )
+        .arg(
+            Arg::with_name("tag")
+                .long("tag")
+                .help("Only process the entries with this tag. Can be given more than once.")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("TAG"),
+        );
+    let pharmacy_lint = SubCommand::with_name("lint")
+        .about("Checks the pharmacy file itself, and that every file it names exists.")
+        .help_message("Prints help information.")
+        .arg(
+            Arg::with_name("pharmacy")
+                .help(
+                    "The .toml file to check. Defaults to Pharmacy.toml if it exists, or else \
+                     Cargo.toml.",
+                )
+                .takes_value(true)
+                .value_name("PHARMACY"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .help("Use verbose (multiline) output for errors and warnings."),
         );
     let pharmacy_validate = SubCommand::with_name("validate")
         .about("Validates all the Markdown document and .rx Prescription file pairs in the pharmacy file.")
         .help_message("Prints help information.")
-        .setting(AppSettings::ArgRequiredElseHelp)
         .arg(
             Arg::with_name("pharmacy")
-                .required(true)
-                .help("The .toml file containing the documents to process.")
+                .help("The .toml file containing the documents to process. Defaults to Pharmacy.toml if it exists, or else Cargo.toml.")
                 .value_name("PHARMACY")
         )
         .arg(
@@ -216,11 +547,200 @@ fn make_app<'a, 'b>() -> App<'a, 'b> {
                 .long("fail-early")
                 .help("Stop processing and exit after the first error.")
         )
+        .arg(
+            Arg::with_name("jobs")
+                .short("j")
+                .long("jobs")
+                .help("Process this many files at once. Defaults to 1.")
+                .takes_value(true)
+                .value_name("N"),
+        )
+        .arg(
+            Arg::with_name("tag")
+                .long("tag")
+                .help("Only process the entries with this tag. Can be given more than once.")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("TAG"),
+        )
+        .arg(
+            Arg::with_name("baseline")
+                .long("baseline")
+                .help("Only report problems that are not recorded in this baseline file.")
+                .takes_value(true)
+                .value_name("FILE")
+        )
+        .arg(
+            Arg::with_name("write-baseline")
+                .long("write-baseline")
+                .help("Record the current problems in this baseline file instead of reporting them.")
+                .takes_value(true)
+                .conflicts_with("baseline")
+                .value_name("FILE")
+        )
+        .arg(
+            Arg::with_name("cache-dir")
+                .long("cache-dir")
+                .help("Store validation results in this directory. Defaults to .howser-cache.")
+                .takes_value(true)
+                .value_name("DIR")
+        )
+        .arg(
+            Arg::with_name("no-cache")
+                .long("no-cache")
+                .help("Validate every document instead of replaying cached results.")
+                .conflicts_with("cache-dir")
+        )
+        .arg(
+            Arg::with_name("changed-since")
+                .long("changed-since")
+                .help("Only validate documents affected by changes since this git revision.")
+                .takes_value(true)
+                .conflicts_with("write-baseline")
+                .value_name("REV")
+        )
+        .arg(
+            Arg::with_name("watch")
+                .short("w")
+                .long("watch")
+                .help("Revalidate documents whenever the files involved change.")
+                .conflicts_with_all(&["fail-early", "write-baseline", "changed-since"])
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .help("Use verbose (multiline) output for errors and warnings."),
+        );
+    let infer = SubCommand::with_name("infer")
+        .about("Infers a draft .rx Prescription file from example Markdown documents.")
+        .help_message("Prints help information.")
+        .setting(AppSettings::ArgRequiredElseHelp)
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .help("Use verbose (multiline) output for errors and warnings."),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .help("Write the draft prescription to this file instead of stdout.")
+                .takes_value(true)
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::with_name("document")
+                .required(true)
+                .multiple(true)
+                .help("Example documents that share the shape to describe")
+                .takes_value(true)
+                .value_name("DOCUMENT"),
+        );
+    let scaffold = SubCommand::with_name("scaffold")
+        .about("Generates a skeleton Markdown document that conforms to an .rx Prescription file.")
+        .help_message("Prints help information.")
+        .setting(AppSettings::ArgRequiredElseHelp)
+        .arg(
+            Arg::with_name("include-optional")
+                .short("o")
+                .long("include-optional")
+                .help("Include optional elements in the skeleton document."),
+        )
+        .arg(
+            Arg::with_name("prescription")
+                .required(true)
+                .help("Prescription file to scaffold from")
+                .takes_value(true)
+                .value_name("PRESCRIPTION"),
+        );
+    let extract = SubCommand::with_name("extract")
+        .about("Extracts the prompted content of a Markdown document as JSON or TOML.")
+        .help_message("Prints help information.")
+        .setting(AppSettings::ArgRequiredElseHelp)
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .help("Use verbose (multiline) output for errors and warnings."),
+        )
+        .arg(
+            Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .help("Format of the extracted values.")
+                .takes_value(true)
+                .possible_values(&["json", "toml"])
+                .default_value("json")
+                .value_name("FORMAT"),
+        )
+        .arg(
+            Arg::with_name("prescription")
+                .required(true)
+                .help("Prescription file describing the document")
+                .takes_value(true)
+                .value_name("PRESCRIPTION"),
+        )
+        .arg(
+            Arg::with_name("document")
+                .required(true)
+                .help("Document to extract from")
+                .takes_value(true)
+                .value_name("DOCUMENT"),
+        );
+    let fix = SubCommand::with_name("fix")
+        .about("Repairs mechanical problems in a Markdown document, showing the changes as a diff.")
+        .help_message("Prints help information.")
+        .setting(AppSettings::ArgRequiredElseHelp)
         .arg(
             Arg::with_name("verbose")
                 .short("v")
                 .long("verbose")
                 .help("Use verbose (multiline) output for errors and warnings."),
+        )
+        .arg(
+            Arg::with_name("in-place")
+                .short("i")
+                .long("in-place")
+                .help("Write the repaired document back to its file instead of printing a diff."),
+        )
+        .arg(
+            Arg::with_name("prescription")
+                .required(true)
+                .help("Prescription file describing the document")
+                .takes_value(true)
+                .value_name("PRESCRIPTION"),
+        )
+        .arg(
+            Arg::with_name("document")
+                .required(true)
+                .help("Document to repair")
+                .takes_value(true)
+                .value_name("DOCUMENT"),
+        );
+    let explain = SubCommand::with_name("explain")
+        .about("Explains an error code, or lists all error codes if none is given.")
+        .help_message("Prints help information.")
+        .arg(
+            Arg::with_name("code")
+                .help("Error code to explain, such as HX0001.")
+                .takes_value(true)
+                .value_name("CODE"),
+        );
+    let lsp = SubCommand::with_name("lsp")
+        .about("Runs a Language Server Protocol server on stdin and stdout.")
+        .help_message("Prints help information.")
+        .arg(
+            Arg::with_name("pharmacy")
+                .long("pharmacy")
+                .help(
+                    "The .toml file that pairs documents with their prescriptions. Defaults to \
+                     Pharmacy.toml if it exists, or else the metadata of Cargo.toml.",
+                )
+                .takes_value(true)
+                .value_name("PHARMACY"),
         );
 
     App::new("Howser")
@@ -231,6 +751,12 @@ fn make_app<'a, 'b>() -> App<'a, 'b> {
         .setting(AppSettings::SubcommandRequiredElseHelp)
         .setting(AppSettings::VersionlessSubcommands)
         .setting(AppSettings::DisableHelpSubcommand)
+        .arg(
+            Arg::with_name("deny-warnings")
+                .long("deny-warnings")
+                .global(true)
+                .help("Treat warnings as errors when deciding the exit status."),
+        )
         .subcommand(check.clone())
         .subcommand(validate.clone())
         .subcommand(
@@ -241,81 +767,657 @@ fn make_app<'a, 'b>() -> App<'a, 'b> {
                 .setting(AppSettings::DisableHelpSubcommand)
                 .setting(AppSettings::VersionlessSubcommands)
                 .subcommand(pharmacy_check)
+                .subcommand(pharmacy_lint)
                 .subcommand(pharmacy_validate),
         )
+        .subcommand(infer)
+        .subcommand(extract)
+        .subcommand(fix)
+        .subcommand(scaffold)
+        .subcommand(explain)
+        .subcommand(lsp)
 }
 
 fn validate<P: AsRef<Path>, Q: AsRef<Path>>(
     rx_name: P,
     document_name: Q,
+    trace: Option<&str>,
+    allow_extra_content: bool,
 ) -> HowserResult<Vec<ValidationProblem>> {
-    let rx_root = parse_document(&get_file_contents(&rx_name)?);
-    let doc_root = parse_document(&get_file_contents(&document_name)?);
-    let rx = Document::new(&rx_root, rx_name.as_ref().to_str().map(|s| s.to_string()))?
-        .into_prescription()?;
-    let document = Document::new(
-        &doc_root,
-        document_name.as_ref().to_str().map(|s| s.to_string()),
-    )?;
+    validate_against(
+        read_prescription(rx_name)?,
+        document_name,
+        trace,
+        allow_extra_content,
+    )
+}
 
-    Validator::new(rx, document).validate()
+/// Reads and parses the indicated prescription file, or stdin for `-`.
+fn read_prescription<P: AsRef<Path>>(rx_name: P) -> HowserResult<Prescription> {
+    Prescription::from_text(&get_file_contents(&rx_name)?, source_name(&rx_name))
 }
 
-fn check<P: AsRef<Path>>(filename: P) -> HowserResult<Vec<ValidationProblem>> {
-    let rx_root = parse_document(&get_file_contents(&filename)?);
-    let document = Document::new(&rx_root, filename.as_ref().to_str().map(|s| s.to_string()))?;
+/// Validates the indicated document against a prescription that has already been parsed.
+fn validate_against<Q: AsRef<Path>>(
+    rx: Prescription,
+    document_name: Q,
+    trace: Option<&str>,
+    allow_extra_content: bool,
+) -> HowserResult<Vec<ValidationProblem>> {
+    let document = Document::from_text(
+        &get_file_contents(&document_name)?,
+        source_name(&document_name),
+    )?;
+
+    let validator = Validator::new(rx, document).with_extra_content_allowed(allow_extra_content);
+    let problems = validator.validate()?;
 
-    match document.into_prescription() {
-        Err(HowserError::PrescriptionError(warning)) => Ok(vec![Box::new(warning)]),
-        Err(error) => Err(error),
-        Ok(_) => Ok(Vec::new()),
+    if let Some(format) = trace {
+        let steps = validator.alignment();
+        let report = match format {
+            "json" => make_alignment_json(&steps, validator.prescription(), validator.document()),
+            _ => make_alignment_report(&steps, validator.prescription(), validator.document()),
+        };
+        eprintln!("{}", report);
     }
+
+    Ok(problems)
 }
 
-fn check_pharmacy(pharmacy: &Pharmacy, fail_early: bool) -> HowserResult<Vec<ValidationProblem>> {
-    let mut report: Vec<ValidationProblem> = Vec::new();
+/// Validates several documents against one prescription, printing a report for each document
+/// followed by a summary, and returns the problems found in all of them.
+fn validate_documents(
+    rx_name: &str,
+    document_names: &[&str],
+    trace: Option<&str>,
+    options: &Vec<CLIOption>,
+) -> HowserResult<Vec<ValidationProblem>> {
+    let deny_warnings = options.iter().any(|option| match option {
+        &CLIOption::DenyWarnings(deny) => deny,
+        _ => false,
+    });
+    let rx = read_prescription(rx_name)?;
+    let mut problems = Vec::new();
+    let mut invalid_count = 0;
 
-    for rx_file in pharmacy.spec_to_targets.keys() {
-        let mut problems = check(&rx_file)?;
-        if fail_early && !problems.is_empty() {
-            return Ok(problems);
-        } else {
-            report.append(&mut problems);
+    for document_name in document_names {
+        let mut found = validate_against(rx.clone(), document_name, trace, false)?;
+        println!(
+            "{}{}{}",
+            style::Bold,
+            source_name(document_name).unwrap_or_default(),
+            style::Reset
+        );
+        println!("{}\n", make_cli_report(&found, options));
+        if exit_status(&found, deny_warnings) != 0 {
+            invalid_count += 1;
         }
+        problems.append(&mut found);
     }
 
-    Ok(report)
+    println!(
+        "{} documents validated :: {} valid, {} invalid",
+        document_names.len(),
+        document_names.len() - invalid_count,
+        invalid_count
+    );
+
+    Ok(problems)
 }
 
-fn validate_pharmacy(
-    pharmacy: &Pharmacy,
-    fail_early: bool,
+fn explain(code: Option<&str>) -> HowserResult<String> {
+    match code {
+        Some(code) => {
+            let error_code = ErrorCode::parse(code)
+                .ok_or(HowserError::Usage(format!("Unknown error code '{}'.", code)))?;
+            Ok(format!(
+                "{}: {}\n\n{}",
+                error_code,
+                error_code.summary(),
+                error_code.explanation()
+            ))
+        }
+        None => Ok(ErrorCode::ALL
+            .iter()
+            .map(|error_code| format!("{}  {}", error_code, error_code.summary()))
+            .collect::<Vec<String>>()
+            .join("\n")),
+    }
+}
+
+fn infer<P: AsRef<Path>>(
+    filenames: &[P],
+    output: Option<&str>,
 ) -> HowserResult<Vec<ValidationProblem>> {
-    let mut report: Vec<ValidationProblem> = Vec::new();
+    let mut documents = Vec::new();
+    for filename in filenames {
+        documents.push(Document::from_text(
+            &get_file_contents(filename)?,
+            source_name(filename),
+        )?);
+    }
 
-    for (rx_file, target_docs) in pharmacy.spec_to_targets.iter() {
-        for doc_file in target_docs {
-            let mut problems = validate(&rx_file, doc_file)?;
-            if fail_early && !problems.is_empty() {
-                return Ok(problems);
-            } else {
-                report.append(&mut problems);
-            }
+    let draft = infer_prescription(&documents)?;
+    let rx_name = output.unwrap_or("<inferred>").to_string();
+    let mut problems = Vec::new();
+    for document in documents {
+        let prescription = Prescription::from_text(&draft, Some(rx_name.clone()))?;
+        problems.append(&mut Validator::new(prescription, document).validate()?);
+    }
+
+    if !has_errors(&problems) {
+        match output {
+            Some(filename) => File::create(filename)?.write_all(draft.as_bytes())?,
+            None => print!("{}", draft),
         }
     }
 
-    Ok(report)
+    Ok(problems)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::clap::ErrorKind;
-    use super::{parse_pharmacy_string, HowserError, Pharmacy};
-    use std::collections::BTreeMap;
-    use std::path::PathBuf;
-
-    #[test]
-    fn test_validate_subcommand() {
+fn extract<P: AsRef<Path>, Q: AsRef<Path>>(
+    rx_name: P,
+    document_name: Q,
+    format: &str,
+) -> HowserResult<Vec<ValidationProblem>> {
+    let rx = Prescription::from_text(
+        &get_file_contents(&rx_name)?,
+        source_name(&rx_name),
+    )?;
+    let document = Document::from_text(
+        &get_file_contents(&document_name)?,
+        source_name(&document_name),
+    )?;
+    let validator = Validator::new(rx, document);
+    let problems = validator.validate()?;
+
+    if !has_errors(&problems) {
+        let values = Extractor::new(&validator).extract()?;
+        let output = match format {
+            "toml" => toml::to_string(&values)
+                .map_err(|e| HowserError::RuntimeError(e.description().to_string()))?,
+            _ => serde_json::to_string_pretty(&values)
+                .map_err(|e| HowserError::RuntimeError(e.description().to_string()))?,
+        };
+        println!("{}", output.trim_right());
+    }
+
+    Ok(problems)
+}
+
+fn fix<P: AsRef<Path>, Q: AsRef<Path>>(
+    rx_name: P,
+    document_name: Q,
+    in_place: bool,
+) -> HowserResult<(Vec<ValidationProblem>, usize)> {
+    let document_string = source_name(&document_name).unwrap_or_default();
+    let text = get_file_contents(&document_name)?;
+    let result = fix_document(
+        &get_file_contents(&rx_name)?,
+        source_name(&rx_name),
+        &text,
+        Some(document_string.clone()),
+    )?;
+
+    match in_place {
+        true if result.source != text => {
+            File::create(&document_name)?.write_all(result.source.as_bytes())?
+        }
+        true => (),
+        false => {
+            let diff = unified_diff(
+                &text,
+                &result.source,
+                &format!("a/{}", document_string),
+                &format!("b/{}", document_string),
+            );
+            if !diff.is_empty() {
+                println!("{}\n", diff);
+            }
+        }
+    }
+
+    for fix in &result.fixes {
+        let needs_content = match fix.needs_content {
+            true => " :: needs content",
+            false => "",
+        };
+        println!(
+            "{} line {} :: {}{}",
+            document_string, fix.line, fix.description, needs_content
+        );
+    }
+    if !result.fixes.is_empty() {
+        println!();
+    }
+
+    let placeholders = result.fixes.iter().filter(|fix| fix.needs_content).count();
+    Ok((result.problems, placeholders))
+}
+
+fn scaffold<P: AsRef<Path>>(filename: P, include_optional: bool) -> HowserResult<String> {
+    let prescription = Prescription::from_text(
+        &get_file_contents(&filename)?,
+        source_name(filename),
+    )?;
+
+    Scaffolder::new(&prescription, include_optional).scaffold()
+}
+
+fn check<P: AsRef<Path>>(filename: P) -> HowserResult<Vec<ValidationProblem>> {
+    let document = Document::from_text(
+        &get_file_contents(&filename)?,
+        source_name(filename),
+    )?;
+
+    Ok(lint(&document)?
+        .into_iter()
+        .map(|warning| Box::new(warning) as ValidationProblem)
+        .collect())
+}
+
+fn check_pharmacy(
+    pharmacy: &Pharmacy,
+    fail_early: bool,
+    jobs: usize,
+) -> HowserResult<Vec<ValidationProblem>> {
+    let mut report: Vec<ValidationProblem> = Vec::new();
+    let rx_files: Vec<PathBuf> = pharmacy.spec_to_targets.keys().cloned().collect();
+    let pharmacy = pharmacy.clone();
+    let results = map_ordered(
+        rx_files,
+        jobs,
+        move |rx_file| {
+            get_file_contents(rx_file)
+                .and_then(|rx_text| pharmacy.check_prescription(rx_file, &rx_text))
+        },
+        move |result| match result {
+            &Ok(ref problems) => fail_early && has_errors(problems),
+            &Err(_) => true,
+        },
+    );
+
+    for result in results.into_iter().filter_map(|result| result) {
+        let mut problems = result?;
+        let is_final = fail_early && has_errors(&problems);
+        report.append(&mut problems);
+        if is_final {
+            break;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Validates the documents of `pharmacy`, stopping at the first document with errors under
+/// `fail_early`.
+///
+/// An entry that fails early only stops the validation of its own remaining documents.
+fn validate_pharmacy(
+    pharmacy: &Pharmacy,
+    fail_early: bool,
+    jobs: usize,
+    mut baseline: Option<Baseline>,
+    cache: Option<ValidationCache>,
+) -> HowserResult<Vec<ValidationProblem>> {
+    let mut report: Vec<ValidationProblem> = Vec::new();
+    let pairs = pharmacy.pairs();
+    let is_baselined = baseline.is_some();
+    let known = baseline.clone();
+    let pharmacy = pharmacy.clone();
+    // Prescriptions whose entries have failed early, so their remaining pairs are skipped.
+    let stopped = Arc::new(Mutex::new(BTreeSet::new()));
+    let results = map_ordered(
+        pairs.clone(),
+        jobs,
+        move |&(ref rx_file, ref doc_file)| {
+            if stopped.lock().unwrap().contains(rx_file) {
+                return None;
+            }
+
+            let options = pharmacy.options(rx_file);
+            let result = validate_pair(rx_file, doc_file, &options, is_baselined, cache.as_ref())
+                .map(|(problems, entries)| {
+                    let failed = has_new_errors(&problems, &entries, known.as_ref());
+                    (problems, entries, failed, options.fail_early)
+                });
+            if let Ok((_, _, true, true)) = result {
+                stopped.lock().unwrap().insert(rx_file.clone());
+            }
+
+            Some(result)
+        },
+        move |result| match result {
+            &Some(Ok((_, _, failed, _))) => fail_early && failed,
+            &Some(Err(_)) => true,
+            &None => false,
+        },
+    );
+
+    let mut failed_entries = BTreeSet::new();
+    let mut validated = BTreeSet::new();
+    for (&(ref rx_file, ref doc_file), result) in pairs.iter().zip(results) {
+        let (problems, entries, failed, entry_fail_early) = match result {
+            Some(Some(result)) => result?,
+            Some(None) => continue,
+            None => break,
+        };
+        // Documents started before their entry failed early are still dropped.
+        if failed_entries.contains(rx_file) {
+            continue;
+        }
+
+        validated.insert(doc_file.to_string_lossy().to_string());
+        let mut problems: Vec<ValidationProblem> = match baseline {
+            Some(ref mut baseline) => problems
+                .into_iter()
+                .zip(entries)
+                .filter(|&(_, ref entry)| !baseline.take(entry))
+                .map(|(problem, _)| problem)
+                .collect(),
+            None => problems,
+        };
+        report.append(&mut problems);
+        if failed && fail_early {
+            break;
+        } else if failed && entry_fail_early {
+            failed_entries.insert(rx_file.clone());
+        }
+    }
+
+    if let Some(baseline) = baseline {
+        // Documents left unvalidated by failing early may still have their recorded problems.
+        let fixed: Vec<&BaselineEntry> = baseline
+            .entries()
+            .iter()
+            .filter(|entry| validated.contains(&entry.document))
+            .collect();
+        for entry in &fixed {
+            println!("Fixed since the baseline :: {}", entry);
+        }
+        if !fixed.is_empty() {
+            println!("Rewrite the baseline with --write-baseline to drop fixed problems.\n");
+        }
+    }
+
+    Ok(report)
+}
+
+/// Validates one document of a pharmacy under the options of its entry, along with the baseline
+/// fingerprints of its problems if `is_baselined` is set.
+///
+/// Results are replayed from `cache` when neither file has changed since they were stored.
+fn validate_pair(
+    rx_file: &Path,
+    doc_file: &Path,
+    options: &SpecOptions,
+    is_baselined: bool,
+    cache: Option<&ValidationCache>,
+) -> HowserResult<(Vec<ValidationProblem>, Vec<BaselineEntry>)> {
+    let rx_source = get_file_contents(rx_file)?;
+    let doc_source = get_file_contents(doc_file)?;
+    let doc_name = doc_file.to_string_lossy();
+    let allow_extra_content = options.allow_extra_content;
+    let validate_document = || {
+        let rx = Prescription::from_text(&rx_source, source_name(rx_file))?;
+        pharmacy::validate(rx, doc_file, &doc_source, allow_extra_content)
+    };
+    let problems = match cache {
+        Some(cache) => {
+            let key = ValidationCache::key(
+                &rx_file.to_string_lossy(),
+                &rx_source,
+                &doc_name,
+                &doc_source,
+                match allow_extra_content {
+                    true => "allow-extra-content",
+                    false => "",
+                },
+            );
+            match cache.get(&key) {
+                Some(problems) => problems,
+                None => {
+                    let problems = validate_document()?;
+                    cache.put(&key, &problems)?;
+                    problems
+                }
+            }
+        }
+        None => validate_document()?,
+    };
+    let problems = options.apply(problems);
+    let entries = match is_baselined {
+        true => problems
+            .iter()
+            .map(|problem| BaselineEntry::new(problem, &rx_source, &doc_name))
+            .collect(),
+        false => Vec::new(),
+    };
+
+    Ok((problems, entries))
+}
+
+/// Returns the part of the pharmacy affected by changes to the files at the `changed` absolute
+/// paths.
+///
+/// Pairs are kept when their document changed. Every pair of a prescription that changed is kept.
+fn changed_pharmacy(pharmacy: &Pharmacy, changed: &BTreeSet<PathBuf>) -> HowserResult<Pharmacy> {
+    let mut spec_to_targets = BTreeMap::new();
+
+    for (rx_file, target_docs) in pharmacy.spec_to_targets.iter() {
+        let rx_changed = changed.contains(&absolute_path(rx_file)?);
+        let mut targets = Vec::new();
+        for doc_file in target_docs {
+            if rx_changed || changed.contains(&absolute_path(doc_file)?) {
+                targets.push(doc_file.clone());
+            }
+        }
+        if !targets.is_empty() {
+            spec_to_targets.insert(rx_file.clone(), targets);
+        }
+    }
+
+    Ok(Pharmacy {
+        spec_to_targets,
+        spec_options: pharmacy.spec_options.clone(),
+        sources: pharmacy.sources.clone(),
+    })
+}
+
+/// Returns the entries of `baseline` that were recorded for documents in the pharmacy.
+fn pharmacy_baseline(pharmacy: &Pharmacy, baseline: &Baseline) -> Baseline {
+    let documents: BTreeSet<String> = pharmacy.pairs()
+        .iter()
+        .map(|&(_, ref doc_file)| doc_file.to_string_lossy().to_string())
+        .collect();
+
+    Baseline::new(
+        baseline
+            .entries()
+            .iter()
+            .filter(|entry| documents.contains(&entry.document))
+            .cloned()
+            .collect(),
+    )
+}
+
+/// The result of validating one pair, along with the baseline fingerprints of its problems.
+type PairResult = HowserResult<(Vec<ValidationProblem>, Vec<BaselineEntry>)>;
+
+/// Validates the documents of `pharmacy`, then revalidates them whenever the files involved
+/// change, redrawing the report each time.
+///
+/// Only the pairs involving a changed file are revalidated, unless the pharmacy file itself
+/// changes. Runs until interrupted.
+fn watch_pharmacy(
+    mut pharmacy: Pharmacy,
+    pharmacy_file: Option<&str>,
+    jobs: usize,
+    baseline: Option<Baseline>,
+    cache: Option<ValidationCache>,
+    options: &Vec<CLIOption>,
+) -> HowserResult<i32> {
+    let is_baselined = baseline.is_some();
+    let mut pairs = pharmacy.pairs();
+    let mut results = validate_pairs(&pharmacy, pairs.clone(), jobs, is_baselined, &cache);
+    let mut watcher = FileWatcher::new(&watched_files(&pharmacy, &pairs));
+    let mut notice = None;
+
+    loop {
+        draw_watch_report(&results, baseline.clone(), options, &notice)?;
+        let changed = wait_for_changes(&mut watcher);
+        notice = None;
+
+        let pharmacy_changed = changed.iter().any(|path| pharmacy.sources.contains(path));
+        if let (true, Some(filename)) = (pharmacy_changed, pharmacy_file) {
+            match Pharmacy::from_file(filename) {
+                Ok(reloaded) => {
+                    pharmacy = reloaded;
+                    pairs = pharmacy.pairs();
+                    results = validate_pairs(&pharmacy, pairs.clone(), jobs, is_baselined, &cache);
+                    watcher.set_paths(&watched_files(&pharmacy, &pairs));
+                }
+                Err(e) => notice = Some(error_message(&e)),
+            }
+            continue;
+        }
+
+        let affected: Vec<usize> = pairs
+            .iter()
+            .enumerate()
+            .filter(|&(_, &(ref rx_file, ref doc_file))| {
+                changed.contains(rx_file) || changed.contains(doc_file)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        let updated = validate_pairs(
+            &pharmacy,
+            affected.iter().map(|&index| pairs[index].clone()).collect(),
+            jobs,
+            is_baselined,
+            &cache,
+        );
+        for (index, result) in affected.into_iter().zip(updated) {
+            results[index] = result;
+        }
+    }
+}
+
+/// Validates every pair of `pharmacy` in `pairs`, in order.
+fn validate_pairs(
+    pharmacy: &Pharmacy,
+    pairs: Vec<(PathBuf, PathBuf)>,
+    jobs: usize,
+    is_baselined: bool,
+    cache: &Option<ValidationCache>,
+) -> Vec<PairResult> {
+    let pharmacy = pharmacy.clone();
+    let cache = cache.clone();
+    map_ordered(
+        pairs,
+        jobs,
+        move |&(ref rx_file, ref doc_file)| {
+            let options = pharmacy.options(rx_file);
+            validate_pair(rx_file, doc_file, &options, is_baselined, cache.as_ref())
+        },
+        |_| false,
+    ).into_iter()
+        .filter_map(|result| result)
+        .collect()
+}
+
+/// Returns every file involved in validating the pairs, along with the files `pharmacy` was read
+/// from.
+fn watched_files(pharmacy: &Pharmacy, pairs: &[(PathBuf, PathBuf)]) -> Vec<PathBuf> {
+    let mut files = pharmacy.sources.clone();
+    for &(ref rx_file, ref doc_file) in pairs {
+        files.push(rx_file.clone());
+        files.push(doc_file.clone());
+    }
+
+    files
+}
+
+/// Blocks until at least one watched file changes, returning the changed files.
+fn wait_for_changes(watcher: &mut FileWatcher) -> Vec<PathBuf> {
+    loop {
+        thread::sleep(Duration::from_millis(WATCH_INTERVAL_MS));
+        let changed = watcher.changed();
+        if !changed.is_empty() {
+            return changed;
+        }
+    }
+}
+
+/// Clears the terminal and reports the latest results of watched pairs.
+fn draw_watch_report(
+    results: &[PairResult],
+    mut baseline: Option<Baseline>,
+    options: &Vec<CLIOption>,
+    notice: &Option<String>,
+) -> HowserResult<()> {
+    let mut failures = Vec::new();
+    let mut problems: Vec<ValidationProblem> = Vec::new();
+    for result in results {
+        match result {
+            &Ok((ref found, ref entries)) => {
+                for (index, problem) in found.iter().enumerate() {
+                    let is_known = match baseline {
+                        Some(ref mut baseline) => baseline.take(&entries[index]),
+                        None => false,
+                    };
+                    if !is_known {
+                        // Results are kept for later redraws, so copies are reported.
+                        problems.push(Box::new(CachedProblem::new(problem)));
+                    }
+                }
+            }
+            &Err(ref e) => failures.push(error_message(e)),
+        }
+    }
+
+    print!("{}{}", clear::All, cursor::Goto(1, 1));
+    for message in notice.iter().chain(failures.iter()) {
+        println!("{}\n", message);
+    }
+    println!("{}\n", make_cli_report(&problems, options));
+    println!("Watching for changes. Press Ctrl-C to stop.");
+    std::io::stdout().flush()?;
+
+    Ok(())
+}
+
+/// Records the current problems of every document in the pharmacy, returning how many there were.
+fn write_baseline(pharmacy: &Pharmacy, baseline_name: &str) -> HowserResult<usize> {
+    let mut entries = Vec::new();
+
+    for (rx_file, target_docs) in pharmacy.spec_to_targets.iter() {
+        let rx_source = get_file_contents(&rx_file)?;
+        let rx = Prescription::from_text(&rx_source, source_name(rx_file))?;
+        for doc_file in target_docs {
+            let doc_name = doc_file.to_string_lossy();
+            let doc_source = get_file_contents(doc_file)?;
+            for problem in pharmacy.validate_document(rx_file, rx.clone(), doc_file, &doc_source)? {
+                entries.push(BaselineEntry::new(&problem, &rx_source, &doc_name));
+            }
+        }
+    }
+
+    let count = entries.len();
+    File::create(baseline_name)?.write_all(Baseline::new(entries).to_toml()?.as_bytes())?;
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::clap::ErrorKind;
+    use super::{HowserError, Pharmacy};
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_validate_subcommand() {
         let app = super::make_app();
         let matches =
             app.get_matches_from(vec!["howser", "validate", "some_template", "some_document"]);
@@ -351,19 +1453,47 @@ mod tests {
     }
 
     #[test]
-    fn test_validate_subcommand_prevents_extra_args() {
+    fn test_validate_subcommand_has_trace_format() {
         let app = super::make_app();
-        if let Err(e) = app.get_matches_from_safe(vec![
+        let matches = app.get_matches_from(vec![
             "howser",
             "validate",
+            "--trace=json",
             "some_template",
             "some_document",
-            "something_extra",
-        ]) {
-            assert_eq!(e.kind, ErrorKind::UnknownArgument);
-        } else {
-            panic!();
-        }
+        ]);
+        let sub_m = matches.subcommand_matches("validate").unwrap();
+        assert_eq!(sub_m.value_of("trace").unwrap(), "json");
+
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec![
+            "howser",
+            "validate",
+            "--trace",
+            "some_template",
+            "some_document",
+        ]);
+        let sub_m = matches.subcommand_matches("validate").unwrap();
+        assert!(sub_m.is_present("trace"));
+        assert_eq!(sub_m.value_of("trace"), None);
+        assert_eq!(sub_m.value_of("prescription").unwrap(), "some_template");
+    }
+
+    #[test]
+    fn test_validate_subcommand_accepts_several_documents() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec![
+            "howser",
+            "validate",
+            "some_template",
+            "some_document",
+            "-",
+        ]);
+        let sub_m = matches.subcommand_matches("validate").unwrap();
+        assert_eq!(
+            sub_m.values_of("document").unwrap().collect::<Vec<_>>(),
+            vec!["some_document", "-"]
+        );
     }
 
     #[test]
@@ -403,6 +1533,205 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_infer_subcommand_has_documents() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec!["howser", "infer", "-o", "out.rx", "a.md", "b.md"]);
+        let sub_m = matches.subcommand_matches("infer").unwrap();
+        let documents: Vec<&str> = sub_m.values_of("document").unwrap().collect();
+        assert_eq!(documents, vec!["a.md", "b.md"]);
+        assert_eq!(sub_m.value_of("output").unwrap(), "out.rx");
+    }
+
+    #[test]
+    fn test_extract_subcommand_has_format() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec![
+            "howser",
+            "extract",
+            "-f",
+            "toml",
+            "some_template",
+            "some_document",
+        ]);
+        let sub_m = matches.subcommand_matches("extract").unwrap();
+        assert_eq!(sub_m.value_of("format").unwrap(), "toml");
+        assert_eq!(sub_m.value_of("prescription").unwrap(), "some_template");
+        assert_eq!(sub_m.value_of("document").unwrap(), "some_document");
+    }
+
+    #[test]
+    fn test_extract_subcommand_rejects_unknown_formats() {
+        let app = super::make_app();
+        if let Err(e) = app.get_matches_from_safe(vec![
+            "howser",
+            "extract",
+            "-f",
+            "yaml",
+            "some_template",
+            "some_document",
+        ]) {
+            assert_eq!(e.kind, ErrorKind::InvalidValue);
+        } else {
+            panic!();
+        }
+    }
+
+    #[test]
+    fn test_fix_subcommand_defaults_to_dry_run() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec!["howser", "fix", "some_template", "some_document"]);
+        let sub_m = matches.subcommand_matches("fix").unwrap();
+        assert_eq!(sub_m.value_of("prescription").unwrap(), "some_template");
+        assert_eq!(sub_m.value_of("document").unwrap(), "some_document");
+        assert!(!sub_m.is_present("in-place"));
+    }
+
+    #[test]
+    fn test_fix_subcommand_has_in_place() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec![
+            "howser",
+            "fix",
+            "--in-place",
+            "some_template",
+            "some_document",
+        ]);
+        let sub_m = matches.subcommand_matches("fix").unwrap();
+        assert!(sub_m.is_present("in-place"));
+    }
+
+    #[test]
+    fn test_scaffold_subcommand_has_prescription() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec!["howser", "scaffold", "-o", "some_template"]);
+        let sub_m = matches.subcommand_matches("scaffold").unwrap();
+        assert_eq!(sub_m.value_of("prescription").unwrap(), "some_template");
+        assert!(sub_m.is_present("include-optional"));
+    }
+
+    #[test]
+    fn test_explain_subcommand_has_code() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec!["howser", "explain", "HX0005"]);
+        let sub_m = matches.subcommand_matches("explain").unwrap();
+        assert_eq!(sub_m.value_of("code").unwrap(), "HX0005");
+    }
+
+    #[test]
+    fn test_pharmacy_validate_subcommand_accepts_several_tags() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec![
+            "howser",
+            "pharmacy",
+            "validate",
+            "--tag",
+            "guides",
+            "--tag",
+            "api",
+            "Pharmacy.toml",
+        ]);
+        let sub_m = matches
+            .subcommand_matches("pharmacy")
+            .and_then(|sub_m| sub_m.subcommand_matches("validate"))
+            .unwrap();
+        assert_eq!(
+            sub_m.values_of("tag").unwrap().collect::<Vec<&str>>(),
+            vec!["guides", "api"]
+        );
+        assert_eq!(sub_m.value_of("pharmacy").unwrap(), "Pharmacy.toml");
+    }
+
+    #[test]
+    fn test_pharmacy_lint_subcommand_has_pharmacy() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec!["howser", "pharmacy", "lint", "Pharmacy.toml"]);
+        let sub_m = matches
+            .subcommand_matches("pharmacy")
+            .and_then(|sub_m| sub_m.subcommand_matches("lint"))
+            .unwrap();
+        assert_eq!(sub_m.value_of("pharmacy").unwrap(), "Pharmacy.toml");
+    }
+
+    #[test]
+    fn test_lsp_subcommand_has_optional_pharmacy() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec!["howser", "lsp"]);
+        assert!(!matches.subcommand_matches("lsp").unwrap().is_present("pharmacy"));
+
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec!["howser", "lsp", "--pharmacy", "Docs.toml"]);
+        let sub_m = matches.subcommand_matches("lsp").unwrap();
+        assert_eq!(sub_m.value_of("pharmacy").unwrap(), "Docs.toml");
+    }
+
+    #[test]
+    fn test_pharmacy_subcommands_have_optional_pharmacy() {
+        for subcommand in &["check", "lint", "validate"] {
+            let app = super::make_app();
+            let matches = app.get_matches_from(vec!["howser", "pharmacy", subcommand]);
+            let sub_m = matches
+                .subcommand_matches("pharmacy")
+                .and_then(|matches| matches.subcommand_matches(subcommand))
+                .unwrap();
+            assert!(!sub_m.is_present("pharmacy"));
+        }
+    }
+
+    #[test]
+    fn test_validate_subcommands_have_watch() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec!["howser", "validate", "-w", "a.rx", "a.md"]);
+        assert!(matches.subcommand_matches("validate").unwrap().is_present("watch"));
+
+        let app = super::make_app();
+        let matches =
+            app.get_matches_from(vec!["howser", "pharmacy", "validate", "--watch", "p.toml"]);
+        let sub_m = matches
+            .subcommand_matches("pharmacy")
+            .and_then(|matches| matches.subcommand_matches("validate"))
+            .unwrap();
+        assert!(sub_m.is_present("watch"));
+
+        let app = super::make_app();
+        let result = app.get_matches_from_safe(vec![
+            "howser",
+            "pharmacy",
+            "validate",
+            "--watch",
+            "--fail-early",
+            "p.toml",
+        ]);
+        assert_eq!(result.unwrap_err().kind, ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_deny_warnings_is_accepted_after_subcommands() {
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec![
+            "howser",
+            "pharmacy",
+            "validate",
+            "--deny-warnings",
+            "Pharmacy.toml",
+        ]);
+        assert!(matches.is_present("deny-warnings"));
+
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec!["howser", "check", "some_template"]);
+        assert!(!matches.is_present("deny-warnings"));
+    }
+
+    #[test]
+    fn test_explain_rejects_unknown_codes() {
+        assert!(super::explain(Some("HX0005")).is_ok());
+        assert!(super::explain(None).is_ok());
+        match super::explain(Some("HX9999")) {
+            Err(HowserError::Usage(_)) => {}
+            _ => panic!(),
+        }
+    }
+
     #[test]
     fn test_pharmacy_check_subcommand() {
         let app = super::make_app();
@@ -432,72 +1761,124 @@ mod tests {
     }
 
     #[test]
-    fn parse_pharmacy_allows_empty_table() {
-        assert_eq!(
-            Pharmacy {
-                spec_to_targets: BTreeMap::new()
-            },
-            parse_pharmacy_string("test_file", r#"[Specs]"#.to_string())
-                .expect("Should have been able to parse")
-        );
+    fn test_pharmacy_subcommands_have_jobs() {
+        for subcommand in &["check", "validate"] {
+            let app = super::make_app();
+            let matches =
+                app.get_matches_from(vec!["howser", "pharmacy", subcommand, "-j", "8", "p.toml"]);
+            let sub_m = matches
+                .subcommand_matches("pharmacy")
+                .and_then(|matches| matches.subcommand_matches(subcommand))
+                .unwrap();
+            assert_eq!(super::parse_jobs(sub_m).unwrap(), 8);
+        }
+
+        let app = super::make_app();
+        let matches = app.get_matches_from(vec!["howser", "pharmacy", "check", "-j", "0", "p.toml"]);
+        let sub_m = matches
+            .subcommand_matches("pharmacy")
+            .and_then(|matches| matches.subcommand_matches("check"))
+            .unwrap();
+        match super::parse_jobs(sub_m) {
+            Err(HowserError::Usage(_)) => {}
+            _ => panic!(),
+        }
     }
 
     #[test]
-    fn parse_pharmacy_supports_string_values() {
-        let mut m = BTreeMap::new();
-        m.insert(PathBuf::from("README.rx"), vec![PathBuf::from("README.md")]);
+    fn test_watched_files_include_pharmacy_sources() {
+        let mut spec_to_targets = BTreeMap::new();
+        spec_to_targets.insert(
+            PathBuf::from("/docs/guide.rx"),
+            vec![PathBuf::from("/docs/a.md")],
+        );
+        let pharmacy = Pharmacy {
+            spec_to_targets,
+            spec_options: BTreeMap::new(),
+            sources: vec![
+                PathBuf::from("/Cargo.toml"),
+                PathBuf::from("/crates/first/Cargo.toml"),
+            ],
+        };
+
         assert_eq!(
-            Pharmacy { spec_to_targets: m },
-            parse_pharmacy_string(
-                "test_file",
-                r#"[Specs]
-                   "README.rx" = "README.md""#.to_string()
-            ).expect("Should have been able to parse")
+            super::watched_files(&pharmacy, &pharmacy.pairs()),
+            vec![
+                PathBuf::from("/Cargo.toml"),
+                PathBuf::from("/crates/first/Cargo.toml"),
+                PathBuf::from("/docs/guide.rx"),
+                PathBuf::from("/docs/a.md"),
+            ]
         );
     }
 
     #[test]
-    fn parse_pharmacy_supports_array_of_string_values() {
-        let mut m = BTreeMap::new();
-        m.insert(
-            PathBuf::from("README.rx"),
-            vec![
-                PathBuf::from("README.md"),
-                PathBuf::from("subdir/README.md"),
-            ],
+    fn test_changed_pharmacy_keeps_affected_pairs() {
+        let mut spec_to_targets = BTreeMap::new();
+        spec_to_targets.insert(
+            PathBuf::from("/docs/guide.rx"),
+            vec![PathBuf::from("/docs/a.md"), PathBuf::from("/docs/b.md")],
         );
-        assert_eq!(
-            Pharmacy { spec_to_targets: m },
-            parse_pharmacy_string(
-                "test_file",
-                r#"[Specs]
-                   "README.rx" = ["README.md", "subdir/README.md"]"#.to_string()
-            ).expect("Should have been able to parse")
+        spec_to_targets.insert(
+            PathBuf::from("/docs/readme.rx"),
+            vec![PathBuf::from("/docs/c.md"), PathBuf::from("/docs/d.md")],
         );
-    }
+        let pharmacy = Pharmacy {
+            spec_to_targets,
+            spec_options: BTreeMap::new(),
+            sources: Vec::new(),
+        };
+        let changed: BTreeSet<PathBuf> = vec![
+            PathBuf::from("/docs/b.md"),
+            PathBuf::from("/docs/readme.rx"),
+            PathBuf::from("/docs/unrelated.md"),
+        ].into_iter()
+            .collect();
 
-    #[test]
-    fn parse_pharmacy_disallows_duplicate_keys() {
-        let result = parse_pharmacy_string(
-            "test_file",
-            r#"[Specs]
-            "README.rx" = "README.md"
-            "README.rx" = "subdir/README.md""#.to_string(),
+        let changed_pharmacy = super::changed_pharmacy(&pharmacy, &changed).unwrap();
+        assert_eq!(
+            changed_pharmacy.pairs(),
+            vec![
+                (PathBuf::from("/docs/guide.rx"), PathBuf::from("/docs/b.md")),
+                (PathBuf::from("/docs/readme.rx"), PathBuf::from("/docs/c.md")),
+                (PathBuf::from("/docs/readme.rx"), PathBuf::from("/docs/d.md")),
+            ]
         );
-        match result {
-            Err(HowserError::TomlError(_)) => println!("As expected"),
-            x @ _ => panic!("Unexpected success or kind of error: {:?}", x),
-        }
     }
 }
 
 /// Returns the textual content of the indicated file
 ///
 /// # Arguments
-/// 'file_name': The name of the file to get.
+/// 'file_name': The name of the file to get, or `-` for stdin.
 fn get_file_contents<P: AsRef<Path>>(file_name: P) -> HowserResult<String> {
-    let mut file = File::open(file_name)?;
     let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
+    match is_stdin(&file_name) {
+        true => std::io::stdin().read_to_string(&mut contents)?,
+        false => File::open(file_name)?.read_to_string(&mut contents)?,
+    };
     Ok(contents)
 }
+
+/// Returns the pharmacy file named in `args`, or else `Pharmacy.toml` if it exists, or else
+/// `Cargo.toml`.
+fn pharmacy_file<'a>(args: &'a ArgMatches) -> &'a str {
+    match args.value_of("pharmacy") {
+        Some(filename) => filename,
+        None if Path::new(DEFAULT_PHARMACY_FILE).is_file() => DEFAULT_PHARMACY_FILE,
+        None => CARGO_MANIFEST,
+    }
+}
+
+/// Determines if the file name stands for stdin.
+fn is_stdin<P: AsRef<Path>>(file_name: P) -> bool {
+    file_name.as_ref() == Path::new(STDIN_FILE_NAME)
+}
+
+/// Returns the name of the indicated file as it is shown in reports.
+fn source_name<P: AsRef<Path>>(file_name: P) -> Option<String> {
+    match is_stdin(&file_name) {
+        true => Some(STDIN_SOURCE_NAME.to_string()),
+        false => file_name.as_ref().to_str().map(|s| s.to_string()),
+    }
+}
@@ -0,0 +1,262 @@
+//! Checks documentation from Cargo build scripts.
+//!
+//! A crate can validate its documents on every `cargo build` or `cargo test` by calling
+//! `check_pharmacy` from its `build.rs`:
+//!
+//! ```no_run
+//! extern crate howser;
+//!
+//! fn main() {
+//!     howser::build::check_pharmacy("Pharmacy.toml").unwrap();
+//! }
+//! ```
+//!
+//! Build scripts run in the directory of the crate's manifest, so relative paths in the pharmacy
+//! file are relative to it. Every problem is reported as a `cargo:warning=` line, and Cargo is told
+//! to rerun the script whenever the pharmacy file, the manifest of a workspace member it reads, or
+//! any prescription or document in it changes. The `severity` and `allow-extra-content` options of
+//! pharmacy entries are honored.
+
+extern crate regex;
+
+use self::regex::Regex;
+use document::Prescription;
+use errors::{HowserError, HowserResult, Severity, ValidationProblem};
+use pharmacy::Pharmacy;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, Write};
+use std::io::prelude::*;
+use std::path::Path;
+
+/// Which problems fail the build. Problems are reported either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strictness {
+    /// Never fail the build.
+    Warn,
+    /// Fail the build if errors are found.
+    DenyErrors,
+    /// Fail the build if errors or warnings are found.
+    DenyWarnings,
+}
+
+/// Validates every document of the pharmacy file against its prescription, and checks the
+/// prescriptions themselves, failing the build if errors are found.
+///
+/// Returns an error describing the failure if the build should fail.
+pub fn check_pharmacy<P: AsRef<Path>>(pharmacy_file: P) -> HowserResult<()> {
+    check_pharmacy_with(pharmacy_file, Strictness::DenyErrors)
+}
+
+/// Like `check_pharmacy`, failing the build according to `strictness`.
+pub fn check_pharmacy_with<P: AsRef<Path>>(
+    pharmacy_file: P,
+    strictness: Strictness,
+) -> HowserResult<()> {
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    check(pharmacy_file.as_ref(), strictness, &mut output)
+}
+
+fn check<W: Write>(pharmacy_file: &Path, strictness: Strictness, output: &mut W) -> HowserResult<()> {
+    trace!("build::check()");
+    let pharmacy = Pharmacy::from_file(pharmacy_file)?;
+
+    let mut files = BTreeSet::new();
+    for (rx_file, doc_file) in pharmacy.pairs() {
+        files.insert(rx_file);
+        files.insert(doc_file);
+    }
+    for source in pharmacy.sources.iter() {
+        writeln!(output, "cargo:rerun-if-changed={}", source.display())?;
+    }
+    for file in files {
+        writeln!(output, "cargo:rerun-if-changed={}", file.display())?;
+    }
+
+    let mut problems: Vec<ValidationProblem> = Vec::new();
+    for (rx_file, doc_files) in pharmacy.spec_to_targets.iter() {
+        let rx_name = rx_file.to_string_lossy().to_string();
+        let rx_text = read_file(rx_file)?;
+        problems.append(&mut pharmacy.check_prescription(rx_file, &rx_text)?);
+
+        let prescription = match Prescription::from_text(&rx_text, Some(rx_name)) {
+            Ok(prescription) => prescription,
+            // The linter has already reported why the prescription cannot be used.
+            Err(HowserError::PrescriptionError(_)) => continue,
+            Err(error) => return Err(error),
+        };
+        for doc_file in doc_files {
+            let doc_text = read_file(doc_file)?;
+            problems.append(&mut pharmacy.validate_document(
+                rx_file,
+                prescription.clone(),
+                doc_file,
+                &doc_text,
+            )?);
+        }
+    }
+
+    let colors = Regex::new("\x1b\\[[0-9;]*m").unwrap();
+    for problem in problems.iter() {
+        writeln!(output, "cargo:warning={}", describe(problem, &colors))?;
+    }
+
+    let error_count = problems
+        .iter()
+        .filter(|problem| problem.severity() == Severity::Error)
+        .count();
+    let fails = match strictness {
+        Strictness::Warn => false,
+        Strictness::DenyErrors => error_count > 0,
+        Strictness::DenyWarnings => !problems.is_empty(),
+    };
+
+    match fails {
+        true => Err(HowserError::RuntimeError(format!(
+            "{} does not conform: {} errors and {} warnings were found.",
+            pharmacy_file.display(),
+            error_count,
+            problems.len() - error_count
+        ))),
+        false => Ok(()),
+    }
+}
+
+/// Describes a problem on a single line with the escape codes matched by `colors` removed, as
+/// Cargo shows it verbatim.
+fn describe(problem: &ValidationProblem, colors: &Regex) -> String {
+    let severity = match problem.severity() {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    };
+    let message = colors.replace_all(&problem.short_msg(), "").into_owned();
+
+    format!("{}: {}", severity, message)
+}
+
+/// Reads a file named in the pharmacy, naming it in the error if it cannot be read.
+fn read_file(path: &Path) -> HowserResult<String> {
+    let mut text = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut text))
+        .map_err(|e| {
+            HowserError::RuntimeError(format!(
+                "Unable to read {}: {}",
+                path.display(),
+                e.description()
+            ))
+        })?;
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempfile;
+
+    use super::{check, Strictness};
+    use std::fs::File;
+    use std::io::Write;
+    use std::path::Path;
+
+    /// Writes a pharmacy of `README.rx` and `README.md` to a temporary directory, then checks it
+    /// and returns whether the build passed and the lines written for Cargo.
+    fn check_readme(document: &str, strictness: Strictness) -> (bool, Vec<String>) {
+        let directory = tempfile::tempdir().unwrap();
+        let write = |name: &str, text: &str| {
+            File::create(directory.path().join(name))
+                .unwrap()
+                .write_all(text.as_bytes())
+                .unwrap()
+        };
+        let root = directory.path().display().to_string();
+        write("README.rx", "# -!!-\n\n## Usage\n\n-!!-\n\n-++-\n");
+        write("README.md", document);
+        write(
+            "Pharmacy.toml",
+            &format!("[Specs]\n\"{0}/README.rx\" = \"{0}/README.md\"\n", root),
+        );
+
+        let mut output = Vec::new();
+        let passed = check(
+            &directory.path().join("Pharmacy.toml"),
+            strictness,
+            &mut output,
+        ).is_ok();
+        let lines = String::from_utf8(output)
+            .unwrap()
+            .replace(&root, "")
+            .lines()
+            .map(|line| line.to_string())
+            .collect();
+
+        (passed, lines)
+    }
+
+    #[test]
+    fn test_problems_are_reported_to_cargo() {
+        let (passed, lines) = check_readme("# Howser\n\n### Usage\n\nRun it.\n", Strictness::Warn);
+
+        assert!(passed);
+        assert_eq!(
+            lines,
+            vec![
+                "cargo:rerun-if-changed=/Pharmacy.toml",
+                "cargo:rerun-if-changed=/README.md",
+                "cargo:rerun-if-changed=/README.rx",
+                "cargo:warning=error: Type Mismatch Error[HX0005]: heading from /README.rx line 3, \
+                 columns 1-8 does not match heading from /README.md line 3, columns 1-9 :: this is \
+                 an H3, the prescription expects an H2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strictness_decides_failure() {
+        let invalid = "# Howser\n\n### Usage\n\nRun it.\n";
+        let warned = "# Howser\n\n## Usage\n\nRun it.\n";
+        let valid = "# Howser\n\n## Usage\n\nRun it.\n\nRun it again.\n";
+
+        assert!(!check_readme(invalid, Strictness::DenyErrors).0);
+        assert!(check_readme(warned, Strictness::DenyErrors).0);
+        assert!(!check_readme(warned, Strictness::DenyWarnings).0);
+        assert!(check_readme(valid, Strictness::DenyWarnings).0);
+    }
+
+    #[test]
+    fn test_unusable_prescriptions_are_reported_once() {
+        let directory = tempfile::tempdir().unwrap();
+        let rx = directory.path().join("broken.rx");
+        let pharmacy = directory.path().join("Pharmacy.toml");
+        File::create(&rx).unwrap().write_all(b"-\"\"-\n").unwrap();
+        File::create(&pharmacy)
+            .unwrap()
+            .write_all(format!("[Specs]\n\"{}\" = \"unread.md\"\n", rx.display()).as_bytes())
+            .unwrap();
+
+        let mut output = Vec::new();
+        assert!(check(&pharmacy, Strictness::DenyErrors, &mut output).is_err());
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("cargo:warning=").count(), 1);
+        assert!(output.contains("cargo:warning=error: Rx Specification Error[HX0009]"));
+    }
+
+    #[test]
+    fn test_missing_files_are_named() {
+        let mut output = Vec::new();
+        let directory = tempfile::tempdir().unwrap();
+        let pharmacy = directory.path().join("Pharmacy.toml");
+        File::create(&pharmacy)
+            .unwrap()
+            .write_all(b"[Specs]\n\"missing.rx\" = \"missing.md\"\n")
+            .unwrap();
+
+        match check(&pharmacy, Strictness::Warn, &mut output) {
+            Err(error) => assert!(format!("{:?}", error).contains("missing.rx")),
+            Ok(()) => panic!(),
+        }
+        assert!(!check(Path::new("missing.toml"), Strictness::Warn, &mut output).is_ok());
+    }
+}
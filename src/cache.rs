@@ -0,0 +1,303 @@
+//! Stores validation results on disk, keyed by the content of the files that were validated.
+
+extern crate serde_json;
+
+use self::serde_json::{Map, Value};
+use ast::Span;
+use errors::{ErrorCode, HowserResult, Location, Reportable, ValidationProblem};
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// The version of Howser. Results cached by other versions are discarded.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The file in a cache directory recording the version of Howser that wrote it.
+const VERSION_FILE: &str = "version";
+/// The extension of cached results.
+const ENTRY_EXTENSION: &str = "json";
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A problem replayed from the cache.
+///
+/// Messages are stored as they were rendered, so replayed problems report exactly as they did when
+/// they were found.
+pub struct CachedProblem {
+    short_msg: String,
+    long_msg: String,
+    code: ErrorCode,
+    prescription_location: Option<Location>,
+    document_location: Option<Location>,
+}
+
+impl CachedProblem {
+    /// Returns a copy of `problem` that can be stored.
+    pub fn new(problem: &ValidationProblem) -> Self {
+        CachedProblem {
+            short_msg: problem.short_msg(),
+            long_msg: problem.long_msg(),
+            code: problem.code(),
+            prescription_location: problem.prescription_location(),
+            document_location: problem.document_location(),
+        }
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        let field = |name: &str| value.get(name).and_then(|field| field.as_str());
+
+        Some(CachedProblem {
+            short_msg: field("short")?.to_string(),
+            long_msg: field("long")?.to_string(),
+            code: ErrorCode::parse(field("code")?)?,
+            prescription_location: location_from_value(value.get("prescription")?)?,
+            document_location: location_from_value(value.get("document")?)?,
+        })
+    }
+
+    fn to_value(&self) -> Value {
+        let mut entry = Map::new();
+        entry.insert("code".to_string(), Value::from(self.code.to_string()));
+        entry.insert("short".to_string(), Value::from(self.short_msg.clone()));
+        entry.insert("long".to_string(), Value::from(self.long_msg.clone()));
+        entry.insert(
+            "prescription".to_string(),
+            location_to_value(&self.prescription_location),
+        );
+        entry.insert(
+            "document".to_string(),
+            location_to_value(&self.document_location),
+        );
+
+        Value::Object(entry)
+    }
+}
+
+impl Reportable for CachedProblem {
+    fn short_msg(&self) -> String {
+        self.short_msg.clone()
+    }
+
+    fn long_msg(&self) -> String {
+        self.long_msg.clone()
+    }
+
+    fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    fn prescription_location(&self) -> Option<Location> {
+        self.prescription_location.clone()
+    }
+
+    fn document_location(&self) -> Option<Location> {
+        self.document_location.clone()
+    }
+}
+
+/// Validation results stored in a directory.
+///
+/// Results are keyed by hashes of the names and contents of the prescription and the document, so
+/// a result is only replayed when neither file has changed since it was stored.
+#[derive(Debug, Clone)]
+pub struct ValidationCache {
+    directory: PathBuf,
+}
+
+impl ValidationCache {
+    /// Opens the cache in `directory`, creating the directory if needed.
+    ///
+    /// If the cache was written by a different version of Howser, its results are removed.
+    pub fn open<P: AsRef<Path>>(directory: P) -> HowserResult<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)?;
+
+        let version_path = directory.join(VERSION_FILE);
+        let mut version = String::new();
+        if let Ok(mut file) = File::open(&version_path) {
+            file.read_to_string(&mut version)?;
+        }
+
+        if version.trim() != VERSION {
+            info!("ValidationCache::open:: Clearing results from version {}", version);
+            for entry in fs::read_dir(&directory)? {
+                let path = entry?.path();
+                if path.extension().map_or(false, |ext| ext == ENTRY_EXTENSION) {
+                    fs::remove_file(&path)?;
+                }
+            }
+            File::create(&version_path)?.write_all(VERSION.as_bytes())?;
+        }
+
+        Ok(ValidationCache { directory })
+    }
+
+    /// Returns the key of the result of validating a document against a prescription.
+    /// `settings` describes any validator settings that change the result.
+    pub fn key(
+        rx_name: &str,
+        rx_text: &str,
+        document_name: &str,
+        document_text: &str,
+        settings: &str,
+    ) -> String {
+        let hash = [VERSION, rx_name, rx_text, document_name, document_text, settings]
+            .iter()
+            .fold(FNV_OFFSET_BASIS, |hash, part| {
+                let length = format!("{}:", part.len());
+                fnv1a(fnv1a(hash, length.as_bytes()), part.as_bytes())
+            });
+
+        format!("{:016x}", hash)
+    }
+
+    /// Returns the stored result for `key`, if there is a readable one.
+    pub fn get(&self, key: &str) -> Option<Vec<ValidationProblem>> {
+        let mut text = String::new();
+        File::open(self.entry_path(key))
+            .and_then(|mut file| file.read_to_string(&mut text))
+            .ok()?;
+        let value = serde_json::from_str::<Value>(&text).ok()?;
+
+        value
+            .as_array()?
+            .iter()
+            .map(|problem| {
+                CachedProblem::from_value(problem).map(|problem| Box::new(problem) as ValidationProblem)
+            })
+            .collect()
+    }
+
+    /// Stores `problems` as the result for `key`.
+    pub fn put(&self, key: &str, problems: &[ValidationProblem]) -> HowserResult<()> {
+        let value = Value::Array(
+            problems
+                .iter()
+                .map(|problem| CachedProblem::new(problem).to_value())
+                .collect(),
+        );
+        File::create(self.entry_path(key))?.write_all(value.to_string().as_bytes())?;
+
+        Ok(())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!("{}.{}", key, ENTRY_EXTENSION))
+    }
+}
+
+/// Folds `bytes` into a 64 bit FNV-1a hash, which is stable across platforms and releases.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+fn location_to_value(location: &Option<Location>) -> Value {
+    match location {
+        &Some(ref location) => {
+            let span = location.span;
+            let mut entry = Map::new();
+            entry.insert("file".to_string(), Value::from(location.file.clone()));
+            entry.insert(
+                "span".to_string(),
+                Value::from(vec![
+                    span.start_line,
+                    span.start_column,
+                    span.end_line,
+                    span.end_column,
+                ]),
+            );
+            Value::Object(entry)
+        }
+        &None => Value::Null,
+    }
+}
+
+/// Reads a location written by `location_to_value`. The outer `Option` is `None` if the value is
+/// malformed.
+fn location_from_value(value: &Value) -> Option<Option<Location>> {
+    if value.is_null() {
+        return Some(None);
+    }
+
+    let span = value
+        .get("span")?
+        .as_array()?
+        .iter()
+        .map(|part| part.as_u64().map(|part| part as usize))
+        .collect::<Option<Vec<usize>>>()?;
+    if span.len() != 4 {
+        return None;
+    }
+
+    Some(Some(Location {
+        file: value.get("file")?.as_str()?.to_string(),
+        span: Span::new(span[0], span[1], span[2], span[3]),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempfile;
+
+    use super::ValidationCache;
+    use document::{Document, Prescription};
+    use std::fs::File;
+    use std::io::Write;
+    use validator::Validator;
+
+    #[test]
+    fn test_cached_problems_replay_as_found() {
+        let directory = tempfile::tempdir().unwrap();
+        let cache = ValidationCache::open(directory.path()).unwrap();
+        let rx = "# Title\n\n## Usage\n\n-!!-\n";
+        let doc = "# Title\n\n### Usage\n\nRun it.\n";
+        let key = ValidationCache::key("README.rx", rx, "README.md", doc, "");
+        assert!(cache.get(&key).is_none());
+
+        let problems = Validator::new(
+            Prescription::from_text(rx, Some("README.rx".to_string())).unwrap(),
+            Document::from_text(doc, Some("README.md".to_string())).unwrap(),
+        ).validate()
+            .unwrap();
+        cache.put(&key, &problems).unwrap();
+
+        let replayed = cache.get(&key).unwrap();
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].code(), problems[0].code());
+        assert_eq!(replayed[0].short_msg(), problems[0].short_msg());
+        assert_eq!(
+            replayed[0].document_location(),
+            problems[0].document_location()
+        );
+    }
+
+    #[test]
+    fn test_keys_depend_on_names_and_contents() {
+        let key = ValidationCache::key("a.rx", "# -!!-", "a.md", "# Title", "");
+        assert_eq!(key, ValidationCache::key("a.rx", "# -!!-", "a.md", "# Title", ""));
+        assert_ne!(key, ValidationCache::key("a.rx", "# -!!-", "b.md", "# Title", ""));
+        assert_ne!(key, ValidationCache::key("a.rx", "# -!!-", "a.md", "# Title!", ""));
+        assert_ne!(key, ValidationCache::key("a.rx", "# -!!-a.md", "", "# Title", ""));
+        assert_ne!(
+            key,
+            ValidationCache::key("a.rx", "# -!!-", "a.md", "# Title", "allow-extra-content")
+        );
+    }
+
+    #[test]
+    fn test_other_versions_are_cleared() {
+        let directory = tempfile::tempdir().unwrap();
+        let cache = ValidationCache::open(directory.path()).unwrap();
+        cache.put("stale", &[]).unwrap();
+        assert!(cache.get("stale").is_some());
+
+        File::create(directory.path().join("version"))
+            .unwrap()
+            .write_all(b"0.0.0")
+            .unwrap();
+        let cache = ValidationCache::open(directory.path()).unwrap();
+        assert!(cache.get("stale").is_none());
+    }
+}
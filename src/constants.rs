@@ -2,8 +2,12 @@
 
 pub const MANDATORY_PROMPT: &str = "-!!-";
 pub const OPTIONAL_PROMPT: &str = "-??-";
+pub const RECOMMENDED_PROMPT: &str = "-++-";
 pub const DITTO_TOKEN: &str = "-\"\"-";
 pub const U_DITTO_TOKEN: &str = "-\u{201d}\u{201d}-";
 
 pub const CONTENT_PROMPT_PATTERN: &str = "(-(?:!!|\\?\\?)-)";
-pub const PROMPT_PATTERN: &str = "(-(?:!!|\\?\\?|\u{201d}\u{201d}|\"\")-)";
+pub const PROMPT_PATTERN: &str = "(-(?:!!|\\?\\?|\\+\\+|\u{201d}\u{201d}|\"\")-)";
+pub const MALFORMED_PROMPT_PATTERN: &str =
+    "-[!?\"\u{201d}]+-|-[!?]{2,}(?:[^-!?]|$)|(?:^|[^-!?])[!?]{2,}-";
+pub const SUPPRESSION_DIRECTIVE_PATTERN: &str = "^howser-suppress-(next|section|file)(?:\\s+(.*))?$";
@@ -1,7 +1,8 @@
 //! Various data types relating to `Document`s, `Template`s, and `Node`s.
 
+use ast::{Node, NodeId, NodeKind, Span};
 use constants::{MANDATORY_PROMPT, OPTIONAL_PROMPT};
-use doogie::Node;
+use errors::ErrorCode;
 use std::fmt::{Debug, Error, Formatter};
 
 /// Element-Level match types for `Node`s.
@@ -10,10 +11,12 @@ pub enum MatchType {
     None,
     Mandatory,
     Optional,
+    Recommended,
     Repeatable,
 }
 
 /// Supplementary metadata associated with individual `Node`s.
+#[derive(Clone)]
 pub struct NodeData {
     pub match_type: MatchType,
     pub comment: Option<String>,
@@ -33,6 +36,29 @@ impl NodeData {
     }
 }
 
+/// The outcome of one alignment decision made during validation.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Alignment {
+    /// The prescription element matched the document element.
+    Matched,
+    /// The prescription element was compared with the document element and did not match.
+    Mismatched(ErrorCode),
+    /// An optional prescription element was left unmatched.
+    Skipped,
+    /// A dittoed prescription element matched this many consecutive document elements.
+    Repeated(usize),
+}
+
+/// A record of a `Validator` aligning a prescription element with the document.
+#[derive(PartialEq, Debug, Clone)]
+pub struct AlignmentStep {
+    /// The prescription element being aligned.
+    pub rx: NodeId,
+    /// The document element it was compared with, if there was one.
+    pub node: Option<NodeId>,
+    pub alignment: Alignment,
+}
+
 /// Represents a templated prompt for content.
 #[derive(PartialEq, Debug, Clone)]
 pub enum PromptToken {
@@ -71,6 +97,47 @@ impl Comment {
     }
 }
 
+/// How much of a document a suppression directive applies to.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SuppressionScope {
+    /// The block element that follows the directive.
+    NextBlock,
+    /// The section that contains the directive, from its heading to the next heading of the same
+    /// or a higher level.
+    Section,
+    /// The whole document.
+    File,
+}
+
+/// A directive written as an HTML comment in a document that suppresses problems.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Suppression {
+    pub scope: SuppressionScope,
+    /// The codes of the problems that are suppressed.
+    pub codes: Vec<ErrorCode>,
+    /// Names in the directive that are not problem codes.
+    pub unknown_codes: Vec<String>,
+    /// The location of the directive itself.
+    pub span: Span,
+    /// The first and last document lines covered by the directive.
+    pub lines: (usize, usize),
+}
+
+impl Suppression {
+    /// Determines if a problem with the given code that starts on the given line is suppressed.
+    ///
+    /// Problems without a known line can only be suppressed for the whole file.
+    pub fn covers(&self, code: ErrorCode, line: Option<usize>) -> bool {
+        let is_in_scope = match (self.scope, line) {
+            (SuppressionScope::File, _) => true,
+            (_, Some(line)) => line >= self.lines.0 && line <= self.lines.1,
+            (_, None) => false,
+        };
+
+        is_in_scope && self.codes.contains(&code)
+    }
+}
+
 /// Represents a pairing of template prompt and document content.
 #[derive(Clone)]
 pub struct ContentMatchPair(pub PromptToken, pub Option<String>);
@@ -122,25 +189,24 @@ pub enum ElementType {
 
 impl ElementType {
     pub fn determine(node: &Node) -> Self {
-        match node {
-            Node::Document(_) | Node::List(_) | Node::BlockQuote(_) | Node::Item(_) => {
+        match node.kind() {
+            &NodeKind::Document | &NodeKind::List(_) | &NodeKind::BlockQuote | &NodeKind::Item => {
                 ElementType::ContainerBlock
             }
-            Node::Paragraph(_)
-            | Node::Heading(_)
-            | Node::CodeBlock(_)
-            | Node::ThematicBreak(_)
-            | Node::HtmlBlock(_)
-            | Node::CustomBlock(_) => ElementType::LeafBlock,
-            Node::Emph(_) | Node::Strong(_) | Node::Link(_) | Node::Image(_) => {
-                ElementType::InlineContainer
-            }
-            Node::Text(_)
-            | Node::SoftBreak(_)
-            | Node::LineBreak(_)
-            | Node::Code(_)
-            | Node::HtmlInline(_)
-            | Node::CustomInline(_) => ElementType::InlineLeaf,
+            &NodeKind::Paragraph
+            | &NodeKind::Heading(_)
+            | &NodeKind::CodeBlock { .. }
+            | &NodeKind::ThematicBreak
+            | &NodeKind::HtmlBlock(_) => ElementType::LeafBlock,
+            &NodeKind::Emph
+            | &NodeKind::Strong
+            | &NodeKind::Link { .. }
+            | &NodeKind::Image { .. } => ElementType::InlineContainer,
+            &NodeKind::Text(_)
+            | &NodeKind::SoftBreak
+            | &NodeKind::LineBreak
+            | &NodeKind::Code(_)
+            | &NodeKind::HtmlInline(_) => ElementType::InlineLeaf,
         }
     }
 }
@@ -4,153 +4,219 @@ extern crate regex;
 extern crate unicode_segmentation;
 
 use self::regex::Regex;
-use constants::{DITTO_TOKEN, MANDATORY_PROMPT, OPTIONAL_PROMPT, PROMPT_PATTERN};
+use ast::{Node, NodeId, NodeKind, Span, Tree, ROOT_ID};
+use backends::parse_document;
+use constants::{DITTO_TOKEN, MANDATORY_PROMPT, OPTIONAL_PROMPT, PROMPT_PATTERN,
+                RECOMMENDED_PROMPT, SUPPRESSION_DIRECTIVE_PATTERN};
 use data::ElementType;
-use data::{MatchType, NodeData};
-use doogie::constants::*;
-use doogie::Node;
-use errors::{HowserError, HowserResult, SpecWarning};
-use std::cell::RefCell;
+use data::{Comment, MatchType, NodeData, Suppression, SuppressionScope};
+use errors::{ErrorCode, HowserError, HowserResult, SpecWarning};
 use std::collections::HashMap;
 use validator::types_match;
 
 /// Wrapper for a Markdown document that manages extra metadata about the `Node`s contained within
-/// `root`.
-pub struct Document<'a> {
-    pub root: &'a Node,
-    data: RefCell<HashMap<u32, NodeData>>,
+/// `tree`.
+///
+/// A `Document` owns its tree, so it can be stored and moved between threads freely.
+#[derive(Clone)]
+pub struct Document {
+    tree: Tree,
+    data: HashMap<NodeId, NodeData>,
+    suppressions: Vec<Suppression>,
+    source: Option<String>,
     pub filename: Option<String>,
 }
 
-impl<'a> Document<'a> {
-    /// Returns a new `Document`.
+impl Document {
+    /// Returns a new `Document` that takes ownership of the given tree.
     ///
-    /// Note that this is a destructive operation to the root node. It will mutate the internal
-    /// structure of the tree. The reason for taking a reference and not ownership is to enable
-    /// compatibility with proptest generators.
-    pub fn new(root: &'a Node, filename: Option<String>) -> HowserResult<Self> {
-        strip_comments(root)?;
-
-        Ok(Document {
-            root,
-            data: RefCell::new(HashMap::new()),
+    /// Comments are stripped from the `Document`'s own tree. Clone the tree beforehand if the
+    /// original is still needed.
+    pub fn new(tree: Tree, filename: Option<String>) -> HowserResult<Self> {
+        let mut document = Document {
+            tree,
+            data: HashMap::new(),
+            suppressions: Vec::new(),
+            source: None,
             filename,
-        })
+        };
+        document.suppressions = collect_suppressions(&document)?;
+        strip_comments(&mut document)?;
+
+        Ok(document)
+    }
+
+    /// Parses Markdown source text with the enabled backend and returns a new `Document`.
+    ///
+    /// The text is kept so that reports can quote the original source.
+    pub fn from_text(text: &str, filename: Option<String>) -> HowserResult<Self> {
+        let mut document = Document::new(parse_document(text)?, filename)?;
+        document.source = Some(text.to_string());
+        Ok(document)
     }
 
     /// Transform this `Document` instance into a `Prescription`.
-    pub fn into_prescription(self) -> HowserResult<Prescription<'a>> {
+    pub fn into_prescription(self) -> HowserResult<Prescription> {
         trace!("into_prescription");
-        process_child_block_elements(&self.root, &self)?;
-        Ok(Prescription { document: self })
+        let mut document = self;
+        process_child_block_elements(ROOT_ID, &mut document)?;
+        Ok(Prescription { document })
     }
 
-    /// Traverse the document tree and return the first node encountered of the type specified.
-    pub fn first_of_type(&self, node_type: NodeType) -> HowserResult<Option<Node>> {
-        for (node, _) in self.root.iter() {
-            let found_type = node.get_cmark_type()?;
-            if found_type == node_type {
-                return Ok(Some(node));
-            }
-        }
+    /// Returns the tree wrapped by this `Document`.
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Returns the source text of this `Document`, if it was created from text.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_ref().map(|source| source.as_str())
+    }
+
+    /// Returns the suppression directives written in this `Document`.
+    pub fn suppressions(&self) -> &[Suppression] {
+        &self.suppressions
+    }
 
-        Ok(None)
+    /// Returns the document node at the root of the tree.
+    pub fn root(&self) -> Node {
+        self.tree.root()
+    }
+
+    /// Returns the node with the given id.
+    pub fn node(&self, id: NodeId) -> Node {
+        self.tree.node(id)
+    }
+
+    /// Traverse the document tree and return the first node encountered of the type specified.
+    pub fn first_of_type(&self, type_string: &str) -> Option<Node> {
+        self.root()
+            .descendants()
+            .find(|node| node.type_string() == type_string)
     }
 
     /// Returns the `MatchType` of the specified `Node`.
     pub fn get_match_type(&self, node: &Node) -> HowserResult<MatchType> {
-        let id = node.get_id();
-        let mut data_store = self.data.borrow_mut();
-        let node_data = data_store.entry(id).or_insert(NodeData::new());
-        Ok(node_data.match_type.clone())
+        Ok(match self.data.get(&node.id()) {
+            Some(node_data) => node_data.match_type.clone(),
+            None => NodeData::new().match_type,
+        })
     }
 
     /// Set the `MatchType` for a `Node`.
-    fn set_match_type(&self, node: &Node, match_type: MatchType) {
+    fn set_match_type(&mut self, id: NodeId, match_type: MatchType) {
         trace!("set_match_type()");
-        let id = node.get_id();
-        let mut data = self.data.borrow_mut();
-        let node_data = data.entry(id).or_insert(NodeData::new());
+        let node_data = self.data.entry(id).or_insert(NodeData::new());
         node_data.match_type = match_type;
     }
 
+    /// Returns the inline comment that was stripped from the given `Node`, if any.
+    pub fn get_comment(&self, node: &Node) -> Option<Comment> {
+        self.data
+            .get(&node.id())
+            .and_then(|node_data| node_data.comment.clone())
+            .map(Comment)
+    }
+
+    /// Set the comment for a `Node`.
+    fn set_comment(&mut self, id: NodeId, comment: String) {
+        trace!("set_comment()");
+        let node_data = self.data.entry(id).or_insert(NodeData::new());
+        if node_data.comment.is_none() {
+            node_data.comment = Some(comment);
+        }
+    }
+
     /// Returns a boolean indicating if this `Node` is considered a wildcard.
     pub fn is_wildcard(&self, rx_node: &Node) -> bool {
-        let id = rx_node.get_id();
-        let mut data_store = self.data.borrow_mut();
-        let node_data = data_store.entry(id).or_insert(NodeData::new());
-        node_data.is_wildcard
+        match self.data.get(&rx_node.id()) {
+            Some(node_data) => node_data.is_wildcard,
+            None => false,
+        }
     }
 
     /// Set the wildcard status for a `Node`.
-    fn set_is_wildcard(&self, node: &Node, state: bool) {
+    fn set_is_wildcard(&mut self, id: NodeId, state: bool) {
         trace!("set_is_wildcard()");
-        let id = node.get_id();
-        let mut data = self.data.borrow_mut();
-        let node_data = data.entry(id).or_insert(NodeData::new());
+        let node_data = self.data.entry(id).or_insert(NodeData::new());
         node_data.is_wildcard = state;
     }
 
     /// Infers the line number of the given node.
-    ///
-    /// Cmark reports that some inline and nested nodes are at line number zero which is usually
-    /// incorrect. This function searches up through the tree ancestry until it finds a parent node
-    /// that reports a sensible line number and returns that.
     pub fn get_line_num(node: &Node) -> HowserResult<usize> {
-        let parent = node.parent()?;
-        let line_num = node.get_start_line();
+        Ok(Document::get_span(node).start_line)
+    }
 
-        match (parent, line_num) {
-            (Some(parent), 0) => Document::get_line_num(&parent),
-            _ => Ok(line_num as usize),
+    /// Infers the source location of the given node.
+    ///
+    /// Backends may be unable to place some nodes, such as inline content that was written with
+    /// escapes. This function searches up through the tree ancestry until it finds a node with a
+    /// known location and returns that.
+    pub fn get_span(node: &Node) -> Span {
+        let span = node.span();
+
+        match (node.parent(), span.is_known()) {
+            (Some(parent), false) => Document::get_span(&parent),
+            _ => span,
         }
     }
 }
 
 /// A `Document` that has been parsed into an Rx prescription.
-pub struct Prescription<'a> {
-    pub document: Document<'a>,
+#[derive(Clone)]
+pub struct Prescription {
+    pub document: Document,
 }
 
-/// Process the match types of the children of the given parent.
-fn process_child_elements(parent: &Node, document: &Document) -> HowserResult<()> {
-    let child = parent.first_child()?;
+impl Prescription {
+    /// Returns a new `Prescription` that takes ownership of the given tree.
+    pub fn new(tree: Tree, filename: Option<String>) -> HowserResult<Self> {
+        Document::new(tree, filename)?.into_prescription()
+    }
 
-    if let Some(ref node) = child {
-        match ElementType::determine(node) {
-            ElementType::InlineContainer | ElementType::InlineLeaf => {
-                process_child_inline_elements(parent, document)
-            }
-            _ => process_child_block_elements(parent, document),
+    /// Parses Rx source text with the enabled backend and returns a new `Prescription`.
+    pub fn from_text(text: &str, filename: Option<String>) -> HowserResult<Self> {
+        Document::from_text(text, filename)?.into_prescription()
+    }
+}
+
+/// Process the match types of the children of the given parent.
+fn process_child_elements(parent: NodeId, document: &mut Document) -> HowserResult<()> {
+    let child_type = document
+        .node(parent)
+        .first_child()
+        .map(|node| ElementType::determine(&node));
+
+    match child_type {
+        Some(ElementType::InlineContainer) | Some(ElementType::InlineLeaf) => {
+            process_child_inline_elements(parent, document)
         }
-    } else {
-        Ok(())
+        Some(_) => process_child_block_elements(parent, document),
+        None => Ok(()),
     }
 }
 
 /// Process the match types of a set of inline elements that are children of the given parent.
-fn process_child_inline_elements(parent: &Node, document: &Document) -> HowserResult<()> {
-    let mut current_child = parent.first_child()?;
-
-    while let Some(node) = current_child {
-        match node {
-            Node::Text(ref text) => {
-                if all_content_is_optional(&text.get_content()?)? {
-                    document.set_match_type(&node, MatchType::Optional);
-                }
-            }
-            Node::Code(ref code) => {
-                if all_content_is_optional(&code.get_content()?)? {
-                    document.set_match_type(&node, MatchType::Optional);
-                }
+fn process_child_inline_elements(parent: NodeId, document: &mut Document) -> HowserResult<()> {
+    let mut current_child = document.node(parent).first_child().map(|node| node.id());
+
+    while let Some(id) = current_child {
+        let is_optional_content = match document.node(id).kind() {
+            &NodeKind::Text(ref content) | &NodeKind::Code(ref content) => {
+                Some(all_content_is_optional(content)?)
             }
-            _ => {
-                process_child_inline_elements(&node, document)?;
-                annotate_circumstantial_node(&node, document)?;
+            _ => None,
+        };
+        match is_optional_content {
+            Some(true) => document.set_match_type(id, MatchType::Optional),
+            Some(false) => (),
+            None => {
+                process_child_inline_elements(id, document)?;
+                annotate_circumstantial_node(id, document)?;
             }
         }
 
-        current_child = node.next_sibling()?;
+        current_child = next_sibling_of(document, id);
     }
 
     Ok(())
@@ -167,21 +233,25 @@ fn all_content_is_optional(content: &String) -> HowserResult<bool> {
 }
 
 /// Extract and transform the block-level Rx annotations from the document into metadata.
-fn process_child_block_elements(parent: &Node, document: &Document) -> HowserResult<()> {
+fn process_child_block_elements(parent: NodeId, document: &mut Document) -> HowserResult<()> {
     trace!("process_child_block_elements()");
-    let mut current_child = parent.first_child()?;
+    let mut current_child = document.node(parent).first_child().map(|node| node.id());
 
-    while let Some(l1_node) = current_child {
-        let mut l2 = LookaheadType::Other(None);
+    while let Some(l1_id) = current_child {
+        let (l1, l2) = {
+            let l1_node = document.node(l1_id);
+            let mut l2 = LookaheadType::Other(None);
 
-        if let Some(l2_node) = l1_node.next_sibling()? {
-            if types_match(&l1_node, &l2_node)? {
-                l2 = LookaheadType::new(Some(l2_node))?;
-            } else {
-                l2 = LookaheadType::Other(Some(l2_node));
+            if let Some(l2_node) = l1_node.next_sibling() {
+                if types_match(&l1_node, &l2_node)? {
+                    l2 = LookaheadType::new(Some(l2_node))?;
+                } else {
+                    l2 = LookaheadType::Other(Some(l2_node.id()));
+                }
             }
-        }
-        let l1 = LookaheadType::new(Some(l1_node))?;
+
+            (LookaheadType::new(Some(l1_node))?, l2)
+        };
 
         debug!(
             "process_child_block_elements:: Lookahead Sequence: {:?}, {:?}",
@@ -192,106 +262,106 @@ fn process_child_block_elements(parent: &Node, document: &Document) -> HowserRes
                 LookaheadType::DiscreteLiteral(target),
                 LookaheadType::Ditto(ditto),
             ) => {
-                process_child_elements(&target, document)?;
-                document.set_match_type(&ditto, MatchType::Repeatable);
-                current_child = ditto.next_sibling()?;
+                process_child_elements(target, document)?;
+                document.set_match_type(ditto, MatchType::Repeatable);
+                current_child = next_sibling_of(document, ditto);
             },
             (
                 LookaheadType::DiscreteLiteral(target),
                 _,
             ) => {
-                process_child_elements(&target, document)?;
-                current_child = target.next_sibling()?;
+                process_child_elements(target, document)?;
+                current_child = next_sibling_of(document, target);
             },
             (
-                LookaheadType::DiscreteAnnotated(mut annotation, match_type),
+                LookaheadType::DiscreteAnnotated(annotation, match_type),
                 LookaheadType::DiscreteLiteral(target),
             ) => {
-                document.set_match_type(&target, match_type);
-                annotation.unlink();
+                document.set_match_type(target, match_type);
+                document.tree.unlink(annotation);
                 current_child = Some(target);
             },
             (
-                LookaheadType::DiscreteAnnotated(mut target, match_type),
+                LookaheadType::DiscreteAnnotated(target, match_type),
                 LookaheadType::Ditto(ditto),
             ) => {
-                remove_annotation(&mut target)?;
-                document.set_match_type(&target, match_type);
-                document.set_is_wildcard(&target, true);
-                document.set_match_type(&ditto, MatchType::Repeatable);
-                current_child = ditto.next_sibling()?;
+                remove_annotation(&mut document.tree, target)?;
+                document.set_match_type(target, match_type);
+                document.set_is_wildcard(target, true);
+                document.set_match_type(ditto, MatchType::Repeatable);
+                current_child = next_sibling_of(document, ditto);
             },
             (
                 LookaheadType::DiscreteAnnotated(target, match_type),
                 _,
             ) => {
-                document.set_match_type(&target, match_type);
-                document.set_is_wildcard(&target, true);
-                current_child = target.next_sibling()?;
+                document.set_match_type(target, match_type);
+                document.set_is_wildcard(target, true);
+                current_child = next_sibling_of(document, target);
             },
             (
                 LookaheadType::IntegratedLiteral(target),
                 LookaheadType::Ditto(ditto),
             ) => {
-                process_child_elements(&target, document)?;
-                document.set_match_type(&ditto, MatchType::Repeatable);
-                current_child = ditto.next_sibling()?;
+                process_child_elements(target, document)?;
+                document.set_match_type(ditto, MatchType::Repeatable);
+                current_child = next_sibling_of(document, ditto);
             },
             (
                 LookaheadType::IntegratedLiteral(target),
                 _,
             ) => {
-                process_child_elements(&target, document)?;
-                current_child = target.next_sibling()?;
+                process_child_elements(target, document)?;
+                current_child = next_sibling_of(document, target);
             },
             (
-                LookaheadType::IntegratedVacant(mut target, match_type),
+                LookaheadType::IntegratedVacant(target, match_type),
                 LookaheadType::Ditto(ditto),
             ) => {
-                remove_annotation(&mut target)?;
-                document.set_match_type(&target, match_type);
-                document.set_match_type(&ditto, MatchType::Repeatable);
-                document.set_is_wildcard(&target, true);
-                current_child = ditto.next_sibling()?;
+                remove_annotation(&mut document.tree, target)?;
+                document.set_match_type(target, match_type);
+                document.set_match_type(ditto, MatchType::Repeatable);
+                document.set_is_wildcard(target, true);
+                current_child = next_sibling_of(document, ditto);
             },
             (
-                LookaheadType::IntegratedVacant(mut target, match_type),
+                LookaheadType::IntegratedVacant(target, match_type),
                 _,
             ) => {
-                remove_annotation(&mut target)?;
-                document.set_match_type(&target, match_type);
-                document.set_is_wildcard(&target, true);
-                current_child = target.next_sibling()?;
+                remove_annotation(&mut document.tree, target)?;
+                document.set_match_type(target, match_type);
+                document.set_is_wildcard(target, true);
+                current_child = next_sibling_of(document, target);
             },
             (
-                LookaheadType::IntegratedOccupied(mut target, match_type),
+                LookaheadType::IntegratedOccupied(target, match_type),
                 LookaheadType::Ditto(ditto),
             ) => {
-                process_child_elements(&target, document)?;
-                remove_annotation(&mut target)?;
-                document.set_match_type(&target, match_type);
-                document.set_match_type(&ditto, MatchType::Repeatable);
-                current_child = ditto.next_sibling()?;
+                process_child_elements(target, document)?;
+                remove_annotation(&mut document.tree, target)?;
+                document.set_match_type(target, match_type);
+                document.set_match_type(ditto, MatchType::Repeatable);
+                current_child = next_sibling_of(document, ditto);
             },
             (
-                LookaheadType::IntegratedOccupied(mut target, match_type),
+                LookaheadType::IntegratedOccupied(target, match_type),
                 _,
             ) => {
-                process_child_elements(&target, document)?;
-                remove_annotation(&mut target)?;
-                document.set_match_type(&target, match_type);
-                current_child = target.next_sibling()?;
+                process_child_elements(target, document)?;
+                remove_annotation(&mut document.tree, target)?;
+                document.set_match_type(target, match_type);
+                current_child = next_sibling_of(document, target);
             },
             (
                 LookaheadType::List(target),
                 _,
             ) => {
-                process_child_elements(&target, document)?;
-                annotate_circumstantial_node(&target, document)?;
-                current_child = target.next_sibling()?;
+                process_child_elements(target, document)?;
+                annotate_circumstantial_node(target, document)?;
+                current_child = next_sibling_of(document, target);
             },
             (LookaheadType::Ditto(node), _) => {
-                return Err(HowserError::PrescriptionError(SpecWarning::new(&node, document, "An element with a Ditto prompt must be preceded by an element of the same type.")?))
+                return Err(HowserError::PrescriptionError(SpecWarning::new(&document.node(node), document, ErrorCode::OrphanedDitto)?))
             },
             _ => return Err(HowserError::RuntimeError("Unexpected Lookahead Encountered".to_string()))
         }
@@ -300,30 +370,36 @@ fn process_child_block_elements(parent: &Node, document: &Document) -> HowserRes
     Ok(())
 }
 
+/// Returns the id of the sibling following the given node.
+fn next_sibling_of(document: &Document, id: NodeId) -> Option<NodeId> {
+    document.node(id).next_sibling().map(|node| node.id())
+}
+
 /// Returns the annotation of a block level element.
 ///
-/// Returns None if the element is not annotated.
-fn get_annotation(node: &Node) -> HowserResult<MatchType> {
+/// Returns `MatchType::None` if the element is not annotated.
+pub fn get_annotation(node: &Node) -> HowserResult<MatchType> {
     trace!("document::get_annotation");
-    match node {
-        Node::Paragraph(_) => get_paragraph_annotation(node),
-        Node::BlockQuote(_) => get_block_quote_annotation(node),
-        Node::CodeBlock(_) => get_code_block_annotation(node),
-        Node::Heading(_) => get_heading_annotation(node),
-        Node::Item(_) => get_item_annotation(node),
+    match node.kind() {
+        &NodeKind::Paragraph => get_paragraph_annotation(node),
+        &NodeKind::BlockQuote => get_block_quote_annotation(node),
+        &NodeKind::CodeBlock { .. } => get_code_block_annotation(node),
+        &NodeKind::Heading(_) => get_heading_annotation(node),
+        &NodeKind::Item => get_item_annotation(node),
         _ => Ok(MatchType::None),
     }
 }
 
 /// Strips the annotation from a block level element if one exists.
-fn remove_annotation(node: &mut Node) -> HowserResult<()> {
+fn remove_annotation(tree: &mut Tree, id: NodeId) -> HowserResult<()> {
     trace!("remove_annotation()");
-    match node {
-        Node::Paragraph(_) => remove_paragraph_annotation(node),
-        Node::BlockQuote(_) => remove_block_quote_annotation(node),
-        Node::CodeBlock(_) => remove_code_block_annotation(node),
-        Node::Heading(_) => remove_heading_annotation(node),
-        Node::Item(_) => remove_item_annotation(node),
+    let kind = tree.node(id).kind().clone();
+    match kind {
+        NodeKind::Paragraph => remove_paragraph_annotation(tree, id),
+        NodeKind::BlockQuote => remove_block_quote_annotation(tree, id),
+        NodeKind::CodeBlock { .. } => remove_code_block_annotation(tree, id),
+        NodeKind::Heading(_) => remove_heading_annotation(tree, id),
+        NodeKind::Item => remove_item_annotation(tree, id),
         _ => Ok(()),
     }
 }
@@ -331,18 +407,18 @@ fn remove_annotation(node: &mut Node) -> HowserResult<()> {
 /// Returns the block level annotation of a paragraph node if one exists.
 fn get_paragraph_annotation(node: &Node) -> HowserResult<MatchType> {
     trace!("get_paragraph_annotation()");
-    if let Some(text_node @ Node::Text(_)) = node.first_child()? {
-        match text_node.next_sibling()? {
-            Some(Node::SoftBreak(_)) | None => {
-                if let Node::Text(ref text) = text_node {
-                    let content = text.get_content()?;
-                    let (token, remainder) = extract_match_type(&content)?;
-                    if token != MatchType::None && remainder.is_empty() {
-                        return Ok(token);
-                    }
+    if let Some(text_node) = node.first_child() {
+        if let &NodeKind::Text(ref content) = text_node.kind() {
+            let ends_line = match text_node.next_sibling() {
+                Some(sibling) => sibling.kind() == &NodeKind::SoftBreak,
+                None => true,
+            };
+            if ends_line {
+                let (token, remainder) = extract_match_type(content)?;
+                if token != MatchType::None && remainder.is_empty() {
+                    return Ok(token);
                 }
             }
-            _ => (),
         }
     }
 
@@ -350,46 +426,47 @@ fn get_paragraph_annotation(node: &Node) -> HowserResult<MatchType> {
 }
 
 /// Strips the block-level annotation from a paragraph node if one exists.
-fn remove_paragraph_annotation(node: &mut Node) -> HowserResult<()> {
-    if let Some(mut text_node @ Node::Text(_)) = node.first_child()? {
-        let sibling = text_node.next_sibling()?;
-        match sibling {
-            Some(Node::SoftBreak(_)) | None => {
-                let match_result = match text_node {
-                    Node::Text(ref text) => extract_match_type(&text.get_content()?)?,
-                    _ => (MatchType::None, String::new()),
-                };
-                let (token, remainder) = match_result;
-                if token != MatchType::None && remainder.is_empty() {
-                    text_node.unlink();
-                    if let Some(mut node) = sibling {
-                        node.unlink();
-                    }
+fn remove_paragraph_annotation(tree: &mut Tree, id: NodeId) -> HowserResult<()> {
+    let mut annotation_nodes = Vec::new();
+    {
+        let node = tree.node(id);
+        if get_paragraph_annotation(&node)? != MatchType::None {
+            if let Some(text_node) = node.first_child() {
+                annotation_nodes.push(text_node.id());
+                if let Some(line_break) = text_node.next_sibling() {
+                    annotation_nodes.push(line_break.id());
                 }
             }
-            _ => (),
         }
     }
 
+    for annotation_node in annotation_nodes {
+        tree.unlink(annotation_node);
+    }
+
     Ok(())
 }
 
 /// Returns the block-level annotation from a block quote node if one exists.
 fn get_block_quote_annotation(node: &Node) -> HowserResult<MatchType> {
-    if let Some(paragraph_node @ Node::Paragraph(_)) = node.first_child()? {
-        get_paragraph_annotation(&paragraph_node)
-    } else {
-        Ok(MatchType::None)
+    match node.first_child() {
+        Some(ref paragraph_node) if paragraph_node.kind() == &NodeKind::Paragraph => {
+            get_paragraph_annotation(paragraph_node)
+        }
+        _ => Ok(MatchType::None),
     }
 }
 
 /// Strips the block-level annotation from a block quote node if one exists.
-fn remove_block_quote_annotation(node: &mut Node) -> HowserResult<()> {
-    if let Some(Node::Paragraph(_)) = node.first_child()? {
-        remove_paragraph_annotation(node)?;
-        if node.first_child()?.is_none() {
-            node.unlink();
-        }
+fn remove_block_quote_annotation(tree: &mut Tree, id: NodeId) -> HowserResult<()> {
+    let paragraph_id = match tree.node(id).first_child() {
+        Some(ref node) if node.kind() == &NodeKind::Paragraph => node.id(),
+        _ => return Ok(()),
+    };
+
+    remove_paragraph_annotation(tree, paragraph_id)?;
+    if tree.node(paragraph_id).first_child().is_none() {
+        tree.unlink(paragraph_id);
     }
 
     Ok(())
@@ -397,8 +474,8 @@ fn remove_block_quote_annotation(node: &mut Node) -> HowserResult<()> {
 
 /// Returns the annotation of a code block node if one exists.
 fn get_code_block_annotation(node: &Node) -> HowserResult<MatchType> {
-    if let Node::CodeBlock(code_block) = node {
-        let (match_type, _) = extract_match_type(&code_block.get_fence_info()?)?;
+    if let &NodeKind::CodeBlock { ref info, .. } = node.kind() {
+        let (match_type, _) = extract_match_type(info)?;
         Ok(match_type)
     } else {
         Ok(MatchType::None)
@@ -406,11 +483,11 @@ fn get_code_block_annotation(node: &Node) -> HowserResult<MatchType> {
 }
 
 /// Strips the annotation from a code block node if one exists.
-fn remove_code_block_annotation(node: &mut Node) -> HowserResult<()> {
-    if let Node::CodeBlock(ref mut code_block) = node {
-        let (match_type, content) = extract_match_type(&code_block.get_fence_info()?)?;
+fn remove_code_block_annotation(tree: &mut Tree, id: NodeId) -> HowserResult<()> {
+    if let &mut NodeKind::CodeBlock { ref mut info, .. } = tree.kind_mut(id) {
+        let (match_type, content) = extract_match_type(info)?;
         if match_type != MatchType::None {
-            code_block.set_fence_info(&content)?;
+            *info = content;
         }
     }
 
@@ -419,15 +496,15 @@ fn remove_code_block_annotation(node: &mut Node) -> HowserResult<()> {
 
 /// Returns the block-level annotation of a heading node if one exists.
 fn get_heading_annotation(heading_node: &Node) -> HowserResult<MatchType> {
-    let text_node = match heading_node.first_child()? {
+    let text_node = match heading_node.first_child() {
         Some(node) => node,
         None => return Ok(MatchType::None)
     };
-    if text_node.next_sibling()?.is_some() {
+    if text_node.next_sibling().is_some() {
         return Ok(MatchType::None);
     }
-    if let Node::Text(ref text) = text_node {
-        let (match_type, content) = extract_match_type(&text.get_content()?)?;
+    if let &NodeKind::Text(ref text) = text_node.kind() {
+        let (match_type, content) = extract_match_type(text)?;
         if match_type != MatchType::None && content.is_empty() {
             return Ok(match_type);
         }
@@ -437,18 +514,17 @@ fn get_heading_annotation(heading_node: &Node) -> HowserResult<MatchType> {
 }
 
 /// Strips the block-level annotation from a heading node if one exists.
-fn remove_heading_annotation(heading_node: &Node) -> HowserResult<()> {
-    if let Some(mut text_node @ Node::Text(_)) = heading_node.first_child()? {
-        let match_result = match text_node {
-            Node::Text(ref text) => extract_match_type(&text.get_content()?)?,
-            _ => (MatchType::None, String::new()),
-        };
-        if text_node.next_sibling()?.is_none() {
-            let (match_type, content) = match_result;
-            if match_type != MatchType::None && content.is_empty() {
-                text_node.unlink();
-            }
-        }
+fn remove_heading_annotation(tree: &mut Tree, id: NodeId) -> HowserResult<()> {
+    let annotation_node = match tree.node(id).first_child() {
+        Some(text_node) => match get_heading_annotation(&tree.node(id))? {
+            MatchType::None => None,
+            _ => Some(text_node.id()),
+        },
+        None => None,
+    };
+
+    if let Some(annotation_node) = annotation_node {
+        tree.unlink(annotation_node);
     }
 
     Ok(())
@@ -456,21 +532,24 @@ fn remove_heading_annotation(heading_node: &Node) -> HowserResult<()> {
 
 /// Returns the block-level annotation from a list item node if one exists.
 fn get_item_annotation(item_node: &Node) -> HowserResult<MatchType> {
-    if let Some(paragraph_node @ Node::Paragraph(_)) = item_node.first_child()? {
-        get_paragraph_annotation(&paragraph_node)
-    } else {
-        Ok(MatchType::None)
+    match item_node.first_child() {
+        Some(ref paragraph_node) if paragraph_node.kind() == &NodeKind::Paragraph => {
+            get_paragraph_annotation(paragraph_node)
+        }
+        _ => Ok(MatchType::None),
     }
 }
 
 /// Strips the block-level annotation from a list item node if one exists.
-fn remove_item_annotation(item_node: &Node) -> HowserResult<()> {
-    if let Some(mut paragraph_node @ Node::Paragraph(_)) = item_node.first_child()? {
-        let token = get_paragraph_annotation(&paragraph_node)?;
-        if token != MatchType::None {
-            remove_paragraph_annotation(&mut paragraph_node)?;
-            paragraph_node.unlink();
-        }
+fn remove_item_annotation(tree: &mut Tree, id: NodeId) -> HowserResult<()> {
+    let paragraph_id = match tree.node(id).first_child() {
+        Some(ref node) if node.kind() == &NodeKind::Paragraph => node.id(),
+        _ => return Ok(()),
+    };
+
+    if get_paragraph_annotation(&tree.node(paragraph_id))? != MatchType::None {
+        remove_paragraph_annotation(tree, paragraph_id)?;
+        tree.unlink(paragraph_id);
     }
 
     Ok(())
@@ -478,20 +557,23 @@ fn remove_item_annotation(item_node: &Node) -> HowserResult<()> {
 
 /// Sets the match type of a list node based on the match types of its children.
 ///
-/// If any children are marked Mandatory, then the list is also mandatory. Otherwise, it is optional.
+/// If any children are marked Mandatory, then the list is also mandatory. Otherwise, it is
+/// recommended if any children are recommended, and optional if none are.
 /// This is necessary because lists nodes are meta-nodes in cmark and will only appear as containers
 /// for list items if they are present.
-fn annotate_circumstantial_node(target_node: &Node, document: &Document) -> HowserResult<()> {
-    document.set_match_type(target_node, MatchType::Optional);
-    let mut child = target_node.first_child()?;
-    while let Some(node) = child {
-        if document.get_match_type(&node)? == MatchType::Mandatory {
-            document.set_match_type(&target_node, MatchType::Mandatory);
-            return Ok(());
-        } else {
-            child = node.next_sibling()?;
+fn annotate_circumstantial_node(target: NodeId, document: &mut Document) -> HowserResult<()> {
+    let mut match_type = MatchType::Optional;
+    for node in document.node(target).children() {
+        match document.get_match_type(&node)? {
+            MatchType::Mandatory => {
+                match_type = MatchType::Mandatory;
+                break;
+            }
+            MatchType::Recommended => match_type = MatchType::Recommended,
+            _ => (),
         }
     }
+    document.set_match_type(target, match_type);
 
     Ok(())
 }
@@ -511,6 +593,7 @@ fn extract_match_type(content: &String) -> HowserResult<(MatchType, String)> {
             let match_type = match prompt {
                 MANDATORY_PROMPT => MatchType::Mandatory,
                 OPTIONAL_PROMPT => MatchType::Optional,
+                RECOMMENDED_PROMPT => MatchType::Recommended,
                 DITTO_TOKEN => MatchType::Repeatable,
                 _ => MatchType::None,
             };
@@ -525,19 +608,180 @@ fn extract_match_type(content: &String) -> HowserResult<(MatchType, String)> {
 }
 
 /// Strips all html from the document.
-fn strip_comments(root: &Node) -> HowserResult<()> {
-    for (mut node, _) in root.iter() {
-        match node {
-            Node::HtmlInline(_) | Node::HtmlBlock(_) => {
-                node.unlink();
+///
+/// The text of inline comments, and of comments written in code block info strings, is kept as
+/// the comment of the element it annotates, so that hints like `-!!-<!-- Project Name -->` remain
+/// available.
+fn strip_comments(document: &mut Document) -> HowserResult<()> {
+    let mut html_nodes = Vec::new();
+    let mut comments = Vec::new();
+    let mut info_comments = Vec::new();
+
+    for node in document.root().descendants() {
+        match node.kind() {
+            &NodeKind::HtmlInline(ref literal) => {
+                if let (Some(parent), Some(comment)) = (node.parent(), comment_text(literal)) {
+                    comments.push((annotated_element(parent).id(), comment));
+                }
+                html_nodes.push(node.id());
+            }
+            &NodeKind::HtmlBlock(_) => html_nodes.push(node.id()),
+            &NodeKind::CodeBlock { ref info, .. } => {
+                if let Some(start) = info.find("<!--") {
+                    if let Some(comment) = comment_text(&info[start..]) {
+                        comments.push((node.id(), comment));
+                        info_comments.push((node.id(), start));
+                    }
+                }
             }
             _ => (),
         }
     }
 
+    for id in html_nodes {
+        document.tree.unlink(id);
+    }
+    for (id, start) in info_comments {
+        if let &mut NodeKind::CodeBlock { ref mut info, .. } = document.tree.kind_mut(id) {
+            info.truncate(start);
+        }
+    }
+    for (id, comment) in comments {
+        document.set_comment(id, comment);
+    }
+
     Ok(())
 }
 
+/// Reads the suppression directives from the html comments of a document.
+///
+/// Directives are block level comments such as `<!-- howser-suppress-next HX0006 -->`, naming
+/// one or more codes separated by spaces or commas.
+fn collect_suppressions(document: &Document) -> HowserResult<Vec<Suppression>> {
+    let pattern = Regex::new(SUPPRESSION_DIRECTIVE_PATTERN)?;
+    let mut suppressions = Vec::new();
+
+    for node in document.root().descendants() {
+        let comment = match node.kind() {
+            &NodeKind::HtmlBlock(ref literal) => comment_text(literal),
+            _ => None,
+        };
+        let captures = match comment.as_ref().and_then(|comment| pattern.captures(comment)) {
+            Some(captures) => captures,
+            None => continue,
+        };
+
+        let scope = match &captures[1] {
+            "next" => SuppressionScope::NextBlock,
+            "section" => SuppressionScope::Section,
+            _ => SuppressionScope::File,
+        };
+        let mut codes = Vec::new();
+        let mut unknown_codes = Vec::new();
+        let names = captures.get(2).map(|names| names.as_str()).unwrap_or("");
+        for name in names.split(|c: char| c == ',' || c.is_whitespace()) {
+            match (name.is_empty(), ErrorCode::parse(name)) {
+                (true, _) => (),
+                (false, Some(code)) => codes.push(code),
+                (false, None) => unknown_codes.push(name.to_string()),
+            }
+        }
+
+        suppressions.push(Suppression {
+            scope,
+            codes,
+            unknown_codes,
+            span: node.span(),
+            lines: suppressed_lines(&node, scope),
+        });
+    }
+
+    Ok(suppressions)
+}
+
+/// Returns the first and last lines covered by a suppression directive at the given node.
+///
+/// The range is empty when there is nothing for the directive to cover.
+fn suppressed_lines(directive: &Node, scope: SuppressionScope) -> (usize, usize) {
+    let span = directive.span();
+    match scope {
+        SuppressionScope::NextBlock => match directive.next_sibling() {
+            Some(next) => (next.span().start_line, next.span().end_line),
+            None => (span.end_line + 1, span.end_line),
+        },
+        SuppressionScope::Section => {
+            let mut top_level = *directive;
+            while let Some(parent) = top_level.parent() {
+                if parent.id() == ROOT_ID {
+                    break;
+                }
+                top_level = parent;
+            }
+            let heading_level = |node: &Node| match node.kind() {
+                &NodeKind::Heading(level) => Some(level),
+                _ => None,
+            };
+
+            let mut section_heading = top_level.prev_sibling();
+            while let Some(node) = section_heading {
+                if heading_level(&node).is_some() {
+                    break;
+                }
+                section_heading = node.prev_sibling();
+            }
+            let level = section_heading.and_then(|node| heading_level(&node));
+
+            let mut next_heading = top_level.next_sibling();
+            while let Some(node) = next_heading {
+                match (heading_level(&node), level) {
+                    (Some(next_level), Some(level)) if next_level <= level => break,
+                    (Some(_), None) => break,
+                    _ => next_heading = node.next_sibling(),
+                }
+            }
+
+            let start = section_heading.map(|node| node.span()).unwrap_or(span);
+            let end = match next_heading {
+                Some(node) => node.span().start_line.saturating_sub(1),
+                None => usize::max_value(),
+            };
+            (start.start_line, end)
+        }
+        SuppressionScope::File => (1, usize::max_value()),
+    }
+}
+
+/// Returns the inner text of an html comment, or `None` if the html is not a comment.
+fn comment_text(html: &str) -> Option<String> {
+    let html = html.trim();
+    if html.starts_with("<!--") && html.ends_with("-->") && html.len() >= 7 {
+        Some(html[4..html.len() - 3].trim().to_string())
+    } else {
+        None
+    }
+}
+
+/// Returns the element that the given inline content belongs to.
+///
+/// The first paragraph of a list item or block quote holds the annotation of its container.
+fn annotated_element(node: Node) -> Node {
+    let mut block = node;
+    while !block.kind().is_block() {
+        match block.parent() {
+            Some(parent) => block = parent,
+            None => break,
+        }
+    }
+
+    match (block.kind(), block.parent(), block.prev_sibling()) {
+        (&NodeKind::Paragraph, Some(parent), None) => match parent.kind() {
+            &NodeKind::Item | &NodeKind::BlockQuote => parent,
+            _ => block,
+        },
+        _ => block,
+    }
+}
+
 /// Abstraction of the different types of block level Rx Elements and their match context.
 ///
 /// These are used to parse block level annotations. Integrated types are elements whose annotations
@@ -546,21 +790,21 @@ fn strip_comments(root: &Node) -> HowserResult<()> {
 #[derive(Debug)]
 enum LookaheadType {
     /// Integrated element with no annotation.
-    IntegratedLiteral(Node),
+    IntegratedLiteral(NodeId),
     /// Integrated element with annotation and content.
-    IntegratedOccupied(Node, MatchType),
+    IntegratedOccupied(NodeId, MatchType),
     /// Integrated element with annotation, but no content.
-    IntegratedVacant(Node, MatchType),
+    IntegratedVacant(NodeId, MatchType),
     /// Discrete element that is an annotation.
-    DiscreteAnnotated(Node, MatchType),
+    DiscreteAnnotated(NodeId, MatchType),
     /// Discrete element that is content.
-    DiscreteLiteral(Node),
+    DiscreteLiteral(NodeId),
     /// Any element that has a ditto annotation.
-    Ditto(Node),
+    Ditto(NodeId),
     /// Just list elements.
-    List(Node),
+    List(NodeId),
     /// Elements that do not support annotation or no element.
-    Other(Option<Node>),
+    Other(Option<NodeId>),
 }
 
 impl LookaheadType {
@@ -569,30 +813,33 @@ impl LookaheadType {
         trace!("LookaheadType::new");
         if let Some(node) = node {
             let match_type = get_annotation(&node)?;
+            let id = node.id();
             debug!("LookaheadType::new: MatchType: {:?}", match_type);
-            match node {
+            match node.kind() {
                 // List
-                Node::List(_) => Ok(LookaheadType::List(node)),
+                &NodeKind::List(_) => Ok(LookaheadType::List(id)),
                 // Integrated
-                Node::Paragraph(_) | Node::BlockQuote(_) | Node::CodeBlock(_) => match match_type {
-                    MatchType::Repeatable => Ok(LookaheadType::Ditto(node)),
-                    MatchType::None => Ok(LookaheadType::IntegratedLiteral(node)),
-                    _ => {
-                        if LookaheadType::is_vacant(&node)? {
-                            Ok(LookaheadType::IntegratedVacant(node, match_type))
-                        } else {
-                            Ok(LookaheadType::IntegratedOccupied(node, match_type))
+                &NodeKind::Paragraph | &NodeKind::BlockQuote | &NodeKind::CodeBlock { .. } => {
+                    match match_type {
+                        MatchType::Repeatable => Ok(LookaheadType::Ditto(id)),
+                        MatchType::None => Ok(LookaheadType::IntegratedLiteral(id)),
+                        _ => {
+                            if LookaheadType::is_vacant(&node)? {
+                                Ok(LookaheadType::IntegratedVacant(id, match_type))
+                            } else {
+                                Ok(LookaheadType::IntegratedOccupied(id, match_type))
+                            }
                         }
                     }
-                },
+                }
                 // Discrete
-                Node::Heading(_) | Node::Item(_) => match match_type {
-                    MatchType::Repeatable => Ok(LookaheadType::Ditto(node)),
-                    MatchType::None => Ok(LookaheadType::DiscreteLiteral(node)),
-                    _ => Ok(LookaheadType::DiscreteAnnotated(node, match_type)),
+                &NodeKind::Heading(_) | &NodeKind::Item => match match_type {
+                    MatchType::Repeatable => Ok(LookaheadType::Ditto(id)),
+                    MatchType::None => Ok(LookaheadType::DiscreteLiteral(id)),
+                    _ => Ok(LookaheadType::DiscreteAnnotated(id, match_type)),
                 },
                 // Other
-                _ => Ok(LookaheadType::Other(Some(node))),
+                _ => Ok(LookaheadType::Other(Some(id))),
             }
         } else {
             Ok(LookaheadType::Other(None))
@@ -601,11 +848,11 @@ impl LookaheadType {
 
     /// Determines whether the given integrated node has content other than its annotation.
     fn is_vacant(node: &Node) -> HowserResult<bool> {
-        match node {
-            Node::CodeBlock(ref code_block) => Ok(code_block.get_content()?.is_empty()),
+        match node.kind() {
+            &NodeKind::CodeBlock { ref literal, .. } => Ok(literal.is_empty()),
             _ => {
-                if let Some(annotation) = node.first_child()? {
-                    Ok(annotation.next_sibling()?.is_none())
+                if let Some(annotation) = node.first_child() {
+                    Ok(annotation.next_sibling().is_none())
                 } else {
                     Err(HowserError::RuntimeError(
                         "Lookahead Error: Got a match type, but no annotation node was present."
@@ -620,9 +867,10 @@ impl LookaheadType {
 #[cfg(test)]
 mod tests {
     use super::process_child_block_elements;
-    use super::Document;
+    use super::{Document, Prescription};
+    use ast::{NodeKind, ROOT_ID};
+    use backends::parse_document;
     use data::{MatchType, PromptToken};
-    use doogie::{parse_document, Node};
     use helpers::test::strategies::cmark::arb_paragraph_match;
     use helpers::test::strategies::helpers::serialize_match_seq;
     use proptest::prelude::*;
@@ -637,12 +885,12 @@ mod tests {
             let (template_content, _) = serialize_match_seq(paragraph);
             let mut test_template = template_content.clone();
             test_template.insert_str(0, &format!("{}\n", prompt.to_string()));
-            let root = parse_document(&test_template);
-            let document = Document::new(&root, None)?;
+            let tree = parse_document(&test_template)?;
+            let mut document = Document::new(tree, None)?;
 
-            process_child_block_elements(&document.root, &document).unwrap();
+            process_child_block_elements(ROOT_ID, &mut document).unwrap();
 
-            if let Some(paragraph_node) = document.root.first_child().unwrap() {
+            if let Some(paragraph_node) = document.root().first_child() {
                 let match_type = document.get_match_type(&paragraph_node).unwrap();
                 match prompt {
                     PromptToken::Mandatory => assert_eq!(match_type, MatchType::Mandatory),
@@ -650,9 +898,8 @@ mod tests {
                     _ => ()
                 }
                 assert!(! document.is_wildcard(&paragraph_node));
-                if let Some(Node::Text(ref text)) = paragraph_node.first_child().unwrap() {
-                    let processed_content = text.get_content().unwrap();
-                    assert_eq!(processed_content, template_content);
+                if let Some(&NodeKind::Text(ref processed_content)) = paragraph_node.first_child().map(|node| node.kind()) {
+                    assert_eq!(processed_content, &template_content);
                 } else {
                     panic!("No text node found");
                 }
@@ -664,39 +911,39 @@ mod tests {
         #[test]
         fn test_wildcard_paragraphs_are_processed(ref prompt in prop_oneof![Just(PromptToken::Mandatory),Just(PromptToken::Optional)]) {
             let template_content = prompt.to_string();
-            let root = parse_document(&template_content);
-            let document = Document::new(&root, None).unwrap();
-
-            process_child_block_elements(&document.root, &document).unwrap();
-
-            if let Some(paragraph @ Node::Paragraph(_)) = document.root.first_child().unwrap() {
-                let match_type = document.get_match_type(&paragraph).unwrap();
-                match prompt {
-                    PromptToken::Mandatory => assert_eq!(match_type, MatchType::Mandatory),
-                    PromptToken::Optional => assert_eq!(match_type, MatchType::Optional),
-                    _ => ()
+            let tree = parse_document(&template_content).unwrap();
+            let mut document = Document::new(tree, None).unwrap();
+
+            process_child_block_elements(ROOT_ID, &mut document).unwrap();
+
+            match document.root().first_child() {
+                Some(ref paragraph) if paragraph.kind() == &NodeKind::Paragraph => {
+                    let match_type = document.get_match_type(paragraph).unwrap();
+                    match prompt {
+                        PromptToken::Mandatory => assert_eq!(match_type, MatchType::Mandatory),
+                        PromptToken::Optional => assert_eq!(match_type, MatchType::Optional),
+                        _ => ()
+                    }
+                    assert!(document.is_wildcard(paragraph));
+                    assert!(paragraph.first_child().is_none());
                 }
-                assert!(document.is_wildcard(&paragraph));
-                assert!(paragraph.first_child().unwrap().is_none());
-            } else {
-                panic!("No paragraph node found");
+                _ => panic!("No paragraph node found"),
             }
         }
 
         #[test]
         fn test_literal_paragraphs_are_processed(ref paragraph in arb_paragraph_match(2..10)) {
             let (template_content, _) = serialize_match_seq(paragraph);
-            let root = parse_document(&template_content);
-            let document = Document::new(&root, None)?;
+            let tree = parse_document(&template_content)?;
+            let mut document = Document::new(tree, None)?;
 
-            process_child_block_elements(&root, &document).unwrap();
+            process_child_block_elements(ROOT_ID, &mut document).unwrap();
 
-            if let Some(paragraph_node) = document.root.first_child().unwrap() {
+            if let Some(paragraph_node) = document.root().first_child() {
                 assert_eq!(document.get_match_type(&paragraph_node).unwrap(), MatchType::Mandatory);
                 assert!(! document.is_wildcard(&paragraph_node));
-                if let Some(Node::Text(ref text)) = paragraph_node.first_child().unwrap() {
-                    let processed_content = text.get_content().unwrap();
-                    assert_eq!(processed_content, template_content);
+                if let Some(&NodeKind::Text(ref processed_content)) = paragraph_node.first_child().map(|node| node.kind()) {
+                    assert_eq!(processed_content, &template_content);
                 } else {
                     panic!("No text node found");
                 }
@@ -711,24 +958,56 @@ mod tests {
             let mut test_template = template_content.clone();
             test_template.push_str("\n\n-\"\"-");
 
-            let root = parse_document(&test_template);
-            let document = Document::new(&root, None)?;
+            let tree = parse_document(&test_template)?;
+            let mut document = Document::new(tree, None)?;
 
-            process_child_block_elements(&root, &document).unwrap();
+            process_child_block_elements(ROOT_ID, &mut document).unwrap();
 
-            if let Some(paragraph @ Node::Paragraph(_)) = document.root.first_child().expect("No Paragraph Node found") {
-                assert_eq!(document.get_match_type(&paragraph).unwrap(), MatchType::Mandatory);
-                assert!(! document.is_wildcard(&paragraph));
+            match document.root().first_child() {
+                Some(ref paragraph) if paragraph.kind() == &NodeKind::Paragraph => {
+                    assert_eq!(document.get_match_type(paragraph).unwrap(), MatchType::Mandatory);
+                    assert!(! document.is_wildcard(paragraph));
 
-                if let Some(Node::Text(ref text)) = paragraph.first_child().expect("No Text Node found") {
-                    let processed_content = text.get_content().unwrap();
-                    assert_eq!(processed_content, template_content);
-                }
+                    if let Some(&NodeKind::Text(ref processed_content)) = paragraph.first_child().map(|node| node.kind()) {
+                        assert_eq!(processed_content, &template_content);
+                    }
 
-                if let Some(ditto_node @ Node::Paragraph(_)) = paragraph.first_child().unwrap().unwrap().next_sibling().expect("No ditto node found") {
-                    assert_eq!(document.get_match_type(&ditto_node).unwrap(), MatchType::Repeatable);
+                    if let Some(ditto_node) = paragraph.next_sibling() {
+                        assert_eq!(document.get_match_type(&ditto_node).unwrap(), MatchType::Repeatable);
+                    } else {
+                        panic!("No ditto node found");
+                    }
                 }
+                _ => panic!("No Paragraph Node found"),
             }
         }
     }
+
+    #[test]
+    fn test_documents_can_be_sent_between_threads() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Document>();
+        assert_send::<Prescription>();
+    }
+
+    #[test]
+    fn test_source_tree_is_left_intact() {
+        let tree = parse_document("Some text\n\n<!-- A comment -->\n\n-!!-").unwrap();
+        let rx = Prescription::new(tree.clone(), None).unwrap();
+
+        assert_eq!(tree.root().children().count(), 3);
+        assert_eq!(rx.document.root().children().count(), 2);
+    }
+
+    #[test]
+    fn test_prescription_from_text() {
+        let rx = Prescription::from_text("-??-\nSome text", Some("test.rx".to_string())).unwrap();
+        let paragraph = rx.document.first_of_type("paragraph").unwrap();
+
+        assert_eq!(rx.document.filename, Some("test.rx".to_string()));
+        assert_eq!(
+            rx.document.get_match_type(&paragraph).unwrap(),
+            MatchType::Optional
+        );
+    }
 }
@@ -8,12 +8,14 @@ use self::regex::Error as RegexError;
 use self::termion::color;
 use self::termion::style;
 use self::toml::de::Error as TomlError;
-use data::ContentMatchPair;
+use ast::{ListType, Node, NodeKind, Span};
+use data::{ContentMatchPair, PromptToken};
 use document::{Document, Prescription};
+#[cfg(feature = "cmark")]
 use doogie::errors::DoogieError;
-use doogie::Node;
 use helpers::cli;
 use helpers::cli::ShellText;
+use helpers::fuzzy;
 use std::error;
 use std::fmt;
 use std::io::Error as IOError;
@@ -21,11 +23,12 @@ use std::io::Error as IOError;
 /// Crate-wide Result type.
 pub type HowserResult<T> = Result<T, HowserError>;
 /// Type alias for `Reportable` trait object
-pub type ValidationProblem = Box<Reportable>;
+pub type ValidationProblem = Box<Reportable + Send + Sync>;
 
 /// Error types for use with `HowserResult`.
 #[derive(Debug)]
 pub enum HowserError {
+    #[cfg(feature = "cmark")]
     DoogieError(DoogieError),
     IOError(IOError),
     Usage(String),
@@ -40,21 +43,24 @@ impl error::Error for HowserError {
     fn description(&self) -> &str {
         match self {
             &HowserError::Usage(ref message) => message.as_str(),
+            #[cfg(feature = "cmark")]
             &HowserError::DoogieError(ref error) => error.description(),
             &HowserError::IOError(ref error) => error.description(),
             &HowserError::RuntimeError(ref message) => message,
             &HowserError::CapabilityError => "Capability Error",
             &HowserError::RegexError(ref error) => error.description(),
             &HowserError::PrescriptionError(_) => "Prescription Error",
-            &HowserError::TomlError(_) => "TomlError",
+            &HowserError::TomlError(_) => "Error parsing TOML",
         }
     }
 
     fn cause(&self) -> Option<&error::Error> {
         match self {
+            #[cfg(feature = "cmark")]
             &HowserError::DoogieError(ref error) => Some(error),
             &HowserError::IOError(ref error) => Some(error),
             &HowserError::RegexError(ref error) => Some(error),
+            &HowserError::TomlError(ref error) => Some(error),
             _ => None,
         }
     }
@@ -72,6 +78,7 @@ impl From<IOError> for HowserError {
     }
 }
 
+#[cfg(feature = "cmark")]
 impl From<DoogieError> for HowserError {
     fn from(err: DoogieError) -> Self {
         HowserError::DoogieError(err)
@@ -98,59 +105,662 @@ pub trait Reportable {
     /// Report in verbose multiline format.
     fn long_msg(&self) -> String;
 
-    /// Error code
-    fn code(&self) -> u32;
+    /// Stable code identifying the kind of problem.
+    fn code(&self) -> ErrorCode;
+
+    /// Where the problem is located in the prescription, if anywhere.
+    fn prescription_location(&self) -> Option<Location>;
+
+    /// Where the problem is located in the document, if anywhere.
+    fn document_location(&self) -> Option<Location>;
+
+    /// How serious the problem is.
+    fn severity(&self) -> Severity {
+        self.code().severity()
+    }
+}
+
+/// How serious a problem is.
+///
+/// Errors make a document invalid. Warnings are reported, but only fail validation when warnings
+/// are denied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Stable codes for each kind of problem that Howser reports.
+///
+/// Codes are displayed as `HX` followed by a four digit number. Numbers are never reused, so
+/// teams can rely on them when documenting or filtering problems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ErrorCode {
+    /// HX0001
+    MissingMandatoryBlock,
+    /// HX0002
+    SuperfluousBlockContent,
+    /// HX0003
+    MissingMandatoryInline,
+    /// HX0004
+    SuperfluousInlineContent,
+    /// HX0005
+    TypeMismatch,
+    /// HX0006
+    TextualContentMismatch,
+    /// HX0007
+    LinkDestinationMismatch,
+    /// HX0008
+    LinkTitleMismatch,
+    /// HX0009
+    OrphanedDitto,
+    /// HX0010
+    AmbiguousOptionalElements,
+    /// HX0011
+    UnterminatedRepetition,
+    /// HX0012
+    IgnoredPrompt,
+    /// HX0013
+    MalformedPrompt,
+    /// HX0014
+    UnusedSuppression,
+    /// HX0015
+    MissingRecommendedBlock,
+    /// HX0016
+    UnknownPharmacyKey,
+    /// HX0017
+    InvalidPharmacyValue,
+    /// HX0018
+    MissingPharmacyFile,
+}
+
+impl ErrorCode {
+    /// Every code, in numerical order.
+    pub const ALL: [ErrorCode; 18] = [
+        ErrorCode::MissingMandatoryBlock,
+        ErrorCode::SuperfluousBlockContent,
+        ErrorCode::MissingMandatoryInline,
+        ErrorCode::SuperfluousInlineContent,
+        ErrorCode::TypeMismatch,
+        ErrorCode::TextualContentMismatch,
+        ErrorCode::LinkDestinationMismatch,
+        ErrorCode::LinkTitleMismatch,
+        ErrorCode::OrphanedDitto,
+        ErrorCode::AmbiguousOptionalElements,
+        ErrorCode::UnterminatedRepetition,
+        ErrorCode::IgnoredPrompt,
+        ErrorCode::MalformedPrompt,
+        ErrorCode::UnusedSuppression,
+        ErrorCode::MissingRecommendedBlock,
+        ErrorCode::UnknownPharmacyKey,
+        ErrorCode::InvalidPharmacyValue,
+        ErrorCode::MissingPharmacyFile,
+    ];
+
+    /// Returns the number of this code.
+    pub fn number(&self) -> u32 {
+        match self {
+            &ErrorCode::MissingMandatoryBlock => 1,
+            &ErrorCode::SuperfluousBlockContent => 2,
+            &ErrorCode::MissingMandatoryInline => 3,
+            &ErrorCode::SuperfluousInlineContent => 4,
+            &ErrorCode::TypeMismatch => 5,
+            &ErrorCode::TextualContentMismatch => 6,
+            &ErrorCode::LinkDestinationMismatch => 7,
+            &ErrorCode::LinkTitleMismatch => 8,
+            &ErrorCode::OrphanedDitto => 9,
+            &ErrorCode::AmbiguousOptionalElements => 10,
+            &ErrorCode::UnterminatedRepetition => 11,
+            &ErrorCode::IgnoredPrompt => 12,
+            &ErrorCode::MalformedPrompt => 13,
+            &ErrorCode::UnusedSuppression => 14,
+            &ErrorCode::MissingRecommendedBlock => 15,
+            &ErrorCode::UnknownPharmacyKey => 16,
+            &ErrorCode::InvalidPharmacyValue => 17,
+            &ErrorCode::MissingPharmacyFile => 18,
+        }
+    }
+
+    /// Returns the severity of problems with this code.
+    pub fn severity(&self) -> Severity {
+        match self {
+            &ErrorCode::UnusedSuppression | &ErrorCode::MissingRecommendedBlock => {
+                Severity::Warning
+            }
+            _ => Severity::Error,
+        }
+    }
+
+    /// Parses a code such as `HX0001`. The prefix and leading zeros are optional and case is
+    /// ignored.
+    pub fn parse(code: &str) -> Option<ErrorCode> {
+        let code = code.trim().to_uppercase();
+        let digits = code.trim_left_matches("HX");
+        let number = digits.parse::<u32>().ok()?;
+
+        ErrorCode::ALL
+            .iter()
+            .find(|error_code| error_code.number() == number)
+            .cloned()
+    }
+
+    /// Returns a one line summary of the problem.
+    pub fn summary(&self) -> &'static str {
+        match self {
+            &ErrorCode::MissingMandatoryBlock => "Missing mandatory block node.",
+            &ErrorCode::SuperfluousBlockContent => "Superfluous block content was present.",
+            &ErrorCode::MissingMandatoryInline => "Missing mandatory inline node.",
+            &ErrorCode::SuperfluousInlineContent => "Superfluous inline content was present.",
+            &ErrorCode::TypeMismatch => "Element type does not match the prescription.",
+            &ErrorCode::TextualContentMismatch => "Text does not match the prescription.",
+            &ErrorCode::LinkDestinationMismatch => {
+                "Link destination does not match the prescription."
+            }
+            &ErrorCode::LinkTitleMismatch => "Link title does not match the prescription.",
+            &ErrorCode::OrphanedDitto => {
+                "An element with a Ditto prompt must be preceded by an element of the same type."
+            }
+            &ErrorCode::AmbiguousOptionalElements => {
+                "Adjacent optional elements of the same type cannot be told apart."
+            }
+            &ErrorCode::UnterminatedRepetition => {
+                "A repeated wildcard consumes the element of the same type that follows it."
+            }
+            &ErrorCode::IgnoredPrompt => "Prompts in this element are ignored.",
+            &ErrorCode::MalformedPrompt => "Malformed prompt token.",
+            &ErrorCode::UnusedSuppression => "A suppression directive did not suppress anything.",
+            &ErrorCode::MissingRecommendedBlock => "Missing recommended block node.",
+            &ErrorCode::UnknownPharmacyKey => "Unknown key in the pharmacy file.",
+            &ErrorCode::InvalidPharmacyValue => "Pharmacy entry is missing or has the wrong type.",
+            &ErrorCode::MissingPharmacyFile => "A file named in the pharmacy does not exist.",
+        }
+    }
+
+    /// Returns a detailed explanation of the problem and how to resolve it.
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            &ErrorCode::MissingMandatoryBlock => {
+                "A block element in the prescription, such as a heading, paragraph or list, is
+mandatory but nothing in the document matched it.
+
+Prescription:
+
+    ## Installation
+
+    -!!-
+
+Document:
+
+    ## Installation
+
+The paragraph following the heading is mandatory. Add the missing content to the document, or
+mark the element as optional with `-??-` in the prescription."
+            }
+            &ErrorCode::SuperfluousBlockContent => {
+                "The document contains a block element where the prescription allows none. Every
+block in a document must be matched by an element of the prescription, unless it falls within a
+wildcard.
+
+Prescription:
+
+    # -!!-
+
+Document:
+
+    # Howser
+
+    An extra paragraph.
+
+Remove the extra content from the document, or allow for it in the prescription with a prompted
+element such as `-??-`."
+            }
+            &ErrorCode::MissingMandatoryInline => {
+                "An inline element in the prescription, such as emphasis, a link or inline code,
+is mandatory but nothing in the document matched it.
+
+Prescription:
+
+    See the [-!!-](-!!-) for details.
+
+Document:
+
+    See the documentation for details.
+
+Add the missing inline element to the document, or make it optional in the prescription."
+            }
+            &ErrorCode::SuperfluousInlineContent => {
+                "The document contains an inline element, such as emphasis, a link or inline code,
+that the prescription does not allow.
+
+Prescription:
+
+    Some text -!!-
+
+Document:
+
+    Some text *emphasized*
+
+Remove the extra inline element from the document, or add it to the prescription."
+            }
+            &ErrorCode::TypeMismatch => {
+                "An element of the document was matched against an element of the prescription of
+a different type. Heading levels and list types (bulleted or ordered) must also agree.
+
+Prescription:
+
+    ## -!!-
+
+Document:
+
+    ### Getting Started
+
+Change the document element to the type that the prescription expects."
+            }
+            &ErrorCode::TextualContentMismatch => {
+                "The text of a document element does not match the text of its prescription.
+Literal text in the prescription must appear verbatim, `-!!-` must be replaced by some content
+and `-??-` may be replaced by some content.
+
+Prescription:
+
+    Copyright -!!- PolySync Technologies
+
+Document:
+
+    Copyright 2018 Some Other Company
+
+Edit the document text so that it agrees with the literal text of the prescription."
+            }
+            &ErrorCode::LinkDestinationMismatch => {
+                "The destination of a link in the document does not match the destination of the
+link in the prescription. Destinations may contain prompts like any other text.
+
+Prescription:
+
+    [LICENSE](./LICENSE)
+
+Document:
+
+    [LICENSE](./COPYING)
+
+Point the link at the destination that the prescription expects."
+            }
+            &ErrorCode::LinkTitleMismatch => {
+                "The title of a link in the document does not match the title of the link in the
+prescription. Titles may contain prompts like any other text.
+
+Prescription:
+
+    [Howser](https://github.com/PolySync/howser \"Howser\")
+
+Document:
+
+    [Howser](https://github.com/PolySync/howser \"Repository\")
+
+Change the link title to the title that the prescription expects."
+            }
+            &ErrorCode::OrphanedDitto => {
+                "A ditto prompt (`-\"\"-`) repeats the element before it, so it must follow an
+element of the same type. This is a problem with the prescription itself.
+
+Prescription:
+
+    ## -!!-
+
+    * -\"\"-
+
+Move the ditto directly after the element that should repeat, and make sure both are of the same
+type, such as two list items."
+            }
+            &ErrorCode::AmbiguousOptionalElements => {
+                "An optional wildcard is directly followed by another optional element of the same
+type. A single document element could match either of them, so the prescription is ambiguous
+and the second element can never be matched on its own.
+
+Prescription:
+
+    -??-
+
+    -??-
+
+Merge the elements into one, repeat the first with a ditto (`-\"\"-`), or separate them with an
+element of a different type."
+            }
+            &ErrorCode::UnterminatedRepetition => {
+                "A vacant wildcard that is repeated with a ditto matches every following element of
+the same type. The repetition never stops before the element after the ditto, so that element can
+never be matched.
+
+Prescription:
+
+    * -!!-
+    * -\"\"-
+    * Last Item
+
+Give the repeated element some literal content, or separate it from the following element with
+an element of a different type."
+            }
+            &ErrorCode::IgnoredPrompt => {
+                "Prompts are only meaningful in text, inline code and links. The content of code
+blocks and the destinations and titles of images are not compared against documents, so prompts
+written there have no effect.
+
+Prescription:
+
+    ```rust
+    let name = -!!-;
+    ```
+
+Remove the prompt, or annotate the whole element instead, for example with a code fence info
+string of `-!!-`."
+            }
+            &ErrorCode::MalformedPrompt => {
+                "The prescription contains something that looks like a prompt but is not one of the
+recognized tokens, so it will be matched as literal text. The recognized tokens are `-!!-`,
+`-??-`, `-++-` and `-\"\"-`.
+
+Prescription:
+
+    Copyright -!- PolySync Technologies
+
+Correct the spelling of the token."
+            }
+            &ErrorCode::UnusedSuppression => {
+                "A suppression directive in the document names a code that it never suppressed,
+either because the code is unknown or because the document no longer has that problem. Stale
+directives would hide new problems, so they are reported as warnings once the rest of the
+document is valid.
+
+Document:
+
+    <!-- howser-suppress-section HX0006 -->
+
+Remove the directive, or the codes in it that are no longer needed. Directives can apply to the
+next block (`howser-suppress-next`), to the enclosing section (`howser-suppress-section`) or to
+the whole file (`howser-suppress-file`)."
+            }
+            &ErrorCode::MissingRecommendedBlock => {
+                "A block element in the prescription is recommended, but nothing in the document
+matched it. Recommended elements are marked with `-++-`. Their absence is a warning rather than
+an error, so the document is still valid unless warnings are denied with `--deny-warnings`.
+
+Prescription:
+
+    ## Examples
+
+    -++-
+
+Document:
+
+    ## Examples
+
+Add content for the recommended element, or suppress the warning if the document deliberately
+leaves it out."
+            }
+            &ErrorCode::UnknownPharmacyKey => {
+                "The pharmacy file contains a key or table that howser does not know about. Unknown
+keys are usually misspellings, so they are reported rather than ignored.
+
+Pharmacy:
+
+    [Spec]
+    \"README.rx\" = \"README.md\"
+
+Correct the spelling of the key. A pharmacy file may only contain the `[Specs]` table and the
+tables of its entries, which take the keys described by HX0017. In a `Cargo.toml`, howser reads
+only the `[package.metadata.howser]` and `[workspace.metadata.howser]` tables, and these may only
+contain `Specs`."
+            }
+            &ErrorCode::InvalidPharmacyValue => {
+                "The pharmacy file has no `[Specs]` table, or one of its values has the wrong type.
+Each key of `[Specs]` names a prescription, and its value names the documents to validate
+against it, either as a single string, as an array of strings, or as a table. A table names its
+documents with `documents` and may set options for them:
+
+    documents            required, a string or an array of strings
+    severity             'error' or 'warning'
+    allow-extra-content  a boolean
+    fail-early           a boolean
+    tags                 a string or an array of strings
+    owner                a string
+
+Only one entry may set the options of a prescription.
+
+Pharmacy:
+
+    [Specs]
+    \"README.rx\" = 1
+    \"docs/guide.rx\" = { documents = \"docs/guide.md\", severity = \"fatal\" }
+
+Add the missing table, or give the entry a value of the expected type."
+            }
+            &ErrorCode::MissingPharmacyFile => {
+                "A prescription or document named in the pharmacy file does not exist. Paths in a
+pharmacy file are used as written, so they are relative to the directory howser is run from. Paths
+in a `Cargo.toml` are relative to the directory of that manifest.
+
+Pharmacy:
+
+    [Specs]
+    \"README.rx\" = \"READEM.md\"
+
+Correct the path, create the missing file, or remove the entry from the pharmacy."
+            }
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HX{:04}", self.number())
+    }
+}
+
+/// A span of source text within a named file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub file: String,
+    pub span: Span,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.file, self.span)
+    }
 }
 
 /// A warning related to `Prescription` specification compliance issues.
 #[derive(Debug)]
 pub struct SpecWarning {
-    line: usize,
+    code: ErrorCode,
+    span: Span,
     file: String,
     message: String,
 }
 
 impl SpecWarning {
-    pub fn new(node: &Node, rx: &Document, message: &str) -> HowserResult<Self> {
-        let line = Document::get_line_num(&node)?;
+    pub fn new(node: &Node, rx: &Document, code: ErrorCode) -> HowserResult<Self> {
+        let span = Document::get_span(&node);
         let file = match rx.filename.as_ref() {
             Some(filename) => filename.clone(),
             None => String::new(),
         };
 
         Ok(SpecWarning {
-            line,
+            code,
+            span,
             file,
-            message: message.to_string(),
+            message: code.summary().to_string(),
         })
     }
 
-    fn type_string() -> String {
-        error_type("Rx Specification Error")
+    /// Returns the location of this warning within the prescription.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Narrows the location of this warning to a span within the offending node.
+    pub fn with_focus(mut self, span: Span) -> Self {
+        if span.is_known() {
+            self.span = span;
+        }
+        self
+    }
+
+    fn type_string(&self) -> String {
+        error_type("Rx Specification Error", self.code)
     }
 }
 
 impl Reportable for SpecWarning {
     fn short_msg(&self) -> String {
-        let file_info = file_info(&self.file, self.line);
+        let file_info = file_info(&self.file, &self.span);
         let message = format!(
             " :: {}",
             ShellText::MessageColor(Box::new(ShellText::Literal(self.message.clone()))).to_string()
         );
-        format!("{} :: {}{}", Self::type_string(), file_info, message)
+        format!("{} :: {}{}", self.type_string(), file_info, message)
+    }
+
+    fn long_msg(&self) -> String {
+        self.short_msg()
+    }
+
+    fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    fn prescription_location(&self) -> Option<Location> {
+        Some(Location {
+            file: self.file.clone(),
+            span: self.span,
+        })
+    }
+
+    fn document_location(&self) -> Option<Location> {
+        None
+    }
+}
+
+/// A warning about a suppression directive written in a `Document`.
+pub struct SuppressionWarning {
+    span: Span,
+    file: String,
+    message: String,
+}
+
+impl SuppressionWarning {
+    pub fn new(document: &Document, span: Span, message: String) -> Self {
+        SuppressionWarning {
+            span,
+            file: document
+                .filename
+                .clone()
+                .unwrap_or_else(|| "Unknown".to_string()),
+            message,
+        }
+    }
+
+    fn type_string() -> String {
+        error_type("Suppression Warning", ErrorCode::UnusedSuppression)
+    }
+}
+
+impl Reportable for SuppressionWarning {
+    fn short_msg(&self) -> String {
+        let message = ShellText::MessageColor(Box::new(ShellText::Literal(self.message.clone())));
+        format!(
+            "{} :: {} :: {}",
+            Self::type_string(),
+            file_info(&self.file, &self.span),
+            message.to_string()
+        )
+    }
+
+    fn long_msg(&self) -> String {
+        self.short_msg()
+    }
+
+    fn code(&self) -> ErrorCode {
+        ErrorCode::UnusedSuppression
+    }
+
+    fn prescription_location(&self) -> Option<Location> {
+        None
+    }
+
+    fn document_location(&self) -> Option<Location> {
+        Some(Location {
+            file: self.file.clone(),
+            span: self.span,
+        })
+    }
+}
+
+/// A problem with the schema of a pharmacy file, or with a file that it names.
+#[derive(Debug)]
+pub struct PharmacyWarning {
+    code: ErrorCode,
+    span: Span,
+    file: String,
+    message: String,
+}
+
+impl PharmacyWarning {
+    pub fn new(file: &str, span: Span, code: ErrorCode, message: String) -> Self {
+        PharmacyWarning {
+            code,
+            span,
+            file: file.to_string(),
+            message,
+        }
+    }
+
+    fn type_string(&self) -> String {
+        error_type("Pharmacy Error", self.code)
+    }
+}
+
+impl fmt::Display for PharmacyWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} :: {}", self.file, self.span, self.message)
+    }
+}
+
+impl Reportable for PharmacyWarning {
+    fn short_msg(&self) -> String {
+        let message = ShellText::MessageColor(Box::new(ShellText::Literal(self.message.clone())));
+        format!(
+            "{} :: {} :: {}",
+            self.type_string(),
+            file_info(&self.file, &self.span),
+            message.to_string()
+        )
     }
 
     fn long_msg(&self) -> String {
         self.short_msg()
     }
 
-    fn code(&self) -> u32 {
-        1
+    fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    fn prescription_location(&self) -> Option<Location> {
+        None
+    }
+
+    fn document_location(&self) -> Option<Location> {
+        Some(Location {
+            file: self.file.clone(),
+            span: self.span,
+        })
     }
 }
 
 /// General `Document` validity error.
 pub struct DocumentError {
+    code: ErrorCode,
     info: ErrorInfo,
     message: String,
 }
@@ -161,16 +771,20 @@ impl DocumentError {
         rx_node: &Node,
         document: &Document,
         rx: &Prescription,
-        message: String,
+        code: ErrorCode,
     ) -> HowserResult<Self> {
         Ok(DocumentError {
+            code,
             info: ErrorInfo::new(rx_node, doc_node, rx, document)?,
-            message,
+            message: code.summary().to_string(),
         })
     }
 
-    fn type_string() -> String {
-        error_type("Document Error")
+    fn type_string(&self) -> String {
+        match self.code.severity() {
+            Severity::Warning => error_type("Document Warning", self.code),
+            Severity::Error => error_type("Document Error", self.code),
+        }
     }
 
     fn message(&self) -> String {
@@ -191,7 +805,7 @@ impl Reportable for DocumentError {
     fn short_msg(&self) -> String {
         format!(
             "{}: {} {} {}",
-            Self::type_string(),
+            self.type_string(),
             self.info.rx_location(),
             self.info.node_location(),
             self.message()
@@ -199,7 +813,8 @@ impl Reportable for DocumentError {
     }
 
     fn long_msg(&self) -> String {
-        let mut message = String::from(self.message());
+        let mut message = self.type_string();
+        message += &self.message();
         message += "\n\n";
         message += &self.info.rx_location();
         message += "\n";
@@ -211,20 +826,59 @@ impl Reportable for DocumentError {
         message
     }
 
-    fn code(&self) -> u32 {
-        1
+    fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    fn prescription_location(&self) -> Option<Location> {
+        self.info.prescription_location()
+    }
+
+    fn document_location(&self) -> Option<Location> {
+        self.info.document_location()
     }
 }
 
 struct ErrorInfo {
     pub node_file: String,
-    pub node_line: usize,
+    pub node_span: Span,
     pub node_type: String,
-    pub node_snippet: String,
+    pub node_snippet: Snippet,
     pub rx_file: String,
-    pub rx_line: usize,
+    pub rx_span: Span,
     pub rx_type: String,
-    pub rx_snippet: String,
+    pub rx_snippet: Snippet,
+}
+
+/// Text to display for a node in verbose reports.
+enum Snippet {
+    /// The original source lines of the node, starting at the given line.
+    Source(String, usize),
+    /// The node rendered back into CommonMark, for documents without source text.
+    Rendered(String, usize),
+}
+
+impl Snippet {
+    fn new(node: &Node, document: &Document, span: &Span) -> Self {
+        match document.source() {
+            Some(source) if span.is_known() => {
+                Snippet::Source(cli::source_lines(source, span), span.start_line)
+            }
+            _ => Snippet::Rendered(node.render_commonmark(), span.start_line),
+        }
+    }
+
+    /// Formats the snippet with the spanned text underlined where the source is available.
+    fn format(&self, span: &Span) -> String {
+        match self {
+            &Snippet::Source(ref code, start_line) => {
+                cli::as_source_excerpt(code, start_line, span).join("\n")
+            }
+            &Snippet::Rendered(ref code, start_line) => {
+                cli::as_code_lines(code, start_line).join("\n")
+            }
+        }
+    }
 }
 
 impl ErrorInfo {
@@ -238,36 +892,50 @@ impl ErrorInfo {
             .as_ref()
             .unwrap_or(&"Unknown".to_string())
             .to_string();
-        let node_line = Document::get_line_num(doc_node)?;
-        let node_type = doc_node.get_cmark_type_string()?;
-        let node_snippet = doc_node.render_commonmark();
+        let node_span = Document::get_span(doc_node);
+        let node_type = doc_node.type_string().to_string();
+        let node_snippet = Snippet::new(doc_node, doc, &node_span);
         let rx_file = rx.document
             .filename
             .as_ref()
             .unwrap_or(&"Unknown".to_string())
             .clone();
-        let rx_line = Document::get_line_num(rx_node)?;
-        let rx_type = rx_node.get_cmark_type_string()?;
-        let rx_snippet = rx_node.render_commonmark();
+        let rx_span = Document::get_span(rx_node);
+        let rx_type = rx_node.type_string().to_string();
+        let rx_snippet = Snippet::new(rx_node, &rx.document, &rx_span);
 
         Ok(ErrorInfo {
             node_file,
-            node_line,
+            node_span,
             node_type,
             node_snippet,
             rx_file,
-            rx_line,
+            rx_span,
             rx_type,
             rx_snippet,
         })
     }
 
     fn rx_location(&self) -> String {
-        file_info(&self.rx_file, self.rx_line)
+        file_info(&self.rx_file, &self.rx_span)
     }
 
     fn node_location(&self) -> String {
-        file_info(&self.node_file, self.node_line)
+        file_info(&self.node_file, &self.node_span)
+    }
+
+    fn prescription_location(&self) -> Option<Location> {
+        Some(Location {
+            file: self.rx_file.clone(),
+            span: self.rx_span,
+        })
+    }
+
+    fn document_location(&self) -> Option<Location> {
+        Some(Location {
+            file: self.node_file.clone(),
+            span: self.node_span,
+        })
     }
 
     fn rx_type(&self) -> String {
@@ -279,17 +947,18 @@ impl ErrorInfo {
     }
 
     fn rx_snippet(&self) -> String {
-        cli::as_code_lines(&self.rx_snippet, self.rx_line).join("\n")
+        self.rx_snippet.format(&self.rx_span)
     }
 
     fn node_snippet(&self) -> String {
-        cli::as_code_lines(&self.node_snippet, self.node_line).join("\n")
+        self.node_snippet.format(&self.node_span)
     }
 }
 
 /// Error resulting from disparate Node types
 pub struct TypeMismatchError {
     info: ErrorInfo,
+    suggestion: Option<String>,
 }
 
 impl TypeMismatchError {
@@ -301,28 +970,50 @@ impl TypeMismatchError {
     ) -> HowserResult<Self> {
         Ok(TypeMismatchError {
             info: ErrorInfo::new(rx_node, doc_node, rx, doc)?,
+            suggestion: Self::suggest(rx_node, doc_node),
         })
     }
 
+    /// Describes the difference between elements that share a name but not a type.
+    fn suggest(rx_node: &Node, doc_node: &Node) -> Option<String> {
+        match (rx_node.kind(), doc_node.kind()) {
+            (&NodeKind::Heading(rx_level), &NodeKind::Heading(doc_level)) => Some(format!(
+                "this is an H{}, the prescription expects an H{}",
+                doc_level, rx_level
+            )),
+            (&NodeKind::List(ref rx_type), &NodeKind::List(ref doc_type)) => Some(format!(
+                "this is {}, the prescription expects {}",
+                list_type_string(doc_type),
+                list_type_string(rx_type)
+            )),
+            _ => None,
+        }
+    }
+
     fn type_string() -> String {
-        error_type("Type Mismatch Error")
+        error_type("Type Mismatch Error", ErrorCode::TypeMismatch)
     }
 }
 
 impl Reportable for TypeMismatchError {
     fn short_msg(&self) -> String {
         format!(
-            "{}: {} from {} does not match {} from {}",
+            "{}: {} from {} does not match {} from {}{}",
             Self::type_string(),
             self.info.rx_type(),
             self.info.rx_location(),
             self.info.node_type(),
-            self.info.node_location()
+            self.info.node_location(),
+            suggestion_text(&self.suggestion)
         )
     }
 
     fn long_msg(&self) -> String {
-        let mut message = format!("{}\n\n", Self::type_string());
+        let mut message = format!(
+            "{}{}\n\n",
+            Self::type_string(),
+            suggestion_text(&self.suggestion)
+        );
         message += &self.info.rx_type();
         message += "\n";
         message += &self.info.rx_location();
@@ -337,16 +1028,26 @@ impl Reportable for TypeMismatchError {
         message
     }
 
-    fn code(&self) -> u32 {
-        1
+    fn code(&self) -> ErrorCode {
+        ErrorCode::TypeMismatch
+    }
+
+    fn prescription_location(&self) -> Option<Location> {
+        self.info.prescription_location()
+    }
+
+    fn document_location(&self) -> Option<Location> {
+        self.info.document_location()
     }
 }
 
 /// Error resulting from a textual content mismatch
 pub struct TextualContentError {
+    code: ErrorCode,
     info: ErrorInfo,
     rx_prompts: Vec<String>,
     doc_matches: Vec<String>,
+    suggestion: Option<String>,
 }
 
 impl TextualContentError {
@@ -356,6 +1057,7 @@ impl TextualContentError {
         rx: &Prescription,
         document: &Document,
         match_pairs: &Vec<ContentMatchPair>,
+        code: ErrorCode,
     ) -> HowserResult<Self> {
         let rx_prompts: Vec<String> = match_pairs
             .iter()
@@ -382,29 +1084,71 @@ impl TextualContentError {
             .collect();
 
         Ok(TextualContentError {
+            code,
             info: ErrorInfo::new(rx_node, doc_node, rx, document)?,
             rx_prompts,
             doc_matches,
+            suggestion: Self::suggest(doc_node, match_pairs, code),
         })
     }
 
-    fn type_string() -> String {
-        error_type("Textual Content Error")
+    /// Describes how the document differs from the first literal text it failed to match, if the
+    /// document holds something close to it.
+    fn suggest(
+        doc_node: &Node,
+        match_pairs: &Vec<ContentMatchPair>,
+        code: ErrorCode,
+    ) -> Option<String> {
+        let expected = match_pairs
+            .iter()
+            .filter_map(|pair| match pair {
+                &ContentMatchPair(PromptToken::Literal(ref literal), None) => Some(literal.trim()),
+                _ => None,
+            })
+            .next()?;
+        let content = match (doc_node.kind(), code) {
+            (&NodeKind::Link { ref url, .. }, ErrorCode::LinkDestinationMismatch) => url,
+            (&NodeKind::Link { ref title, .. }, ErrorCode::LinkTitleMismatch) => title,
+            (&NodeKind::Text(ref content), _) | (&NodeKind::Code(ref content), _) => content,
+            _ => return None,
+        };
+
+        fuzzy::describe_near_miss(expected, &fuzzy::closest_region(expected, content))
+    }
+
+    /// Narrows the reported locations to the content that failed to match.
+    pub fn with_focus(mut self, rx_span: Span, node_span: Span) -> Self {
+        if rx_span.is_known() {
+            self.info.rx_span = rx_span;
+        }
+        if node_span.is_known() {
+            self.info.node_span = node_span;
+        }
+        self
+    }
+
+    fn type_string(&self) -> String {
+        error_type("Textual Content Error", self.code)
     }
 }
 
 impl Reportable for TextualContentError {
     fn short_msg(&self) -> String {
         format!(
-            "{}: at {}, {}",
-            Self::type_string(),
+            "{}: at {}, {}{}",
+            self.type_string(),
             self.info.rx_location(),
-            self.info.node_location()
+            self.info.node_location(),
+            suggestion_text(&self.suggestion)
         )
     }
 
     fn long_msg(&self) -> String {
-        let mut message = format!("{}\n\n", Self::type_string());
+        let mut message = format!(
+            "{}{}\n\n",
+            self.type_string(),
+            suggestion_text(&self.suggestion)
+        );
         message += &format!("{}Prescription : {}", style::Bold, style::Reset);
         message += &self.rx_prompts.join("");
         message += "\n";
@@ -421,30 +1165,63 @@ impl Reportable for TextualContentError {
         message
     }
 
-    fn code(&self) -> u32 {
-        1
+    fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    fn prescription_location(&self) -> Option<Location> {
+        self.info.prescription_location()
+    }
+
+    fn document_location(&self) -> Option<Location> {
+        self.info.document_location()
     }
 }
 
-fn error_type(error_type: &str) -> String {
+fn error_type(error_type: &str, code: ErrorCode) -> String {
+    let type_color = match code.severity() {
+        Severity::Warning => color::Fg(color::Yellow).to_string(),
+        Severity::Error => color::Fg(color::Red).to_string(),
+    };
     format!(
-        "{}{}{}",
-        color::Fg(color::Red),
+        "{}{}[{}]{}",
+        type_color,
         error_type,
+        code,
         color::Fg(color::Reset),
     )
 }
 
-fn file_info(filename: &str, line: usize) -> String {
+fn file_info(filename: &str, span: &Span) -> String {
     format!(
-        "{}{} line {}{}",
+        "{}{} {}{}",
         style::Underline,
         filename,
-        line,
+        span,
         style::Reset
     )
 }
 
+/// Formats a suggestion for the end of a report's first line.
+fn suggestion_text(suggestion: &Option<String>) -> String {
+    match suggestion {
+        &Some(ref suggestion) => format!(
+            "{} :: {}{}",
+            color::Fg(color::Yellow),
+            suggestion,
+            color::Fg(color::Reset)
+        ),
+        &None => String::new(),
+    }
+}
+
+fn list_type_string(list_type: &ListType) -> &'static str {
+    match list_type {
+        &ListType::Bullet => "a bullet list",
+        &ListType::Ordered => "an ordered list",
+    }
+}
+
 fn node_type_string(node_type: &String) -> String {
     format!("{}{}{}", style::Bold, node_type, style::Reset)
 }
@@ -466,3 +1243,45 @@ fn error_text(text: &str) -> String {
         color::Fg(color::Reset)
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ErrorCode;
+    use document::{Document, Prescription};
+    use validator::Validator;
+
+    fn first_report(rx: &str, document: &str) -> String {
+        let prescription = Prescription::from_text(rx, None).unwrap();
+        let document = Document::from_text(document, None).unwrap();
+        let problems = Validator::new(prescription, document).validate().unwrap();
+        problems[0].short_msg()
+    }
+
+    #[test]
+    fn test_error_codes_round_trip() {
+        for code in ErrorCode::ALL.iter() {
+            assert_eq!(ErrorCode::parse(&code.to_string()), Some(*code));
+        }
+        assert_eq!(ErrorCode::MissingMandatoryBlock.to_string(), "HX0001");
+        assert_eq!(ErrorCode::parse("hx5"), Some(ErrorCode::TypeMismatch));
+        assert_eq!(ErrorCode::parse("HX0000"), None);
+        assert_eq!(ErrorCode::parse("HXABCD"), None);
+    }
+
+    #[test]
+    fn test_near_misses_include_suggestions() {
+        assert!(
+            first_report("# Getting Started", "# Getting started")
+                .contains("\"Getting started\" differs from \"Getting Started\" only by case")
+        );
+        assert!(
+            first_report("Copyright -!!- PolySync", "Copyrigth 2018 PolySync")
+                .contains("did you mean \"Copyright\"? \"Copyrigth\" is 1 edit away")
+        );
+        assert!(
+            first_report("## Usage", "### Usage")
+                .contains("this is an H3, the prescription expects an H2")
+        );
+        assert!(!first_report("# Usage", "# Installation").contains(" :: "));
+    }
+}
@@ -0,0 +1,337 @@
+//! Extracts the content that fills the prompts of a prescription from a valid document.
+
+extern crate regex;
+extern crate toml;
+
+use self::regex::{escape, Regex};
+use self::toml::Value;
+use self::toml::value::Table;
+use ast::{Node, NodeId, NodeKind, Span};
+use constants::{CONTENT_PROMPT_PATTERN, MANDATORY_PROMPT, OPTIONAL_PROMPT};
+use data::MatchType;
+use document::Document;
+use errors::HowserResult;
+use std::collections::HashMap;
+use std::ops::Range;
+use validator::Validator;
+
+/// Collects the values of prompts and wildcards from a document that passed validation.
+///
+/// Each value is keyed by the closest hint comment in the prescription, or by the line and
+/// column of its prompt when there is no hint or the hint is already taken. Repeated elements
+/// become arrays, holding plain values when the element has a single prompt and tables of
+/// values otherwise.
+pub struct Extractor<'a> {
+    rx: &'a Document,
+    document: &'a Document,
+    matches: HashMap<NodeId, Vec<NodeId>>,
+}
+
+impl<'a> Extractor<'a> {
+    /// Returns a new `Extractor` for the last validation performed by `validator`.
+    ///
+    /// The validation is expected to have found no problems.
+    pub fn new(validator: &'a Validator) -> Self {
+        let mut matches: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        for (rx_id, node_id) in validator.matches() {
+            matches.entry(rx_id).or_insert_with(Vec::new).push(node_id);
+        }
+        for node_ids in matches.values_mut() {
+            node_ids.sort();
+            node_ids.dedup();
+        }
+
+        Extractor {
+            rx: &validator.prescription().document,
+            document: validator.document(),
+            matches,
+        }
+    }
+
+    /// Returns the extracted values as a table.
+    pub fn extract(&self) -> HowserResult<Value> {
+        trace!("extract()");
+        let root = self.rx.root();
+        let mut table = Table::new();
+        self.extract_children(&root, &self.document.root(), &root, &mut table)?;
+
+        Ok(Value::Table(table))
+    }
+
+    /// Extracts the values within the children of `rx` that were matched to children of `node`.
+    fn extract_children(
+        &self,
+        rx: &Node,
+        node: &Node,
+        scope: &Node,
+        table: &mut Table,
+    ) -> HowserResult<()> {
+        for rx_child in rx.children() {
+            if self.rx.get_match_type(&rx_child)? == MatchType::Repeatable {
+                continue;
+            }
+
+            let matched = self.matched_children(&rx_child, node);
+            if self.is_repeated(&rx_child)? {
+                let mut items = Vec::new();
+                for node_child in matched {
+                    items.push(self.extract_element(&rx_child, &node_child)?);
+                }
+                let key = self.key(&rx_child, scope, rx_child.span(), table);
+                table.insert(key, Value::Array(items));
+            } else if let Some(node_child) = matched.first() {
+                self.extract_node(&rx_child, node_child, scope, table)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Extracts the values of one occurrence of a repeated element.
+    fn extract_element(&self, rx: &Node, node: &Node) -> HowserResult<Value> {
+        let mut table = Table::new();
+        self.extract_node(rx, node, rx, &mut table)?;
+
+        if self.count_values(rx)? == 1 {
+            let value = table.into_iter().next().map(|(_, value)| value);
+            Ok(value.unwrap_or(Value::String(String::new())))
+        } else {
+            Ok(Value::Table(table))
+        }
+    }
+
+    /// Extracts the values of `rx` and its descendants from the matching `node`.
+    fn extract_node(
+        &self,
+        rx: &Node,
+        node: &Node,
+        scope: &Node,
+        table: &mut Table,
+    ) -> HowserResult<()> {
+        if self.rx.is_wildcard(rx) {
+            let key = self.key(rx, scope, rx.span(), table);
+            table.insert(key, Value::String(wildcard_content(node)));
+            return Ok(());
+        }
+
+        match (rx.kind(), node.kind()) {
+            (&NodeKind::Text(ref rx_text), &NodeKind::Text(ref text))
+            | (&NodeKind::Code(ref rx_text), &NodeKind::Code(ref text)) => {
+                for (range, value) in capture_prompts(rx_text, text)? {
+                    let key = self.key(rx, scope, rx.content_span(range), table);
+                    table.insert(key, Value::String(value));
+                }
+            }
+            (
+                &NodeKind::Link {
+                    url: ref rx_url,
+                    title: ref rx_title,
+                },
+                &NodeKind::Link { ref url, ref title },
+            ) => {
+                let mut captures = capture_prompts(rx_url, url)?;
+                captures.append(&mut capture_prompts(rx_title, title)?);
+                for (_, value) in captures {
+                    let key = self.key(rx, scope, rx.span(), table);
+                    table.insert(key, Value::String(value));
+                }
+            }
+            _ => (),
+        }
+
+        self.extract_children(rx, node, scope, table)
+    }
+
+    /// Returns the children of `parent` that were matched to `rx`, in document order.
+    fn matched_children(&self, rx: &Node, parent: &Node) -> Vec<Node<'a>> {
+        self.matches
+            .get(&rx.id())
+            .map(|node_ids| {
+                node_ids
+                    .iter()
+                    .map(|&id| self.document.node(id))
+                    .filter(|node| node.parent().map(|p| p.id()) == Some(parent.id()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Determines if `rx` is followed by a ditto.
+    fn is_repeated(&self, rx: &Node) -> HowserResult<bool> {
+        match rx.next_sibling() {
+            Some(next) => Ok(self.rx.get_match_type(&next)? == MatchType::Repeatable),
+            None => Ok(false),
+        }
+    }
+
+    /// Returns the number of values that `rx` and its descendants can hold.
+    fn count_values(&self, rx: &Node) -> HowserResult<usize> {
+        if self.rx.is_wildcard(rx) {
+            return Ok(1);
+        }
+
+        let mut count = match rx.kind() {
+            &NodeKind::Text(ref content) | &NodeKind::Code(ref content) => {
+                locate_prompts(content)?.len()
+            }
+            &NodeKind::Link { ref url, ref title } => {
+                locate_prompts(url)?.len() + locate_prompts(title)?.len()
+            }
+            _ => 0,
+        };
+        for child in rx.children() {
+            if self.rx.get_match_type(&child)? != MatchType::Repeatable {
+                count += match self.is_repeated(&child)? {
+                    true => 1,
+                    false => self.count_values(&child)?,
+                };
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Returns the key for a value of `rx`, preferring the closest hint comment below `scope`.
+    ///
+    /// Positions are numbered when more than one value comes from the same place.
+    fn key(&self, rx: &Node, scope: &Node, span: Span, table: &Table) -> String {
+        let mut current = Some(*rx);
+        while let Some(node) = current {
+            if node.id() == scope.id() {
+                break;
+            }
+            if let Some(comment) = self.rx.get_comment(&node) {
+                let hint = comment.content();
+                if !table.contains_key(&hint) {
+                    return hint;
+                }
+                break;
+            }
+            current = node.parent();
+        }
+
+        let position = format!("{}:{}", span.start_line, span.start_column);
+        let mut key = position.clone();
+        let mut suffix = 1;
+        while table.contains_key(&key) {
+            suffix += 1;
+            key = format!("{}.{}", position, suffix);
+        }
+
+        key
+    }
+}
+
+/// Returns the content of a document element that matched a wildcard.
+fn wildcard_content(node: &Node) -> String {
+    match node.kind() {
+        &NodeKind::Paragraph | &NodeKind::Heading(_) => node.children()
+            .map(|child| child.render_commonmark())
+            .collect::<Vec<String>>()
+            .concat(),
+        &NodeKind::CodeBlock { ref literal, .. } => literal.trim_right_matches('\n').to_string(),
+        _ => node.render_commonmark(),
+    }
+}
+
+/// Returns the byte ranges of the prompts in prescription content.
+fn locate_prompts(rx_content: &str) -> HowserResult<Vec<Range<usize>>> {
+    let pattern = Regex::new(CONTENT_PROMPT_PATTERN)?;
+
+    Ok(pattern
+        .find_iter(rx_content)
+        .filter(|prompt| match prompt.as_str() {
+            MANDATORY_PROMPT | OPTIONAL_PROMPT => true,
+            _ => false,
+        })
+        .map(|prompt| prompt.start()..prompt.end())
+        .collect())
+}
+
+/// Returns the range of each prompt in `rx_content` along with the text that filled it.
+///
+/// Each prompt takes as little text as possible while still matching the literal content around
+/// it. Nothing is captured if the content cannot be lined up with the prompts that way.
+fn capture_prompts(rx_content: &str, content: &str) -> HowserResult<Vec<(Range<usize>, String)>> {
+    let prompts = locate_prompts(rx_content)?;
+    if prompts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut pattern = String::from("(?s)^");
+    let mut literal_start = 0;
+    for prompt in &prompts {
+        pattern.push_str(&escape(&rx_content[literal_start..prompt.start]));
+        pattern.push_str(match &rx_content[prompt.clone()] {
+            MANDATORY_PROMPT => "(.+?)",
+            _ => "(.*?)",
+        });
+        literal_start = prompt.end;
+    }
+    pattern.push_str(&escape(&rx_content[literal_start..]));
+    pattern.push('$');
+
+    let captures = match Regex::new(&pattern)?.captures(content) {
+        Some(captures) => captures,
+        None => return Ok(Vec::new()),
+    };
+
+    Ok(prompts
+        .into_iter()
+        .enumerate()
+        .map(|(i, prompt)| {
+            let value = captures.get(i + 1).map(|m| m.as_str()).unwrap_or("");
+            (prompt, value.to_string())
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Extractor;
+    use document::{Document, Prescription};
+    use validator::Validator;
+
+    const RX: &str = "# -!!-<!-- Project Name -->\n\n\
+                      Copyright -!!- PolySync\n\n\
+                      ## Maintainers\n\n\
+                      * -!!-<!-- Maintainer -->\n\
+                      * -\"\"-\n\n\
+                      ## Dependencies<!-- Dependencies -->\n\n\
+                      * [-!!-](-!!-)\n\
+                      * -\"\"-\n";
+
+    fn extract(text: &str) -> String {
+        let prescription = Prescription::from_text(RX, None).unwrap();
+        let document = Document::from_text(text, None).unwrap();
+        let validator = Validator::new(prescription, document);
+        assert!(validator.validate().unwrap().is_empty());
+
+        Extractor::new(&validator).extract().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_prompts_are_keyed_by_hint_or_position() {
+        let values = extract(
+            "# Howser\n\nCopyright 2018 PolySync\n\n## Maintainers\n\n* Devin\n\n\
+             ## Dependencies\n\n* [regex](https://crates.io/crates/regex)\n",
+        );
+
+        assert!(values.contains("\"3:11\" = \"2018\"\n"), "{}", values);
+        assert!(values.contains("\"Project Name\" = \"Howser\"\n"), "{}", values);
+    }
+
+    #[test]
+    fn test_repeated_elements_become_arrays() {
+        let values = extract(
+            "# Howser\n\nCopyright 2018 PolySync\n\n## Maintainers\n\n* Devin\n* Jeff\n\n\
+             ## Dependencies\n\n* [regex](https://crates.io/crates/regex)\n* [toml](toml.md)\n",
+        );
+
+        assert!(values.contains("Maintainer = [\"Devin\", \"Jeff\"]\n"), "{}", values);
+        assert!(values.contains(
+            "[[\"12:1\"]]\n\"12:3\" = \"https://crates.io/crates/regex\"\n\"12:4\" = \"regex\"\n"
+        ));
+        assert!(values.contains("[[\"12:1\"]]\n\"12:3\" = \"toml.md\"\n\"12:4\" = \"toml\"\n"));
+    }
+}
@@ -0,0 +1,495 @@
+//! Repairs mechanical problems in documents so that they move toward their prescription.
+
+extern crate regex;
+
+use self::regex::Regex;
+use ast::{Node, NodeKind, ROOT_ID};
+use constants::CONTENT_PROMPT_PATTERN;
+use data::{Alignment, MatchType};
+use document::{Document, Prescription};
+use errors::{ErrorCode, HowserResult, ValidationProblem};
+use helpers::fuzzy;
+use scaffold::{Scaffolder, PLACEHOLDER};
+use std::ops::Range;
+use validator::Validator;
+
+/// Upper bound on the number of edits made to one document, in case edits never converge.
+const MAX_FIX_PASSES: usize = 100;
+
+/// A description of one edit made to a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fix {
+    /// The line of the fixed document where the edit was made.
+    pub line: usize,
+    pub description: String,
+    /// Whether the edit inserted placeholders that still need to be replaced by hand.
+    pub needs_content: bool,
+}
+
+/// The outcome of fixing a document.
+pub struct FixResult {
+    /// The fixed source text of the document.
+    pub source: String,
+    /// The edits that were made, in the order they were applied.
+    pub fixes: Vec<Fix>,
+    /// The problems that remain after fixing, which could not be repaired safely.
+    pub problems: Vec<ValidationProblem>,
+}
+
+/// A replacement of a byte range of source text.
+struct Edit {
+    range: Range<usize>,
+    replacement: String,
+    fix: Fix,
+}
+
+/// Repeatedly validates a document and repairs the first problem found, until the document is
+/// valid or a problem cannot be repaired safely.
+///
+/// Only problems with a single obvious repair are fixed:
+///
+/// * ATX headings with the wrong level.
+/// * Literal text that differs from the prescription by case, whitespace, or quote style.
+/// * Missing mandatory elements, when the document continues with a heading that the
+///   prescription expects further on or when the document ends early.
+///
+/// Inserted elements are scaffolded from the prescription, so prompts are filled with
+/// placeholders that are reported as needing content.
+pub fn fix_document(
+    rx_text: &str,
+    rx_name: Option<String>,
+    text: &str,
+    document_name: Option<String>,
+) -> HowserResult<FixResult> {
+    trace!("fix_document()");
+    fix(rx_text, rx_name, text, document_name, false)
+}
+
+/// Inserts the missing mandatory elements of a document, scaffolded from the prescription, and
+/// leaves every other problem alone.
+pub fn insert_missing_elements(
+    rx_text: &str,
+    rx_name: Option<String>,
+    text: &str,
+    document_name: Option<String>,
+) -> HowserResult<FixResult> {
+    trace!("insert_missing_elements()");
+    fix(rx_text, rx_name, text, document_name, true)
+}
+
+/// Repairs problems until none can be repaired safely, making only insertions if
+/// `insertions_only` is set.
+fn fix(
+    rx_text: &str,
+    rx_name: Option<String>,
+    text: &str,
+    document_name: Option<String>,
+    insertions_only: bool,
+) -> HowserResult<FixResult> {
+    let mut source = text.to_string();
+    let mut fixes = Vec::new();
+
+    for _ in 0..MAX_FIX_PASSES {
+        let prescription = Prescription::from_text(rx_text, rx_name.clone())?;
+        let document = Document::from_text(&source, document_name.clone())?;
+        let validator = Validator::new(prescription, document);
+        let problems = validator.validate()?;
+
+        let edit = match problems.first() {
+            Some(problem) => plan_edit(&validator, problem, &source, insertions_only)?,
+            None => None,
+        };
+        match edit {
+            Some(Edit {
+                range,
+                replacement,
+                fix,
+            }) => {
+                debug!("fix_document:: {:?}", fix);
+                source = format!(
+                    "{}{}{}",
+                    &source[..range.start],
+                    replacement,
+                    &source[range.end..]
+                );
+                fixes.push(fix);
+            }
+            None => {
+                return Ok(FixResult {
+                    source,
+                    fixes,
+                    problems,
+                })
+            }
+        }
+    }
+
+    let prescription = Prescription::from_text(rx_text, rx_name)?;
+    let document = Document::from_text(&source, document_name)?;
+    let problems = Validator::new(prescription, document).validate()?;
+
+    Ok(FixResult {
+        source,
+        fixes,
+        problems,
+    })
+}
+
+/// Returns the edit that repairs `problem`, if there is a safe one.
+fn plan_edit(
+    validator: &Validator,
+    problem: &ValidationProblem,
+    source: &str,
+    insertions_only: bool,
+) -> HowserResult<Option<Edit>> {
+    let code = problem.code();
+    let prescription = validator.prescription();
+    let rx = &prescription.document;
+    let document = validator.document();
+
+    if code == ErrorCode::MissingMandatoryBlock {
+        let span = match problem.prescription_location() {
+            Some(location) => location.span,
+            None => return Ok(None),
+        };
+        let missing = rx.root()
+            .children()
+            .find(|child| child.span() == span && span.is_known());
+        return match missing {
+            Some(missing) => append_element(prescription, &missing, source),
+            None => Ok(None),
+        };
+    }
+
+    // The deepest mismatch is recorded last, since elements are recorded ahead of their children.
+    let step = validator
+        .alignment()
+        .into_iter()
+        .filter(|step| step.alignment == Alignment::Mismatched(code) && step.node.is_some())
+        .last();
+    let (rx_node, doc_node) = match step {
+        Some(step) => (rx.node(step.rx), document.node(step.node.unwrap_or(ROOT_ID))),
+        None => return Ok(None),
+    };
+
+    let edit = match code {
+        _ if insertions_only => None,
+        ErrorCode::TypeMismatch => fix_heading_level(rx, &rx_node, &doc_node, source)?,
+        ErrorCode::TextualContentMismatch => fix_literal_text(&rx_node, &doc_node, source)?,
+        _ => None,
+    };
+    match edit {
+        Some(edit) => Ok(Some(edit)),
+        None => insert_missing_element(prescription, &rx_node, &doc_node, source),
+    }
+}
+
+/// Rewrites the markers of an ATX heading that has the wrong level, as long as its text is what
+/// the prescription expects.
+fn fix_heading_level(
+    rx: &Document,
+    rx_node: &Node,
+    doc_node: &Node,
+    source: &str,
+) -> HowserResult<Option<Edit>> {
+    let (rx_level, doc_level) = match (rx_node.kind(), doc_node.kind()) {
+        (&NodeKind::Heading(rx_level), &NodeKind::Heading(doc_level)) => (rx_level, doc_level),
+        _ => return Ok(None),
+    };
+    let has_expected_text = rx.is_wildcard(rx_node)
+        || Regex::new(CONTENT_PROMPT_PATTERN)?.is_match(&inline_content(rx_node))
+        || fuzzy::is_cosmetic_difference(&inline_content(rx_node), &inline_content(doc_node));
+    if !has_expected_text {
+        return Ok(None);
+    }
+
+    let span = doc_node.span();
+    let start = match byte_offset(source, span.start_line, span.start_column) {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+    let markers = source[start..].chars().take_while(|&c| c == '#').count();
+    if markers != doc_level as usize {
+        return Ok(None);
+    }
+
+    Ok(Some(Edit {
+        range: start..start + markers,
+        replacement: "#".repeat(rx_level as usize),
+        fix: Fix {
+            line: span.start_line,
+            description: format!("changed an H{} to an H{}", doc_level, rx_level),
+            needs_content: false,
+        },
+    }))
+}
+
+/// Replaces document text that differs from a literal of the prescription only in case,
+/// whitespace, or quote style.
+fn fix_literal_text(rx_node: &Node, doc_node: &Node, source: &str) -> HowserResult<Option<Edit>> {
+    let (rx_text, doc_text) = match (rx_node.kind(), doc_node.kind()) {
+        (&NodeKind::Text(ref rx_text), &NodeKind::Text(ref doc_text)) => (rx_text, doc_text),
+        _ => return Ok(None),
+    };
+
+    for literal in Regex::new(CONTENT_PROMPT_PATTERN)?.split(rx_text) {
+        let literal = literal.trim();
+        if literal.is_empty() {
+            continue;
+        }
+        let range = fuzzy::closest_region_range(literal, doc_text);
+        let found = &doc_text[range.clone()];
+        if found == literal || !fuzzy::is_cosmetic_difference(literal, found) {
+            continue;
+        }
+
+        // Text is only replaced where it appears verbatim in the source, so that escapes and
+        // entities are never rewritten.
+        let span = doc_node.content_span(range);
+        let start = byte_offset(source, span.start_line, span.start_column);
+        let end = byte_offset(source, span.end_line, span.end_column + 1);
+        if let (Some(start), Some(end)) = (start, end) {
+            if start <= end && &source[start..end] == found {
+                return Ok(Some(Edit {
+                    range: start..end,
+                    replacement: literal.to_string(),
+                    fix: Fix {
+                        line: span.start_line,
+                        description: format!("changed \"{}\" to \"{}\"", found, literal),
+                        needs_content: false,
+                    },
+                }));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Inserts a missing top level element ahead of a document heading that the prescription
+/// expects further on.
+fn insert_missing_element(
+    prescription: &Prescription,
+    rx_node: &Node,
+    doc_node: &Node,
+    source: &str,
+) -> HowserResult<Option<Edit>> {
+    let rx = &prescription.document;
+    let (missing, following) = match (top_level(rx_node), top_level(doc_node)) {
+        (Some(missing), Some(following)) => (missing, following),
+        _ => return Ok(None),
+    };
+    if rx.get_match_type(&missing)? != MatchType::Mandatory {
+        return Ok(None);
+    }
+
+    let mut is_expected_later = false;
+    let mut current = missing.next_sibling();
+    while let Some(rx_sibling) = current {
+        if is_literal_heading(rx, &rx_sibling)? && fuzzy::is_cosmetic_difference(
+            &rx_sibling.render_commonmark(),
+            &following.render_commonmark(),
+        ) {
+            is_expected_later = true;
+            break;
+        }
+        current = rx_sibling.next_sibling();
+    }
+    if !is_expected_later {
+        return Ok(None);
+    }
+
+    let line = following.span().start_line;
+    let start = match byte_offset(source, line, 1) {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+    let element = Scaffolder::new(prescription, false).scaffold_element(&missing)?;
+
+    Ok(Some(Edit {
+        range: start..start,
+        replacement: format!("{}\n\n", element),
+        fix: insertion_fix(&element, line),
+    }))
+}
+
+/// Appends a missing top level element to the end of the document.
+fn append_element(
+    prescription: &Prescription,
+    missing: &Node,
+    source: &str,
+) -> HowserResult<Option<Edit>> {
+    let element = Scaffolder::new(prescription, false).scaffold_element(missing)?;
+    let content = source.trim_right();
+    let (separator, line) = match content.is_empty() {
+        true => ("", 1),
+        false => ("\n\n", content.lines().count() + 2),
+    };
+
+    Ok(Some(Edit {
+        range: content.len()..source.len(),
+        replacement: format!("{}{}\n", separator, element),
+        fix: insertion_fix(&element, line),
+    }))
+}
+
+/// Describes the insertion of a scaffolded element at the given line.
+fn insertion_fix(element: &str, line: usize) -> Fix {
+    let first_line = element.lines().next().unwrap_or("");
+    let description = match element.lines().count() {
+        1 => format!("inserted \"{}\"", first_line),
+        _ => format!("inserted \"{}\" and the lines after it", first_line),
+    };
+
+    Fix {
+        line,
+        description,
+        needs_content: element.contains(PLACEHOLDER),
+    }
+}
+
+/// Returns the ancestor of `node` that is a direct child of the document, or `node` itself.
+fn top_level<'t>(node: &Node<'t>) -> Option<Node<'t>> {
+    let mut current = *node;
+    while let Some(parent) = current.parent() {
+        if parent.id() == ROOT_ID {
+            return Some(current);
+        }
+        current = parent;
+    }
+
+    None
+}
+
+/// Determines if a prescription element is a heading whose text is entirely literal.
+fn is_literal_heading(rx: &Document, rx_node: &Node) -> HowserResult<bool> {
+    Ok(match rx_node.kind() {
+        &NodeKind::Heading(_) => {
+            rx.get_match_type(rx_node)? != MatchType::Repeatable && !rx.is_wildcard(rx_node)
+                && !Regex::new(CONTENT_PROMPT_PATTERN)?.is_match(&inline_content(rx_node))
+        }
+        _ => false,
+    })
+}
+
+/// Returns the inline content of a block element as CommonMark.
+fn inline_content(node: &Node) -> String {
+    node.children()
+        .map(|child| child.render_commonmark())
+        .collect::<Vec<String>>()
+        .concat()
+}
+
+/// Returns the byte offset of a 1-based line and column of source text, where columns count
+/// characters.
+fn byte_offset(source: &str, line: usize, column: usize) -> Option<usize> {
+    if line == 0 || column == 0 {
+        return None;
+    }
+
+    let line_start: usize = source
+        .split('\n')
+        .take(line - 1)
+        .map(|text| text.len() + 1)
+        .sum();
+    let text = source.split('\n').nth(line - 1)?;
+    match text.char_indices().nth(column - 1) {
+        Some((offset, _)) => Some(line_start + offset),
+        None if column - 1 == text.chars().count() => Some(line_start + text.len()),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fix_document, insert_missing_elements};
+
+    const RX: &str = "# -!!-\n\n\
+                      ## Getting Started\n\n\
+                      -!!-\n\n\
+                      ## Don't Panic\n\n\
+                      ## License\n\n\
+                      Copyright -!!- PolySync\n";
+
+    fn fix(text: &str) -> (String, Vec<String>, usize) {
+        let result = fix_document(RX, None, text, None).unwrap();
+        let fixes = result
+            .fixes
+            .iter()
+            .map(|fix| match fix.needs_content {
+                true => format!("{}: {} (needs content)", fix.line, fix.description),
+                false => format!("{}: {}", fix.line, fix.description),
+            })
+            .collect();
+
+        (result.source, fixes, result.problems.len())
+    }
+
+    #[test]
+    fn test_heading_levels_case_and_quotes_are_fixed() {
+        let (source, fixes, problems) = fix(
+            "# Howser\n\n### getting started\n\nRun it.\n\n\
+             ## Don\u{2019}t Panic\n\n## License\n\nCopyright 2018 PolySync\n",
+        );
+
+        assert_eq!(
+            source,
+            "# Howser\n\n## Getting Started\n\nRun it.\n\n\
+             ## Don't Panic\n\n## License\n\nCopyright 2018 PolySync\n"
+        );
+        assert_eq!(
+            fixes,
+            vec![
+                "3: changed an H3 to an H2",
+                "3: changed \"getting started\" to \"Getting Started\"",
+                "7: changed \"Don\u{2019}t Panic\" to \"Don't Panic\"",
+            ]
+        );
+        assert_eq!(problems, 0);
+    }
+
+    #[test]
+    fn test_missing_elements_are_scaffolded() {
+        let (source, fixes, problems) = fix("# Howser\n\n## License\n");
+
+        assert_eq!(
+            source,
+            "# Howser\n\n## Getting Started\n\nTODO\n\n## Don't Panic\n\n\
+             ## License\n\nCopyright TODO PolySync\n"
+        );
+        assert_eq!(
+            fixes,
+            vec![
+                "3: inserted \"## Getting Started\"",
+                "5: inserted \"TODO\" (needs content)",
+                "7: inserted \"## Don't Panic\"",
+                "11: inserted \"Copyright TODO PolySync\" (needs content)",
+            ]
+        );
+        assert_eq!(problems, 0);
+    }
+
+    #[test]
+    fn test_insertions_leave_other_problems_alone() {
+        let text = "# Howser\n\n## License\n";
+        let result = insert_missing_elements(RX, None, text, None).unwrap();
+        assert_eq!(result.source, fix(text).0);
+
+        let text = "# Howser\n\n### Getting Started\n\nRun it.\n\n## Don't Panic\n\n\
+                    ## License\n\nCopyright 2018 PolySync\n";
+        let result = insert_missing_elements(RX, None, text, None).unwrap();
+        assert_eq!(result.source, text);
+        assert!(result.fixes.is_empty());
+        assert_eq!(result.problems.len(), 1);
+    }
+
+    #[test]
+    fn test_unsafe_problems_are_left_alone() {
+        let text = "# Howser\n\n## Installation\n\nRun it.\n";
+        let (source, fixes, problems) = fix(text);
+
+        assert_eq!(source, text);
+        assert!(fixes.is_empty());
+        assert_eq!(problems, 1);
+    }
+}
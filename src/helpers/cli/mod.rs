@@ -2,6 +2,7 @@ extern crate termion;
 
 use self::termion::color;
 use self::termion::style;
+use ast::Span;
 
 pub enum ShellText {
     Literal(String),
@@ -59,6 +60,74 @@ pub fn as_code_lines(code: &str, start_line: usize) -> Vec<String> {
         .collect()
 }
 
+/// Returns the lines of `source` that the span covers.
+pub fn source_lines(source: &str, span: &Span) -> String {
+    source
+        .lines()
+        .skip(span.start_line.saturating_sub(1))
+        .take(span.end_line.saturating_sub(span.start_line) + 1)
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+/// Formats lines of source text, numbered from `start_line`, with the spanned text underlined by
+/// carets in the manner of rustc diagnostics.
+///
+/// Lines of `code` outside of the span are shown without an underline.
+pub fn as_source_excerpt(code: &str, start_line: usize, span: &Span) -> Vec<String> {
+    let last_line = start_line + code.lines().count().saturating_sub(1);
+    let gutter_width = last_line.max(span.end_line).to_string().len();
+    let blank_gutter = format!(
+        "{}{} |{}",
+        style::Faint,
+        " ".repeat(gutter_width),
+        style::Reset
+    );
+    let mut excerpt = vec![blank_gutter.clone()];
+
+    for (i, line) in code.lines().enumerate() {
+        let line_num = i + start_line;
+        excerpt.push(format!(
+            "{}{:>width$} |{} {}{}{}",
+            style::Faint,
+            line_num,
+            style::Reset,
+            color::Fg(color::LightBlue),
+            line,
+            color::Fg(color::Reset),
+            width = gutter_width
+        ));
+
+        if line_num < span.start_line || line_num > span.end_line {
+            continue;
+        }
+        let line_length = line.chars().count();
+        let first_column = match line_num == span.start_line {
+            true => span.start_column.max(1),
+            false => 1,
+        };
+        let last_column = match line_num == span.end_line {
+            true => span.end_column.min(line_length),
+            false => line_length,
+        };
+        let padding: String = line.chars()
+            .take(first_column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat((last_column + 1).saturating_sub(first_column).max(1));
+        excerpt.push(format!(
+            "{} {}{}{}{}",
+            blank_gutter,
+            padding,
+            color::Fg(color::Red),
+            carets,
+            color::Fg(color::Reset)
+        ));
+    }
+
+    excerpt
+}
+
 pub fn indented_lines(lines: &Vec<String>, indent: usize) -> Vec<String> {
     let leading_spaces = vec![" "; indent].join("");
     lines
@@ -66,3 +135,26 @@ pub fn indented_lines(lines: &Vec<String>, indent: usize) -> Vec<String> {
         .map(|line| format!("{}{}", leading_spaces, line))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::color;
+    use super::{as_source_excerpt, source_lines};
+    use ast::Span;
+
+    #[test]
+    fn test_source_excerpt_underlines_span() {
+        let source = "# Title\n\nSay hello quietly\n";
+        let span = Span::new(3, 5, 3, 9);
+        let excerpt = as_source_excerpt(&source_lines(source, &span), 3, &span);
+        let underline = format!(
+            "     {}^^^^^{}",
+            color::Fg(color::Red),
+            color::Fg(color::Reset)
+        );
+
+        assert_eq!(excerpt.len(), 3);
+        assert!(excerpt[1].contains("Say hello quietly"));
+        assert!(excerpt[2].ends_with(&underline));
+    }
+}
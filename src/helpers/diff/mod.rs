@@ -0,0 +1,137 @@
+//! Line based differences between two versions of a text.
+
+/// Number of unchanged lines shown around each change.
+const CONTEXT_LINES: usize = 3;
+
+/// A line of a diff, tagged with how it changed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DiffLine<'a> {
+    Unchanged(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Returns the differences between `old` and `new` in unified diff format, or an empty string if
+/// they are the same.
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let lines = diff_lines(old, new);
+    let changes: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|&(_, line)| match line {
+            &DiffLine::Unchanged(_) => false,
+            _ => true,
+        })
+        .map(|(i, _)| i)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Group the changes into hunks that share their context.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &change in &changes {
+        let start = change.saturating_sub(CONTEXT_LINES);
+        let end = (change + CONTEXT_LINES + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut output = vec![format!("--- {}", old_name), format!("+++ {}", new_name)];
+    for (start, end) in hunks {
+        let old_start = 1 + lines[..start]
+            .iter()
+            .filter(|line| !is_added(line))
+            .count();
+        let new_start = 1 + lines[..start]
+            .iter()
+            .filter(|line| !is_removed(line))
+            .count();
+        let hunk = &lines[start..end];
+        let old_length = hunk.iter().filter(|line| !is_added(line)).count();
+        let new_length = hunk.iter().filter(|line| !is_removed(line)).count();
+
+        output.push(format!(
+            "@@ -{},{} +{},{} @@",
+            old_start, old_length, new_start, new_length
+        ));
+        for line in hunk {
+            output.push(match line {
+                &DiffLine::Unchanged(text) => format!(" {}", text),
+                &DiffLine::Removed(text) => format!("-{}", text),
+                &DiffLine::Added(text) => format!("+{}", text),
+            });
+        }
+    }
+
+    output.join("\n")
+}
+
+/// Pairs up the lines of two texts using their longest common subsequence.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Lengths of the longest common subsequences of every pair of suffixes.
+    let mut lengths = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = match old[i] == new[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Unchanged(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+
+    lines
+}
+
+fn is_added(line: &DiffLine) -> bool {
+    match line {
+        &DiffLine::Added(_) => true,
+        _ => false,
+    }
+}
+
+fn is_removed(line: &DiffLine) -> bool {
+    match line {
+        &DiffLine::Removed(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unified_diff;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "# Title\n\n### Usage\n\nSome text.\n";
+        let new = "# Title\n\n## Usage\n\nSome text.\n\n## License\n";
+
+        assert_eq!(
+            unified_diff(old, new, "a/README.md", "b/README.md"),
+            "--- a/README.md\n+++ b/README.md\n@@ -1,5 +1,7 @@\n # Title\n \n\
+             -### Usage\n+## Usage\n \n Some text.\n+\n+## License"
+        );
+        assert_eq!(unified_diff(old, old, "a", "b"), "");
+    }
+}
@@ -0,0 +1,252 @@
+//! Approximate text comparison for describing near misses.
+
+use std::ops::Range;
+
+/// Shortest expected text that is compared by edit distance. Shorter text only gets case and
+/// whitespace suggestions, since a single edit changes too much of it.
+const MIN_FUZZY_LENGTH: usize = 4;
+
+/// Number of characters of expected text allowed per edit before text is no longer a near miss.
+const CHARACTERS_PER_EDIT: usize = 5;
+
+/// Returns the region of `haystack` that is the fewest edits away from `needle`.
+pub fn closest_region(needle: &str, haystack: &str) -> String {
+    haystack[closest_region_range(needle, haystack)].to_string()
+}
+
+/// Returns the byte range of the region of `haystack` that is the fewest edits away from `needle`.
+///
+/// Ties are broken in favor of the region closest in length to `needle`, then the one that
+/// appears first. The region is extended to include any words it only partly covers.
+pub fn closest_region_range(needle: &str, haystack: &str) -> Range<usize> {
+    let needle: Vec<char> = needle.chars().collect();
+    let haystack: Vec<char> = haystack.chars().collect();
+
+    // Each cell holds the distance between a prefix of the needle and the best region of the
+    // haystack ending at that column, along with where that region starts.
+    let mut previous: Vec<(usize, usize)> = (0..haystack.len() + 1).map(|j| (0, j)).collect();
+    for (i, needle_char) in needle.iter().enumerate() {
+        let mut current = vec![(i + 1, 0)];
+        for (j, haystack_char) in haystack.iter().enumerate() {
+            let cost = match needle_char == haystack_char {
+                true => 0,
+                false => 1,
+            };
+            let (substitution, substitution_start) = previous[j];
+            let (deletion, deletion_start) = previous[j + 1];
+            let (insertion, insertion_start) = current[j];
+            let substitution = substitution + cost;
+            let cell = if substitution <= deletion + 1 && substitution <= insertion + 1 {
+                (substitution, substitution_start)
+            } else if deletion <= insertion {
+                (deletion + 1, deletion_start)
+            } else {
+                (insertion + 1, insertion_start)
+            };
+            current.push(cell);
+        }
+        previous = current;
+    }
+
+    let length_difference = |start: usize, end: usize| {
+        let length = end - start;
+        match length > needle.len() {
+            true => length - needle.len(),
+            false => needle.len() - length,
+        }
+    };
+    let mut best = (usize::max_value(), usize::max_value(), 0, 0);
+    for (end, &(distance, start)) in previous.iter().enumerate() {
+        let candidate = (distance, length_difference(start, end), start, end);
+        if (candidate.0, candidate.1) < (best.0, best.1) {
+            best = candidate;
+        }
+    }
+
+    // Regions are widened so they never split a word.
+    let (mut start, mut end) = (best.2, best.3);
+    while start > 0 && start < haystack.len() && haystack[start - 1].is_alphanumeric()
+        && haystack[start].is_alphanumeric()
+    {
+        start -= 1;
+    }
+    while end > 0 && end < haystack.len() && haystack[end - 1].is_alphanumeric()
+        && haystack[end].is_alphanumeric()
+    {
+        end += 1;
+    }
+
+    let byte_offset = |index: usize| haystack[..index].iter().map(|c| c.len_utf8()).sum();
+    byte_offset(start)..byte_offset(end)
+}
+
+/// Returns the number of single character insertions, deletions, substitutions, and swaps of
+/// adjacent characters needed to turn one string into another.
+pub fn edit_distance(first: &str, second: &str) -> usize {
+    let first: Vec<char> = first.chars().collect();
+    let second: Vec<char> = second.chars().collect();
+    let mut before_previous: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..second.len() + 1).collect();
+
+    for i in 0..first.len() {
+        let mut current = vec![i + 1];
+        for j in 0..second.len() {
+            let cost = match first[i] == second[j] {
+                true => 0,
+                false => 1,
+            };
+            let mut cell = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+            if i > 0 && j > 0 && first[i] == second[j - 1] && first[i - 1] == second[j] {
+                cell = cell.min(before_previous[j - 1] + 1);
+            }
+            current.push(cell);
+        }
+        before_previous = previous;
+        previous = current;
+    }
+
+    previous[second.len()]
+}
+
+/// Describes how `found` differs from `expected`, if it is close enough to be a likely mistake.
+pub fn describe_near_miss(expected: &str, found: &str) -> Option<String> {
+    if found == expected || found.is_empty() {
+        return None;
+    }
+
+    let difference = match is_cosmetic_difference(expected, found) {
+        true => {
+            let aspects: Vec<&str> = Cosmetic::ALL
+                .iter()
+                .filter(|aspect| {
+                    let others: Vec<Cosmetic> = Cosmetic::ALL
+                        .iter()
+                        .filter(|other| other != aspect)
+                        .cloned()
+                        .collect();
+                    normalize(expected, &others) != normalize(found, &others)
+                })
+                .map(|aspect| aspect.name())
+                .collect();
+            match aspects.as_slice() {
+                &["whitespace"] => "only in whitespace".to_string(),
+                _ => format!("only by {}", aspects.join(" and ")),
+            }
+        }
+        false => {
+            let length = expected.chars().count();
+            let distance = edit_distance(expected, found);
+            if length < MIN_FUZZY_LENGTH || distance > (length / CHARACTERS_PER_EDIT).max(1) {
+                return None;
+            }
+            return Some(format!(
+                "did you mean \"{}\"? \"{}\" is {} {} away",
+                expected,
+                found,
+                distance,
+                match distance {
+                    1 => "edit",
+                    _ => "edits",
+                }
+            ));
+        }
+    };
+
+    Some(format!(
+        "\"{}\" differs from \"{}\" {}",
+        found, expected, difference
+    ))
+}
+
+/// Determines if two strings differ only in ways that do not change their wording.
+pub fn is_cosmetic_difference(expected: &str, found: &str) -> bool {
+    normalize(expected, &Cosmetic::ALL) == normalize(found, &Cosmetic::ALL)
+}
+
+/// Ways that text can differ without changing its wording.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Cosmetic {
+    Case,
+    Whitespace,
+    QuoteStyle,
+}
+
+impl Cosmetic {
+    const ALL: [Cosmetic; 3] = [Cosmetic::Case, Cosmetic::Whitespace, Cosmetic::QuoteStyle];
+
+    fn name(&self) -> &'static str {
+        match self {
+            &Cosmetic::Case => "case",
+            &Cosmetic::Whitespace => "whitespace",
+            &Cosmetic::QuoteStyle => "quote style",
+        }
+    }
+}
+
+/// Removes the given cosmetic differences from text.
+fn normalize(text: &str, aspects: &[Cosmetic]) -> String {
+    let mut normalized = text.to_string();
+    for aspect in aspects {
+        normalized = match aspect {
+            &Cosmetic::Case => normalized.to_lowercase(),
+            &Cosmetic::Whitespace => normalized
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" "),
+            &Cosmetic::QuoteStyle => normalized
+                .chars()
+                .map(|c| match c {
+                    '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' => '\'',
+                    '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' => '"',
+                    c => c,
+                })
+                .collect(),
+        };
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{closest_region, describe_near_miss, edit_distance};
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+        assert_eq!(edit_distance("Copyright", "Copyrigth"), 1);
+    }
+
+    #[test]
+    fn test_closest_region() {
+        assert_eq!(closest_region("Copyright", "(c) Copyrihgt 2018"), "Copyrihgt");
+        assert_eq!(closest_region("Started", "Getting started"), "started");
+        assert_eq!(closest_region("Copyright", "Copyrigth 2018"), "Copyrigth");
+    }
+
+    #[test]
+    fn test_near_misses_are_described() {
+        assert_eq!(
+            describe_near_miss("Getting Started", "Getting started").unwrap(),
+            "\"Getting started\" differs from \"Getting Started\" only by case"
+        );
+        assert_eq!(
+            describe_near_miss("Getting Started", "Getting  Started").unwrap(),
+            "\"Getting  Started\" differs from \"Getting Started\" only in whitespace"
+        );
+        assert_eq!(
+            describe_near_miss("Installation", "Instalation").unwrap(),
+            "did you mean \"Installation\"? \"Instalation\" is 1 edit away"
+        );
+        assert_eq!(
+            describe_near_miss("Don't Panic", "don\u{2019}t panic").unwrap(),
+            "\"don\u{2019}t panic\" differs from \"Don't Panic\" only by case and quote style"
+        );
+        assert_eq!(describe_near_miss("Installation", "Usage"), None);
+        assert_eq!(describe_near_miss("Oz", "On"), None);
+    }
+}
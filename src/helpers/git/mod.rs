@@ -0,0 +1,101 @@
+//! Queries of git repositories, through the git CLI.
+
+use errors::{HowserError, HowserResult};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Returns the absolute paths of the files that differ from revision `rev` in the repository
+/// containing `directory`.
+///
+/// Committed changes since `rev`, uncommitted changes, and untracked files that are not ignored
+/// are all included. Deleted files are included too, although they no longer exist.
+pub fn changed_files<P: AsRef<Path>>(directory: P, rev: &str) -> HowserResult<Vec<PathBuf>> {
+    let directory = directory.as_ref();
+    let root = PathBuf::from(git(directory, &["rev-parse", "--show-toplevel"])?.trim());
+    let diff = git(directory, &["diff", "--name-only", "--no-renames", rev, "--"])?;
+    let untracked = git(
+        directory,
+        &["ls-files", "--others", "--exclude-standard", "--full-name"],
+    )?;
+
+    Ok(diff.lines()
+        .chain(untracked.lines())
+        .filter(|line| !line.is_empty())
+        .map(|line| root.join(line))
+        .collect())
+}
+
+/// Returns the absolute form of `path`, resolving symbolic links where the path exists so that it
+/// can be compared with the paths reported by git.
+pub fn absolute_path<P: AsRef<Path>>(path: P) -> HowserResult<PathBuf> {
+    let path = env::current_dir()?.join(path);
+    Ok(path.canonicalize().unwrap_or(path))
+}
+
+/// Runs git in `directory` with the given arguments and returns its output.
+fn git(directory: &Path, args: &[&str]) -> HowserResult<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(args)
+        .output()
+        .map_err(|e| HowserError::RuntimeError(format!("Unable to run git: {}", e)))?;
+
+    match output.status.success() {
+        true => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        false => Err(HowserError::RuntimeError(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempfile;
+
+    use super::{changed_files, git};
+    use std::fs::File;
+    use std::io::Write;
+
+    #[test]
+    fn test_changed_files_include_edits_and_new_files() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = directory.path().canonicalize().unwrap();
+        let write = |name: &str, text: &str| {
+            File::create(root.join(name))
+                .unwrap()
+                .write_all(text.as_bytes())
+                .unwrap()
+        };
+
+        git(&root, &["init", "--quiet"]).unwrap();
+        write("README.rx", "# -!!-\n");
+        write("README.md", "# Title\n");
+        write("CHANGELOG.md", "# Changes\n");
+        git(&root, &["add", "."]).unwrap();
+        git(
+            &root,
+            &[
+                "-c",
+                "user.name=Howser",
+                "-c",
+                "user.email=howser@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                "Initial",
+            ],
+        ).unwrap();
+
+        write("README.md", "# Another Title\n");
+        write("NEW.md", "# New\n");
+        let mut changed = changed_files(&root, "HEAD").unwrap();
+        changed.sort();
+        assert_eq!(changed, vec![root.join("NEW.md"), root.join("README.md")]);
+
+        assert!(changed_files(&root, "no-such-revision").is_err());
+    }
+}
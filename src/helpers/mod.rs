@@ -1,4 +1,9 @@
 pub mod cli;
+pub mod diff;
+pub mod fuzzy;
+pub mod git;
+pub mod pool;
+pub mod watch;
 
 #[cfg(test)]
 pub mod test;
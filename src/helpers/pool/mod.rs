@@ -0,0 +1,99 @@
+//! A pool of worker threads for running independent tasks.
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::usize;
+
+/// Applies `task` to each of `items` on up to `jobs` threads and returns the results in the order
+/// of `items`.
+///
+/// Items are started in order. Once `is_final` holds for the result of an item, no later item is
+/// started and the results of all later items are `None`. Every earlier item still completes, so
+/// the outcome is the same as processing the items one at a time and stopping at the first final
+/// result.
+pub fn map_ordered<T, R, F, P>(items: Vec<T>, jobs: usize, task: F, is_final: P) -> Vec<Option<R>>
+where
+    T: Send + Sync + 'static,
+    R: Send + 'static,
+    F: Fn(&T) -> R + Send + Sync + 'static,
+    P: Fn(&R) -> bool + Send + Sync + 'static,
+{
+    let count = items.len();
+    let items = Arc::new(items);
+    let task = Arc::new(task);
+    let is_final = Arc::new(is_final);
+    let next = Arc::new(AtomicUsize::new(0));
+    let first_final = Arc::new(Mutex::new(usize::MAX));
+    let results = Arc::new(Mutex::new((0..count).map(|_| None).collect::<Vec<Option<R>>>()));
+
+    let workers: Vec<thread::JoinHandle<()>> = (0..jobs.max(1).min(count))
+        .map(|_| {
+            let items = Arc::clone(&items);
+            let task = Arc::clone(&task);
+            let is_final = Arc::clone(&is_final);
+            let next = Arc::clone(&next);
+            let first_final = Arc::clone(&first_final);
+            let results = Arc::clone(&results);
+
+            thread::spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                if index >= items.len() || index > *first_final.lock().unwrap() {
+                    break;
+                }
+
+                let result = task(&items[index]);
+                if is_final(&result) {
+                    let mut first = first_final.lock().unwrap();
+                    if index < *first {
+                        *first = index;
+                    }
+                }
+                results.lock().unwrap()[index] = Some(result);
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        if let Err(cause) = worker.join() {
+            panic::resume_unwind(cause);
+        }
+    }
+
+    let first = *first_final.lock().unwrap();
+    let results: Vec<Option<R>> = results
+        .lock()
+        .unwrap()
+        .drain(..)
+        .enumerate()
+        .map(|(index, result)| match index > first {
+            true => None,
+            false => result,
+        })
+        .collect();
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::map_ordered;
+
+    #[test]
+    fn test_results_keep_the_order_of_items() {
+        let items: Vec<usize> = (0..50).collect();
+        let results = map_ordered(items, 4, |item| item * 2, |_| false);
+
+        assert_eq!(results, (0..50).map(|item| Some(item * 2)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_work_after_the_first_final_result_is_dropped() {
+        let items: Vec<usize> = (0..50).collect();
+        let results = map_ordered(items, 4, |item| *item, |item| item % 10 == 7);
+
+        assert_eq!(&results[..8], &(0..8).map(Some).collect::<Vec<_>>()[..]);
+        assert!(results[8..].iter().all(|result| result.is_none()));
+    }
+}
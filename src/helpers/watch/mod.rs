@@ -0,0 +1,124 @@
+//! Detection of changes to files on disk.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// What is known about a file when it is polled. Missing files have no stamp.
+type Stamp = Option<(SystemTime, u64)>;
+
+/// Polls a set of files for changes.
+///
+/// A file has changed when its modification time or length differ from the last poll, or when it
+/// has been created or removed since.
+pub struct FileWatcher {
+    stamps: BTreeMap<PathBuf, Stamp>,
+}
+
+impl FileWatcher {
+    /// Returns a new `FileWatcher` for the given files, as they are now.
+    pub fn new<P: AsRef<Path>>(paths: &[P]) -> Self {
+        let mut watcher = FileWatcher {
+            stamps: BTreeMap::new(),
+        };
+        watcher.set_paths(paths);
+
+        watcher
+    }
+
+    /// Replaces the watched files, keeping what is known about files that were already watched.
+    pub fn set_paths<P: AsRef<Path>>(&mut self, paths: &[P]) {
+        let mut stamps = BTreeMap::new();
+        for path in paths {
+            let path = path.as_ref().to_path_buf();
+            let stamp = match self.stamps.remove(&path) {
+                Some(stamp) => stamp,
+                None => stamp(&path),
+            };
+            stamps.insert(path, stamp);
+        }
+
+        self.stamps = stamps;
+    }
+
+    /// Returns the files that have changed since the last poll, in order.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, known) in self.stamps.iter_mut() {
+            let current = stamp(path);
+            if current != *known {
+                *known = current;
+                changed.push(path.clone());
+            }
+        }
+
+        changed
+    }
+}
+
+fn stamp(path: &Path) -> Stamp {
+    fs::metadata(path)
+        .and_then(|metadata| Ok((metadata.modified()?, metadata.len())))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempfile;
+
+    use super::FileWatcher;
+    use std::fs::{self, File, OpenOptions};
+    use std::io::Write;
+
+    #[test]
+    fn test_changes_are_reported_once() {
+        let directory = tempfile::tempdir().unwrap();
+        let watched = directory.path().join("README.md");
+        let unwatched = directory.path().join("README.rx");
+        let mut watcher = FileWatcher::new(&[&watched]);
+        assert!(watcher.changed().is_empty());
+
+        File::create(&watched).unwrap().write_all(b"# Title").unwrap();
+        File::create(&unwatched).unwrap().write_all(b"# -!!-").unwrap();
+        assert_eq!(watcher.changed(), vec![watched.clone()]);
+        assert!(watcher.changed().is_empty());
+
+        File::create(&watched).unwrap().write_all(b"# Title\n\nText").unwrap();
+        assert_eq!(watcher.changed(), vec![watched.clone()]);
+
+        fs::remove_file(&watched).unwrap();
+        assert_eq!(watcher.changed(), vec![watched.clone()]);
+    }
+
+    #[test]
+    fn test_modified_files_are_reported() {
+        let directory = tempfile::tempdir().unwrap();
+        let document = directory.path().join("README.md");
+        let pharmacy = directory.path().join("Pharmacy.toml");
+        File::create(&document).unwrap().write_all(b"# Title").unwrap();
+        File::create(&pharmacy).unwrap().write_all(b"[Specs]").unwrap();
+        let mut watcher = FileWatcher::new(&[&document, &pharmacy]);
+
+        OpenOptions::new()
+            .append(true)
+            .open(&pharmacy)
+            .unwrap()
+            .write_all(b"\n\"README.rx\" = \"README.md\"")
+            .unwrap();
+        assert_eq!(watcher.changed(), vec![pharmacy.clone()]);
+        assert!(watcher.changed().is_empty());
+    }
+
+    #[test]
+    fn test_new_paths_start_unchanged() {
+        let directory = tempfile::tempdir().unwrap();
+        let first = directory.path().join("first.md");
+        let second = directory.path().join("second.md");
+        File::create(&second).unwrap();
+
+        let mut watcher = FileWatcher::new(&[&first]);
+        watcher.set_paths(&[&first, &second]);
+        assert!(watcher.changed().is_empty());
+    }
+}
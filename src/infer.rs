@@ -0,0 +1,419 @@
+//! Infers draft prescriptions from example documents.
+
+use ast::{Node, NodeKind, Span, Tree, ROOT_ID};
+use constants::{DITTO_TOKEN, MANDATORY_PROMPT, OPTIONAL_PROMPT};
+use document::Document;
+use errors::HowserResult;
+use validator::types_match;
+
+/// Score for aligning blocks of the same type.
+const TYPE_MATCH_SCORE: usize = 1;
+/// Score for aligning blocks with the same content, which anchors headings shared by documents.
+const CONTENT_MATCH_SCORE: usize = 3;
+
+/// Top level blocks from each document that were aligned with each other.
+struct Column<'t> {
+    /// The run of adjacent blocks contributed by each document, empty if it contributed none.
+    members: Vec<Vec<Node<'t>>>,
+}
+
+impl<'t> Column<'t> {
+    /// Returns the first block in the column.
+    fn representative(&self) -> Node<'t> {
+        *self.members
+            .iter()
+            .filter_map(|run| run.first())
+            .next()
+            .expect("Columns always hold at least one block")
+    }
+
+    /// Returns every block in the column.
+    fn nodes(&self) -> Vec<Node<'t>> {
+        self.members.iter().flat_map(|run| run.clone()).collect()
+    }
+
+    /// Determines if every document contributed to the column.
+    fn is_mandatory(&self) -> bool {
+        self.members.iter().all(|run| !run.is_empty())
+    }
+
+    /// Determines if any document contributed several adjacent blocks to the column.
+    fn is_repeated(&self) -> bool {
+        self.members.iter().any(|run| run.len() > 1)
+    }
+}
+
+/// Returns the text of a draft prescription that describes the shape shared by `documents`.
+///
+/// The top level blocks of the documents are aligned with each other. Blocks with the same content
+/// in every document are kept as literals, while varying content becomes a prompt. Blocks missing
+/// from some documents become optional, and runs of adjacent blocks of the same type collapse into
+/// a wildcard followed by a ditto. The draft should be validated against the documents, since
+/// some shapes cannot be expressed exactly.
+pub fn infer_prescription(documents: &[Document]) -> HowserResult<String> {
+    trace!("infer_prescription()");
+    let mut columns = Vec::new();
+    for (index, document) in documents.iter().enumerate() {
+        let runs = group_runs(&document.root())?;
+        columns = align(columns, runs, index)?;
+    }
+
+    let mut tree = Tree::new();
+    for column in merge_adjacent_columns(columns)? {
+        append_column(&mut tree, &column)?;
+    }
+
+    let mut prescription = tree.root().render_commonmark();
+    prescription.push('\n');
+    Ok(prescription)
+}
+
+/// Splits the children of `parent` into runs of adjacent blocks of the same type.
+///
+/// Headings are never grouped, since they mark out the sections of a document.
+fn group_runs<'t>(parent: &Node<'t>) -> HowserResult<Vec<Vec<Node<'t>>>> {
+    let mut runs: Vec<Vec<Node<'t>>> = Vec::new();
+
+    for child in parent.children() {
+        let extends_run = match runs.last() {
+            Some(run) => is_groupable(&run[0], &child)?,
+            None => false,
+        };
+        match (extends_run, runs.last_mut()) {
+            (true, Some(run)) => run.push(child),
+            _ => runs.push(vec![child]),
+        }
+    }
+
+    Ok(runs)
+}
+
+/// Determines if two blocks can be described by the same element of a prescription.
+fn is_groupable(node: &Node, other: &Node) -> HowserResult<bool> {
+    match node.kind() {
+        &NodeKind::Heading(_) => Ok(false),
+        _ => types_match(node, other),
+    }
+}
+
+/// Aligns the runs of a document with the columns built from the preceding documents.
+///
+/// The alignment maximises the total score of the paired blocks, like a longest common subsequence.
+fn align<'t>(
+    columns: Vec<Column<'t>>,
+    runs: Vec<Vec<Node<'t>>>,
+    index: usize,
+) -> HowserResult<Vec<Column<'t>>> {
+    let mut scores = vec![vec![0; runs.len()]; columns.len()];
+    for (i, column) in columns.iter().enumerate() {
+        let representative = column.representative();
+        for (j, run) in runs.iter().enumerate() {
+            scores[i][j] = match types_match(&representative, &run[0])? {
+                true if representative.render_commonmark() == run[0].render_commonmark() => {
+                    CONTENT_MATCH_SCORE
+                }
+                true => TYPE_MATCH_SCORE,
+                false => 0,
+            };
+        }
+    }
+
+    // best[i][j] is the highest score for aligning the columns from i with the runs from j.
+    let mut best = vec![vec![0; runs.len() + 1]; columns.len() + 1];
+    for i in (0..columns.len()).rev() {
+        for j in (0..runs.len()).rev() {
+            let paired = match scores[i][j] {
+                0 => 0,
+                score => best[i + 1][j + 1] + score,
+            };
+            best[i][j] = paired.max(best[i + 1][j]).max(best[i][j + 1]);
+        }
+    }
+
+    // Tracing forwards prefers the earliest pairing when alignments score the same.
+    let (column_count, run_count) = (columns.len(), runs.len());
+    let mut columns = columns.into_iter();
+    let mut runs = runs.into_iter();
+    let mut aligned = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < column_count || j < run_count {
+        let is_paired = i < column_count && j < run_count && scores[i][j] > 0
+            && best[i][j] == best[i + 1][j + 1] + scores[i][j];
+
+        if is_paired || (i < column_count && best[i][j] == best[i + 1][j]) {
+            let mut column = columns.next().expect("Each column is aligned once");
+            let run = match is_paired {
+                true => runs.next().expect("Each run is aligned once"),
+                false => Vec::new(),
+            };
+            column.members.push(run);
+            aligned.push(column);
+            i += 1;
+            if is_paired {
+                j += 1;
+            }
+        } else {
+            let mut members = vec![Vec::new(); index];
+            members.push(runs.next().expect("Each run is aligned once"));
+            aligned.push(Column { members });
+            j += 1;
+        }
+    }
+
+    Ok(aligned)
+}
+
+/// Merges adjacent columns of the same type, which would otherwise make the draft ambiguous.
+fn merge_adjacent_columns<'t>(columns: Vec<Column<'t>>) -> HowserResult<Vec<Column<'t>>> {
+    let mut merged: Vec<Column<'t>> = Vec::new();
+
+    for column in columns {
+        let merges = match merged.last() {
+            Some(last) => is_groupable(&last.representative(), &column.representative())?,
+            None => false,
+        };
+        match (merges, merged.last_mut()) {
+            (true, Some(last)) => {
+                for (run, mut other) in last.members.iter_mut().zip(column.members) {
+                    run.append(&mut other);
+                }
+            }
+            _ => merged.push(column),
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Appends the prescription elements describing a column to the root of `tree`.
+fn append_column(tree: &mut Tree, column: &Column) -> HowserResult<()> {
+    let representative = column.representative();
+    let prompt = match column.is_mandatory() {
+        true => MANDATORY_PROMPT,
+        false => OPTIONAL_PROMPT,
+    };
+
+    if let &NodeKind::ThematicBreak = representative.kind() {
+        tree.append_copy(ROOT_ID, &representative);
+        return Ok(());
+    }
+    if column.is_repeated() {
+        append_wildcard(tree, &representative, prompt);
+        append_wildcard(tree, &representative, DITTO_TOKEN);
+        return Ok(());
+    }
+
+    let nodes = column.nodes();
+    let content = representative.render_commonmark();
+    let is_literal = nodes.iter().all(|node| node.render_commonmark() == content);
+
+    match (representative.kind(), is_literal) {
+        (&NodeKind::Paragraph, _) | (&NodeKind::Heading(_), _) => {
+            let affixed_prompt = match is_literal {
+                true => None,
+                false => match affixed_prompt(&nodes) {
+                    Some(text) => Some(text),
+                    None => {
+                        append_wildcard(tree, &representative, prompt);
+                        return Ok(());
+                    }
+                },
+            };
+            append_leaf(tree, &representative, affixed_prompt, column.is_mandatory());
+        }
+        (&NodeKind::List(_), true) if column.is_mandatory() => {
+            tree.append_copy(ROOT_ID, &representative);
+        }
+        _ => append_wildcard(tree, &representative, prompt),
+    }
+
+    Ok(())
+}
+
+/// Appends a paragraph or heading with the content of `node`, or with `text` in its place.
+///
+/// Optional leaves are annotated with an optional prompt, which is part of a paragraph but
+/// precedes a heading as a heading of its own.
+fn append_leaf(tree: &mut Tree, node: &Node, text: Option<String>, is_mandatory: bool) {
+    if !is_mandatory {
+        if let &NodeKind::Heading(_) = node.kind() {
+            let annotation = tree.append(ROOT_ID, node.kind().clone(), Span::default());
+            let prompt = NodeKind::Text(OPTIONAL_PROMPT.to_string());
+            tree.append(annotation, prompt, Span::default());
+        }
+    }
+
+    let id = tree.append(ROOT_ID, node.kind().clone(), Span::default());
+    if let (false, &NodeKind::Paragraph) = (is_mandatory, node.kind()) {
+        let prompt = NodeKind::Text(OPTIONAL_PROMPT.to_string());
+        tree.append(id, prompt, Span::default());
+        tree.append(id, NodeKind::SoftBreak, Span::default());
+    }
+
+    match text {
+        Some(text) => {
+            tree.append(id, NodeKind::Text(text), Span::default());
+        }
+        None => {
+            for child in node.children() {
+                tree.append_copy(id, &child);
+            }
+        }
+    }
+}
+
+/// Appends a wildcard element of the same type as `node`, annotated with `token`.
+fn append_wildcard(tree: &mut Tree, node: &Node, token: &str) {
+    let prompt = NodeKind::Text(token.to_string());
+
+    match node.kind() {
+        &NodeKind::Heading(_) => {
+            let id = tree.append(ROOT_ID, node.kind().clone(), Span::default());
+            tree.append(id, prompt, Span::default());
+        }
+        &NodeKind::CodeBlock { .. } => {
+            let code_block = NodeKind::CodeBlock {
+                info: token.to_string(),
+                literal: String::new(),
+            };
+            tree.append(ROOT_ID, code_block, Span::default());
+        }
+        &NodeKind::BlockQuote => {
+            let id = tree.append(ROOT_ID, NodeKind::BlockQuote, Span::default());
+            let paragraph = tree.append(id, NodeKind::Paragraph, Span::default());
+            tree.append(paragraph, prompt, Span::default());
+        }
+        &NodeKind::List(_) => {
+            let id = tree.append(ROOT_ID, node.kind().clone(), Span::default());
+            for item_token in &[token, DITTO_TOKEN] {
+                let item = tree.append(id, NodeKind::Item, Span::default());
+                let paragraph = tree.append(item, NodeKind::Paragraph, Span::default());
+                let item_prompt = NodeKind::Text(item_token.to_string());
+                tree.append(paragraph, item_prompt, Span::default());
+            }
+        }
+        _ => {
+            let id = tree.append(ROOT_ID, NodeKind::Paragraph, Span::default());
+            tree.append(id, prompt, Span::default());
+        }
+    }
+}
+
+/// Returns text that keeps the words shared by the start and end of every node and prompts for
+/// the words in between.
+///
+/// Returns `None` if the nodes are not plain text or share no words.
+fn affixed_prompt(nodes: &Vec<Node>) -> Option<String> {
+    let mut texts = Vec::new();
+    for node in nodes {
+        match (node.first_child(), node.children().count()) {
+            (Some(child), 1) => match child.kind() {
+                &NodeKind::Text(ref text) => texts.push(text.as_str()),
+                _ => return None,
+            },
+            _ => return None,
+        }
+    }
+
+    let first = texts[0];
+    let mut prefix_len = first.len();
+    let mut suffix_len = first.len();
+    for text in &texts[1..] {
+        let shared_prefix = first
+            .char_indices()
+            .zip(text.chars())
+            .take_while(|&((_, a), b)| a == b)
+            .last()
+            .map(|((i, a), _)| i + a.len_utf8())
+            .unwrap_or(0);
+        let shared_suffix = first
+            .chars()
+            .rev()
+            .zip(text.chars().rev())
+            .take_while(|&(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        prefix_len = prefix_len.min(shared_prefix);
+        suffix_len = suffix_len.min(shared_suffix);
+    }
+
+    let prefix = &first[..prefix_len];
+    let prefix = match prefix.rfind(' ') {
+        Some(end) => &prefix[..end + 1],
+        None => "",
+    };
+    let suffix = &first[first.len() - suffix_len..];
+    let suffix = match suffix.find(' ') {
+        Some(start) => &suffix[start..],
+        None => "",
+    };
+
+    let has_content = texts.iter().all(|text| {
+        text.len() > prefix.len() + suffix.len()
+            && !text[prefix.len()..text.len() - suffix.len()].trim().is_empty()
+    });
+    if (prefix.is_empty() && suffix.is_empty()) || !has_content {
+        return None;
+    }
+
+    Some(format!("{}{}{}", prefix, MANDATORY_PROMPT, suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::infer_prescription;
+    use document::{Document, Prescription};
+    use validator::Validator;
+
+    const ALPHA: &str = "# Alpha\n\n\
+                         Copyright 2018 PolySync\n\n\
+                         ## Usage\n\n\
+                         Run it.\n\n\
+                         Run it again.\n\n\
+                         ## License\n\n\
+                         MIT\n";
+    const BETA: &str = "# Beta\n\n\
+                        Copyright 2019 PolySync\n\n\
+                        ## Install\n\n\
+                        Cargo.\n\n\
+                        ## Usage\n\n\
+                        Run it.\n\n\
+                        ## License\n\n\
+                        MIT\n";
+
+    #[test]
+    fn test_infer_prescription() {
+        let documents = vec![
+            Document::from_text(ALPHA, None).unwrap(),
+            Document::from_text(BETA, None).unwrap(),
+        ];
+        let prescription = infer_prescription(&documents).unwrap();
+
+        assert_eq!(
+            prescription,
+            "# -!!-\n\n\
+             Copyright -!!- PolySync\n\n\
+             ## -??-\n\n\
+             ## Install\n\n\
+             -??-\nCargo.\n\n\
+             ## Usage\n\n\
+             -!!-\n\n\
+             -\"\"-\n\n\
+             ## License\n\n\
+             MIT\n"
+        );
+
+        for text in &[ALPHA, BETA] {
+            let rx = Prescription::from_text(&prescription, None).unwrap();
+            let document = Document::from_text(text, None).unwrap();
+            assert!(Validator::new(rx, document).validate().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_single_document_is_its_own_prescription() {
+        let documents = vec![Document::from_text(BETA, None).unwrap()];
+        assert_eq!(infer_prescription(&documents).unwrap(), BETA);
+    }
+}
@@ -12,12 +12,25 @@ extern crate proptest;
 #[macro_use]
 extern crate log;
 
+#[cfg(feature = "cmark")]
 extern crate doogie;
 
+pub mod ast;
+pub mod backends;
+pub mod baseline;
+pub mod build;
+pub mod cache;
 pub mod constants;
 pub mod data;
 pub mod document;
+pub mod extract;
 pub mod errors;
+pub mod fixer;
 pub mod helpers;
+pub mod infer;
+pub mod linter;
+pub mod lsp;
+pub mod pharmacy;
 pub mod reporters;
+pub mod scaffold;
 pub mod validator;
@@ -0,0 +1,320 @@
+//! Checks prescriptions for problems with their use of the Rx spec.
+//!
+//! Processing a prescription stops at the first problem that prevents it from being used. The
+//! linter instead collects every problem it can find, including constructs that are valid but
+//! will not behave the way the author most likely intended.
+
+extern crate regex;
+
+use self::regex::Regex;
+use ast::{Node, NodeId, NodeKind};
+use constants::{DITTO_TOKEN, MALFORMED_PROMPT_PATTERN, MANDATORY_PROMPT, OPTIONAL_PROMPT,
+                PROMPT_PATTERN, U_DITTO_TOKEN};
+use data::{ElementType, MatchType};
+use document::{get_annotation, Document};
+use errors::{ErrorCode, HowserResult, SpecWarning};
+use std::ops::Range;
+use validator::types_match;
+
+/// Returns all of the spec warnings for the given unprocessed `Document`, ordered by location.
+pub fn lint(document: &Document) -> HowserResult<Vec<SpecWarning>> {
+    trace!("lint()");
+    let mut warnings = Vec::new();
+    let mut tree = document.tree().clone();
+
+    // Orphaned dittos would stop processing, so they are reported and set aside first.
+    for ditto in find_orphaned_dittos(document)? {
+        warnings.push(SpecWarning::new(
+            &document.node(ditto),
+            document,
+            ErrorCode::OrphanedDitto,
+        )?);
+        tree.unlink(ditto);
+    }
+
+    let prescription = Document::new(tree, document.filename.clone())?.into_prescription()?;
+    let rx = &prescription.document;
+
+    for node in rx.root().descendants() {
+        if is_within_wildcard(&node, rx) {
+            continue;
+        }
+        if let ElementType::ContainerBlock = ElementType::determine(&node) {
+            lint_sibling_blocks(&node, rx, &mut warnings)?;
+        }
+        lint_ignored_prompts(&node, rx, &mut warnings)?;
+        lint_malformed_prompts(&node, rx, &mut warnings)?;
+    }
+
+    warnings.sort_by_key(|warning| {
+        let span = warning.span();
+        (span.start_line, span.start_column)
+    });
+
+    Ok(warnings)
+}
+
+/// Returns the ids of all ditto elements that do not directly follow an element of the same type.
+fn find_orphaned_dittos(document: &Document) -> HowserResult<Vec<NodeId>> {
+    let mut orphans = Vec::new();
+
+    for node in document.root().descendants() {
+        if !is_ditto(&node)? || is_container_annotation(&node) {
+            continue;
+        }
+
+        let is_orphaned = match node.prev_sibling() {
+            Some(previous) => is_ditto(&previous)? || !types_match(&previous, &node)?,
+            None => true,
+        };
+        if is_orphaned {
+            orphans.push(node.id());
+        }
+    }
+
+    Ok(orphans)
+}
+
+/// Determines if the given node holds the annotation of the list item or block quote containing it.
+fn is_container_annotation(node: &Node) -> bool {
+    match (node.parent(), node.prev_sibling()) {
+        (Some(parent), None) => match parent.kind() {
+            &NodeKind::Item | &NodeKind::BlockQuote => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Determines if the given unprocessed node is annotated with a ditto.
+fn is_ditto(node: &Node) -> HowserResult<bool> {
+    Ok(get_annotation(node)? == MatchType::Repeatable)
+}
+
+/// Determines if the given node is inside a wildcard, where all content is ignored.
+fn is_within_wildcard(node: &Node, rx: &Document) -> bool {
+    let mut ancestor = node.parent();
+    while let Some(parent) = ancestor {
+        if rx.is_wildcard(&parent) {
+            return true;
+        }
+        ancestor = parent.parent();
+    }
+
+    false
+}
+
+/// Determines if elements of the given match type may be absent from a valid document.
+fn is_skippable(match_type: &MatchType) -> bool {
+    match match_type {
+        &MatchType::Optional | &MatchType::Recommended => true,
+        _ => false,
+    }
+}
+
+/// Checks the block level children of a container for ambiguous and unterminated sequences.
+fn lint_sibling_blocks(
+    parent: &Node,
+    rx: &Document,
+    warnings: &mut Vec<SpecWarning>,
+) -> HowserResult<()> {
+    let mut current = parent.first_child();
+
+    while let Some(element) = current {
+        let ditto = element
+            .next_sibling()
+            .and_then(|sibling| match rx.get_match_type(&sibling) {
+                Ok(MatchType::Repeatable) => Some(sibling),
+                _ => None,
+            });
+        let following = match ditto {
+            Some(ref ditto) => ditto.next_sibling(),
+            None => element.next_sibling(),
+        };
+
+        if let Some(ref following) = following {
+            if rx.is_wildcard(&element) && types_match(&element, following)? {
+                match ditto {
+                    Some(ref ditto) => warnings.push(SpecWarning::new(
+                        ditto,
+                        rx,
+                        ErrorCode::UnterminatedRepetition,
+                    )?),
+                    None => {
+                        if is_skippable(&rx.get_match_type(&element)?)
+                            && is_skippable(&rx.get_match_type(following)?)
+                        {
+                            warnings.push(SpecWarning::new(
+                                following,
+                                rx,
+                                ErrorCode::AmbiguousOptionalElements,
+                            )?);
+                        }
+                    }
+                }
+            }
+        }
+
+        current = following;
+    }
+
+    Ok(())
+}
+
+/// Checks for prompts in content that is never compared against documents.
+fn lint_ignored_prompts(
+    node: &Node,
+    rx: &Document,
+    warnings: &mut Vec<SpecWarning>,
+) -> HowserResult<()> {
+    if rx.get_match_type(node)? == MatchType::Repeatable {
+        return Ok(());
+    }
+
+    let ignored_content = match node.kind() {
+        &NodeKind::CodeBlock {
+            ref info,
+            ref literal,
+        } => vec![info, literal],
+        &NodeKind::Image { ref url, ref title } => vec![url, title],
+        _ => Vec::new(),
+    };
+    let pattern = Regex::new(PROMPT_PATTERN)?;
+
+    if ignored_content
+        .iter()
+        .any(|content| pattern.is_match(content))
+    {
+        warnings.push(SpecWarning::new(node, rx, ErrorCode::IgnoredPrompt)?);
+    }
+
+    Ok(())
+}
+
+/// Checks for text that resembles a prompt but is not a recognized token.
+fn lint_malformed_prompts(
+    node: &Node,
+    rx: &Document,
+    warnings: &mut Vec<SpecWarning>,
+) -> HowserResult<()> {
+    match node.kind() {
+        &NodeKind::Text(ref content) | &NodeKind::Code(ref content) => {
+            for range in find_malformed_prompts(content)? {
+                let span = node.content_span(range);
+                warnings.push(
+                    SpecWarning::new(node, rx, ErrorCode::MalformedPrompt)?.with_focus(span),
+                );
+            }
+        }
+        &NodeKind::Link { ref url, ref title } => {
+            if !find_malformed_prompts(url)?.is_empty()
+                || !find_malformed_prompts(title)?.is_empty()
+            {
+                warnings.push(SpecWarning::new(node, rx, ErrorCode::MalformedPrompt)?);
+            }
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/// Returns the byte ranges of the malformed prompt tokens within the given content.
+fn find_malformed_prompts(content: &str) -> HowserResult<Vec<Range<usize>>> {
+    let pattern = Regex::new(MALFORMED_PROMPT_PATTERN)?;
+    let is_token_char = |c: char| match c {
+        '-' | '!' | '?' | '"' | '\u{201d}' => true,
+        _ => false,
+    };
+    let mut ranges = Vec::new();
+
+    for candidate in pattern.find_iter(content) {
+        // The pattern may capture a neighbouring character to check that a token is unbalanced.
+        let text = candidate.as_str();
+        let token = text.trim_matches(|c| !is_token_char(c));
+        let leading = text.len() - text.trim_left_matches(|c| !is_token_char(c)).len();
+        let start = candidate.start() + leading;
+
+        match token {
+            MANDATORY_PROMPT | OPTIONAL_PROMPT | DITTO_TOKEN | U_DITTO_TOKEN => (),
+            _ => ranges.push(start..start + token.len()),
+        }
+    }
+
+    Ok(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lint;
+    use ast::Span;
+    use document::Document;
+    use errors::{ErrorCode, Reportable};
+
+    fn lint_codes(text: &str) -> Vec<ErrorCode> {
+        let document = Document::from_text(text, None).unwrap();
+        lint(&document)
+            .unwrap()
+            .iter()
+            .map(|warning| warning.code())
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_prescriptions_have_no_warnings() {
+        let codes = lint_codes(
+            "# -!!-\n\n-!!-\n\n-\"\"-\n\n* -??-\n  ```-??-\n  ```\n* Last -!!- Item\n* -\"\"-",
+        );
+        assert!(codes.is_empty(), "{:?}", codes);
+    }
+
+    #[test]
+    fn test_all_orphaned_dittos_are_reported() {
+        let codes = lint_codes("-\"\"-\n\n# Title\n\n-\"\"-");
+        assert_eq!(
+            codes,
+            vec![ErrorCode::OrphanedDitto, ErrorCode::OrphanedDitto]
+        );
+    }
+
+    #[test]
+    fn test_adjacent_optional_wildcards_are_ambiguous() {
+        let codes = lint_codes("## -??-\n\n## -??-");
+        assert_eq!(codes, vec![ErrorCode::AmbiguousOptionalElements]);
+    }
+
+    #[test]
+    fn test_repeated_wildcard_before_same_type_never_terminates() {
+        let codes = lint_codes("* -!!-\n* -\"\"-\n* Last Item");
+        assert_eq!(codes, vec![ErrorCode::UnterminatedRepetition]);
+    }
+
+    #[test]
+    fn test_prompts_in_ignored_constructs_are_reported() {
+        let codes = lint_codes("```-!!-\nlet a = -!!-;\n```\n\n![alt](-!!-.png)");
+        assert_eq!(
+            codes,
+            vec![ErrorCode::IgnoredPrompt, ErrorCode::IgnoredPrompt]
+        );
+    }
+
+    #[test]
+    fn test_malformed_prompts_are_located() {
+        let document = Document::from_text("Copyright -!- and -??- then -!!!- or -??", None).unwrap();
+        let warnings = lint(&document).unwrap();
+        let locations: Vec<Span> = warnings
+            .iter()
+            .map(|warning| warning.prescription_location().unwrap().span)
+            .collect();
+
+        assert_eq!(
+            warnings.iter().map(|warning| warning.code()).collect::<Vec<_>>(),
+            vec![ErrorCode::MalformedPrompt; 3]
+        );
+        assert_eq!(locations, vec![
+                Span::new(1, 11, 1, 13),
+                Span::new(1, 29, 1, 33),
+                Span::new(1, 38, 1, 40),
+            ]);
+    }
+}
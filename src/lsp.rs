@@ -0,0 +1,813 @@
+//! A Language Server Protocol server for Markdown documents and .rx prescriptions.
+//!
+//! The server speaks JSON-RPC over a pair of streams, normally stdin and stdout. It keeps the text
+//! of every open file and publishes diagnostics whenever a file is opened or changed:
+//!
+//! * Markdown documents are validated against their prescription, when they have one.
+//! * Prescriptions are linted, and the documents that are open against them are revalidated.
+//!
+//! Hovering over a prompt of a prescription shows its hint comment, and documents that are missing
+//! mandatory elements are offered a code action that inserts them.
+
+extern crate regex;
+extern crate serde_json;
+
+use self::regex::Regex;
+use self::serde_json::{Map, Value};
+use ast::Span;
+use constants::{DITTO_TOKEN, MANDATORY_PROMPT, OPTIONAL_PROMPT, PROMPT_PATTERN,
+                RECOMMENDED_PROMPT, U_DITTO_TOKEN};
+use document::{Document, Prescription};
+use errors::{HowserError, HowserResult, Location, Reportable, Severity, ValidationProblem};
+use fixer::insert_missing_elements;
+use linter::lint;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use validator::Validator;
+
+/// The version of Howser, reported to clients.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The header giving the length of the content of a message.
+const CONTENT_LENGTH_HEADER: &str = "content-length";
+/// The extension of prescription files.
+const PRESCRIPTION_EXTENSION: &str = "rx";
+
+/// The JSON-RPC error code for requests whose method is not supported.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// The JSON-RPC error code for requests that could not be carried out.
+const REQUEST_FAILED: i64 = -32803;
+/// The LSP code for text documents that are always synchronized by sending their full content.
+const FULL_SYNC: i64 = 1;
+const DIAGNOSTIC_ERROR: i64 = 1;
+const DIAGNOSTIC_WARNING: i64 = 2;
+
+/// A language server for Howser.
+pub struct Server {
+    /// The absolute path of the prescription of each document that has one.
+    prescriptions: BTreeMap<PathBuf, PathBuf>,
+    /// The text of each open file, by URI.
+    open_files: BTreeMap<String, String>,
+    is_shut_down: bool,
+}
+
+impl Server {
+    /// Returns a new `Server` that validates documents against the given prescriptions, which map
+    /// the absolute path of each document to the absolute path of its prescription.
+    pub fn new(prescriptions: BTreeMap<PathBuf, PathBuf>) -> Self {
+        Server {
+            prescriptions,
+            open_files: BTreeMap::new(),
+            is_shut_down: false,
+        }
+    }
+
+    /// Serves the client on the other end of `input` and `output` until it sends an exit
+    /// notification or closes `input`.
+    ///
+    /// Returns the exit status the server should end with, which is nonzero if the client did not
+    /// ask the server to shut down first.
+    pub fn serve<R: BufRead, W: Write>(&mut self, mut input: R, mut output: W) -> HowserResult<i32> {
+        while let Some(message) = read_message(&mut input)? {
+            if message.get("method").and_then(Value::as_str) == Some("exit") {
+                break;
+            }
+            for reply in self.handle(&message) {
+                write_message(&mut output, &reply)?;
+            }
+        }
+
+        Ok(match self.is_shut_down {
+            true => 0,
+            false => 1,
+        })
+    }
+
+    /// Handles one message from the client and returns the messages to send back.
+    fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        debug!("Server::handle:: {}", method);
+
+        let id = match message.get("id") {
+            Some(id) => id.clone(),
+            None => return self.handle_notification(method, &params),
+        };
+        let result = match method {
+            "initialize" => Ok(initialize_result()),
+            "shutdown" => {
+                self.is_shut_down = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => self.hover(&params),
+            "textDocument/codeAction" => self.code_actions(&params),
+            _ => {
+                return vec![error_response(
+                    id,
+                    METHOD_NOT_FOUND,
+                    &format!("Unsupported method {}", method),
+                )]
+            }
+        };
+
+        match result {
+            Ok(result) => vec![response(id, result)],
+            Err(error) => vec![error_response(id, REQUEST_FAILED, &error_text(&error))],
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = match params.pointer("/textDocument/uri").and_then(Value::as_str) {
+            Some(uri) => uri.to_string(),
+            None => return Vec::new(),
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = params.pointer("/textDocument/text").and_then(Value::as_str);
+                self.open_files
+                    .insert(uri.clone(), text.unwrap_or("").to_string());
+            }
+            "textDocument/didChange" => {
+                // Files are synchronized in full, so the last change holds the whole text.
+                let changes = params.get("contentChanges").and_then(Value::as_array);
+                let text = changes
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+                if let Some(text) = text {
+                    self.open_files.insert(uri.clone(), text.to_string());
+                }
+            }
+            "textDocument/didSave" => (),
+            "textDocument/didClose" => {
+                self.open_files.remove(&uri);
+                let mut notifications = vec![publish_diagnostics(&uri, Vec::new())];
+                if is_prescription(&uri) {
+                    notifications.extend(self.revalidate_dependents(&uri));
+                }
+                return notifications;
+            }
+            _ => return Vec::new(),
+        }
+
+        let mut notifications = vec![publish_diagnostics(&uri, self.diagnostics(&uri))];
+        if is_prescription(&uri) {
+            notifications.extend(self.revalidate_dependents(&uri));
+        }
+        notifications
+    }
+
+    /// Returns the notifications that republish the diagnostics of the open documents whose
+    /// prescription is at `rx_uri`.
+    fn revalidate_dependents(&self, rx_uri: &str) -> Vec<Value> {
+        let rx_path = uri_to_path(rx_uri);
+        self.open_files
+            .keys()
+            .filter(|uri| {
+                self.prescriptions.get(&uri_to_path(uri)) == Some(&rx_path) && *uri != rx_uri
+            })
+            .map(|uri| publish_diagnostics(uri, self.diagnostics(uri)))
+            .collect()
+    }
+
+    /// Returns the diagnostics of the open file at `uri`.
+    fn diagnostics(&self, uri: &str) -> Vec<Value> {
+        let text = match self.open_files.get(uri) {
+            Some(text) => text,
+            None => return Vec::new(),
+        };
+        let path = uri_to_path(uri);
+        let name = Some(path.to_string_lossy().to_string());
+
+        let problems = match is_prescription(uri) {
+            true => Document::from_text(text, name).and_then(|document| {
+                Ok(lint(&document)?
+                    .into_iter()
+                    .map(|warning| Box::new(warning) as ValidationProblem)
+                    .collect())
+            }),
+            false => match self.prescriptions.get(&path) {
+                Some(rx_path) => self.validate(rx_path, text, name),
+                None => return Vec::new(),
+            },
+        };
+
+        match problems {
+            Ok(problems) => problems
+                .iter()
+                .map(|problem| diagnostic(problem, text, &path))
+                .collect(),
+            Err(error) => vec![plain_diagnostic(
+                range(text, Span::default()),
+                &error_text(&error),
+            )],
+        }
+    }
+
+    /// Validates the text of a document against the prescription at `rx_path`.
+    fn validate(
+        &self,
+        rx_path: &Path,
+        text: &str,
+        name: Option<String>,
+    ) -> HowserResult<Vec<ValidationProblem>> {
+        let rx_text = self.file_text(rx_path)?;
+        let prescription = Prescription::from_text(
+            &rx_text,
+            Some(rx_path.to_string_lossy().to_string()),
+        )?;
+        let document = Document::from_text(text, name)?;
+
+        Validator::new(prescription, document).validate()
+    }
+
+    /// Shows the kind and hint comment of the prompt under the cursor of a prescription.
+    fn hover(&self, params: &Value) -> HowserResult<Value> {
+        let uri = text_document_uri(params)?;
+        let text = match (is_prescription(&uri), self.open_files.get(&uri)) {
+            (true, Some(text)) => text,
+            _ => return Ok(Value::Null),
+        };
+        let (line, column) = match params.get("position").and_then(|p| from_position(text, p)) {
+            Some(position) => position,
+            None => return Ok(Value::Null),
+        };
+        let line_text = text.lines().nth(line - 1).unwrap_or("");
+
+        let prompt = Regex::new(PROMPT_PATTERN)?.find_iter(line_text).find(|found| {
+            let start = line_text[..found.start()].chars().count() + 1;
+            let end = start + found.as_str().chars().count();
+            start <= column && column < end
+        });
+        let prompt = match prompt {
+            Some(prompt) => prompt,
+            None => return Ok(Value::Null),
+        };
+
+        let document = Document::from_text(text, None)?;
+        let under_cursor = document
+            .root()
+            .descendants()
+            .filter(|node| contains(&node.span(), line, column))
+            .last();
+        let mut hint = None;
+        let mut current = under_cursor;
+        while let (Some(node), true) = (current, hint.is_none()) {
+            hint = document.get_comment(&node);
+            current = node.parent();
+        }
+
+        let mut contents = format!("**{}**", prompt_description(prompt.as_str()));
+        if let Some(hint) = hint {
+            contents += &format!("\n\n{}", hint.content().trim());
+        }
+        let start = line_text[..prompt.start()].chars().count() + 1;
+        let end = line_text[..prompt.end()].chars().count();
+
+        let mut hover = Map::new();
+        hover.insert("contents".to_string(), markdown(&contents));
+        hover.insert(
+            "range".to_string(),
+            range(text, Span::new(line, start, line, end)),
+        );
+        Ok(Value::Object(hover))
+    }
+
+    /// Offers to insert the missing mandatory elements of a document, scaffolded from its
+    /// prescription.
+    fn code_actions(&self, params: &Value) -> HowserResult<Value> {
+        let uri = text_document_uri(params)?;
+        let path = uri_to_path(&uri);
+        let (text, rx_path) = match (self.open_files.get(&uri), self.prescriptions.get(&path)) {
+            (Some(text), Some(rx_path)) => (text, rx_path),
+            _ => return Ok(Value::Array(Vec::new())),
+        };
+
+        let result = insert_missing_elements(
+            &self.file_text(rx_path)?,
+            Some(rx_path.to_string_lossy().to_string()),
+            text,
+            Some(path.to_string_lossy().to_string()),
+        )?;
+        if result.fixes.is_empty() {
+            return Ok(Value::Array(Vec::new()));
+        }
+
+        let title = match result.fixes.len() {
+            1 => "Insert the missing mandatory element".to_string(),
+            count => format!("Insert {} missing mandatory elements", count),
+        };
+        let mut edit = Map::new();
+        edit.insert("range".to_string(), whole_range(text));
+        edit.insert("newText".to_string(), Value::from(result.source));
+        let mut changes = Map::new();
+        changes.insert(uri, Value::Array(vec![Value::Object(edit)]));
+        let mut workspace_edit = Map::new();
+        workspace_edit.insert("changes".to_string(), Value::Object(changes));
+
+        let mut action = Map::new();
+        action.insert("title".to_string(), Value::from(title));
+        action.insert("kind".to_string(), Value::from("quickfix"));
+        action.insert("edit".to_string(), Value::Object(workspace_edit));
+        Ok(Value::Array(vec![Value::Object(action)]))
+    }
+
+    /// Returns the text of a file, preferring the text of the client's copy if it is open.
+    fn file_text(&self, path: &Path) -> HowserResult<String> {
+        if let Some(text) = self.open_files.get(&path_to_uri(path)) {
+            return Ok(text.clone());
+        }
+
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Ok(text)
+    }
+}
+
+/// Reads one message, or returns `None` at the end of the input.
+fn read_message<R: BufRead>(input: &mut R) -> HowserResult<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        let mut parts = header.splitn(2, ':');
+        if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+            if name.trim().to_lowercase() == CONTENT_LENGTH_HEADER {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or(HowserError::RuntimeError(
+        "Received a message without a Content-Length header.".to_string(),
+    ))?;
+    let mut content = vec![0; length];
+    input.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| HowserError::RuntimeError(format!("Received malformed JSON: {}", e)))
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Value) -> HowserResult<()> {
+    let content = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", content.len(), content)?;
+    output.flush()?;
+    Ok(())
+}
+
+fn initialize_result() -> Value {
+    let mut capabilities = Map::new();
+    capabilities.insert("textDocumentSync".to_string(), Value::from(FULL_SYNC));
+    capabilities.insert("hoverProvider".to_string(), Value::from(true));
+    capabilities.insert("codeActionProvider".to_string(), Value::from(true));
+    let mut server_info = Map::new();
+    server_info.insert("name".to_string(), Value::from("howser"));
+    server_info.insert("version".to_string(), Value::from(VERSION));
+
+    let mut result = Map::new();
+    result.insert("capabilities".to_string(), Value::Object(capabilities));
+    result.insert("serverInfo".to_string(), Value::Object(server_info));
+    Value::Object(result)
+}
+
+fn response(id: Value, result: Value) -> Value {
+    let mut response = Map::new();
+    response.insert("jsonrpc".to_string(), Value::from("2.0"));
+    response.insert("id".to_string(), id);
+    response.insert("result".to_string(), result);
+    Value::Object(response)
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    let mut error = Map::new();
+    error.insert("code".to_string(), Value::from(code));
+    error.insert("message".to_string(), Value::from(message));
+
+    let mut response = Map::new();
+    response.insert("jsonrpc".to_string(), Value::from("2.0"));
+    response.insert("id".to_string(), id);
+    response.insert("error".to_string(), Value::Object(error));
+    Value::Object(response)
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    let mut params = Map::new();
+    params.insert("uri".to_string(), Value::from(uri));
+    params.insert("diagnostics".to_string(), Value::Array(diagnostics));
+
+    let mut notification = Map::new();
+    notification.insert("jsonrpc".to_string(), Value::from("2.0"));
+    notification.insert(
+        "method".to_string(),
+        Value::from("textDocument/publishDiagnostics"),
+    );
+    notification.insert("params".to_string(), Value::Object(params));
+    Value::Object(notification)
+}
+
+/// Returns the diagnostic for a problem found in the file at `path`, whose text is `text`.
+///
+/// Problems are placed at their location in the file. Problems that have no location there, such
+/// as missing elements, are placed at the start of the file and point to the prescription.
+fn diagnostic(problem: &ValidationProblem, text: &str, path: &Path) -> Value {
+    let file = path.to_string_lossy();
+    let here = problem
+        .document_location()
+        .into_iter()
+        .chain(problem.prescription_location())
+        .find(|location| location.file == file);
+    let span = here.map(|location| location.span).unwrap_or_default();
+    let code = problem.code();
+
+    let mut diagnostic = match plain_diagnostic(range(text, span), code.summary()) {
+        Value::Object(diagnostic) => diagnostic,
+        _ => Map::new(),
+    };
+    let severity = match problem.severity() {
+        Severity::Warning => DIAGNOSTIC_WARNING,
+        Severity::Error => DIAGNOSTIC_ERROR,
+    };
+    diagnostic.insert("severity".to_string(), Value::from(severity));
+    diagnostic.insert("code".to_string(), Value::from(code.to_string()));
+
+    if let Some(rx_location) = problem.prescription_location() {
+        if rx_location.file != file {
+            diagnostic.insert(
+                "relatedInformation".to_string(),
+                Value::Array(vec![related_information(&rx_location)]),
+            );
+        }
+    }
+    Value::Object(diagnostic)
+}
+
+fn plain_diagnostic(range: Value, message: &str) -> Value {
+    let mut diagnostic = Map::new();
+    diagnostic.insert("range".to_string(), range);
+    diagnostic.insert("severity".to_string(), Value::from(DIAGNOSTIC_ERROR));
+    diagnostic.insert("source".to_string(), Value::from("howser"));
+    diagnostic.insert("message".to_string(), Value::from(message));
+    Value::Object(diagnostic)
+}
+
+/// Points from a diagnostic to the element of the prescription it concerns.
+///
+/// The prescription is not necessarily open, so it is read from disk to place the location.
+fn related_information(location: &Location) -> Value {
+    let path = Path::new(&location.file);
+    let mut text = String::new();
+    let _ = File::open(path).and_then(|mut file| file.read_to_string(&mut text));
+
+    let mut rx_location = Map::new();
+    rx_location.insert("uri".to_string(), Value::from(path_to_uri(path)));
+    rx_location.insert("range".to_string(), range(&text, location.span));
+    let mut information = Map::new();
+    information.insert("location".to_string(), Value::Object(rx_location));
+    information.insert(
+        "message".to_string(),
+        Value::from("Prescribed here."),
+    );
+    Value::Object(information)
+}
+
+fn markdown(value: &str) -> Value {
+    let mut content = Map::new();
+    content.insert("kind".to_string(), Value::from("markdown"));
+    content.insert("value".to_string(), Value::from(value));
+    Value::Object(content)
+}
+
+fn prompt_description(prompt: &str) -> &'static str {
+    match prompt {
+        MANDATORY_PROMPT => "Mandatory prompt",
+        OPTIONAL_PROMPT => "Optional prompt",
+        RECOMMENDED_PROMPT => "Recommended prompt",
+        DITTO_TOKEN | U_DITTO_TOKEN => "Ditto, repeating the element before it",
+        _ => "Prompt",
+    }
+}
+
+fn text_document_uri(params: &Value) -> HowserResult<String> {
+    params
+        .pointer("/textDocument/uri")
+        .and_then(Value::as_str)
+        .map(|uri| uri.to_string())
+        .ok_or(HowserError::RuntimeError(
+            "The request does not name a text document.".to_string(),
+        ))
+}
+
+fn is_prescription(uri: &str) -> bool {
+    uri_to_path(uri)
+        .extension()
+        .map_or(false, |ext| ext == PRESCRIPTION_EXTENSION)
+}
+
+/// Determines if a span contains a 1-based line and column.
+fn contains(span: &Span, line: usize, column: usize) -> bool {
+    span.is_known() && (span.start_line, span.start_column) <= (line, column)
+        && (line, column) <= (span.end_line, span.end_column)
+}
+
+/// Returns the LSP range of a span of `text`, or the start of the text if the span is unknown.
+///
+/// Spans count 1-based lines and characters and include their last character. LSP positions count
+/// 0-based lines and UTF-16 code units, and ranges exclude their end.
+fn range(text: &str, span: Span) -> Value {
+    let (start, end) = match span.is_known() {
+        true => (
+            position(text, span.start_line, span.start_column),
+            position(text, span.end_line, span.end_column + 1),
+        ),
+        false => (position(text, 1, 1), position(text, 1, 1)),
+    };
+
+    let mut range = Map::new();
+    range.insert("start".to_string(), start);
+    range.insert("end".to_string(), end);
+    Value::Object(range)
+}
+
+/// Returns the range covering all of `text`.
+fn whole_range(text: &str) -> Value {
+    let line_count = text.split('\n').count();
+    let last_line = text.split('\n').last().unwrap_or("");
+    let end = position(text, line_count, last_line.chars().count() + 1);
+
+    let mut range = Map::new();
+    range.insert("start".to_string(), position(text, 1, 1));
+    range.insert("end".to_string(), end);
+    Value::Object(range)
+}
+
+/// Returns the LSP position of a 1-based line and character column of `text`.
+fn position(text: &str, line: usize, column: usize) -> Value {
+    let line_text = text.split('\n').nth(line.max(1) - 1).unwrap_or("");
+    let character: usize = line_text
+        .chars()
+        .take(column.max(1) - 1)
+        .map(char::len_utf16)
+        .sum();
+
+    let mut position = Map::new();
+    position.insert("line".to_string(), Value::from(line.max(1) - 1));
+    position.insert("character".to_string(), Value::from(character));
+    Value::Object(position)
+}
+
+/// Returns the 1-based line and character column of an LSP position in `text`.
+fn from_position(text: &str, position: &Value) -> Option<(usize, usize)> {
+    let line = position.get("line")?.as_u64()? as usize;
+    let character = position.get("character")?.as_u64()? as usize;
+    let line_text = text.split('\n').nth(line)?;
+
+    let mut units = 0;
+    let mut column = 1;
+    for c in line_text.chars() {
+        units += c.len_utf16();
+        if units > character {
+            break;
+        }
+        column += 1;
+    }
+    Some((line + 1, column))
+}
+
+/// Returns the path of a `file:` URI, decoding escaped characters.
+fn uri_to_path(uri: &str) -> PathBuf {
+    let path = uri.trim_left_matches("file://");
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = match bytes[index] {
+            b'%' => path.get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    PathBuf::from(String::from_utf8_lossy(&decoded).to_string())
+}
+
+/// Returns the `file:` URI of an absolute path, escaping characters that URIs do not allow.
+fn path_to_uri<P: AsRef<Path>>(path: P) -> String {
+    let path = path.as_ref().to_string_lossy();
+    let mut uri = "file://".to_string();
+    for byte in path.bytes() {
+        match byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            true => uri.push(byte as char),
+            false => uri += &format!("%{:02X}", byte),
+        }
+    }
+    uri
+}
+
+/// Returns the description of an error and its causes.
+fn error_text(error: &HowserError) -> String {
+    if let &HowserError::PrescriptionError(ref warning) = error {
+        return format!("The prescription is invalid: {}", warning.code().summary());
+    }
+
+    let mut parts = vec![error.description().to_string()];
+    let mut cause = error.cause();
+    while let Some(error) = cause {
+        parts.push(error.description().to_string());
+        cause = error.cause();
+    }
+    parts.join(": ")
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+    extern crate tempfile;
+
+    use self::serde_json::Value;
+    use self::serde_json::json;
+    use super::{path_to_uri, read_message, uri_to_path, Server};
+    use std::collections::BTreeMap;
+    use std::fs::File;
+    use std::io::{BufReader, Write};
+    use std::path::PathBuf;
+
+    const RX: &str = "# -!!-<!-- Project Name -->\n\n## Usage\n\n-!!-\n";
+
+    /// Runs a server for `README.md`, validated against `README.rx`, on the given messages and
+    /// returns the exit status and the messages the server sent.
+    fn run_script(messages: &[Value]) -> (i32, Vec<Value>) {
+        let directory = tempfile::tempdir().unwrap();
+        let rx_path = directory.path().join("README.rx");
+        File::create(&rx_path)
+            .unwrap()
+            .write_all(RX.as_bytes())
+            .unwrap();
+        let mut prescriptions = BTreeMap::new();
+        prescriptions.insert(directory.path().join("README.md"), rx_path);
+
+        let mut input = Vec::new();
+        for message in messages {
+            let content = message
+                .to_string()
+                .replace("$DIR", &path_to_uri(directory.path()));
+            write!(input, "Content-Length: {}\r\n\r\n{}", content.len(), content).unwrap();
+        }
+        let mut output = Vec::new();
+        let status = Server::new(prescriptions)
+            .serve(&input[..], &mut output)
+            .unwrap();
+
+        let mut reader = BufReader::new(&output[..]);
+        let mut replies = Vec::new();
+        while let Some(reply) = read_message(&mut reader).unwrap() {
+            replies.push(reply);
+        }
+        (status, replies)
+    }
+
+    fn did_open(name: &str, text: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": {
+                    "uri": format!("$DIR/{}", name),
+                    "languageId": "markdown",
+                    "version": 1,
+                    "text": text
+                }
+            }
+        })
+    }
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+    }
+
+    #[test]
+    fn test_server_initializes_and_shuts_down() {
+        let (status, replies) = run_script(&[
+            request(1, "initialize", json!({"capabilities": {}})),
+            json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+            request(2, "workspace/symbol", json!({"query": ""})),
+            request(3, "shutdown", Value::Null),
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+        ]);
+
+        assert_eq!(status, 0);
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0]["result"]["capabilities"]["hoverProvider"], true);
+        assert_eq!(replies[1]["error"]["code"], -32601);
+        assert_eq!(replies[2]["id"], 3);
+
+        let (status, _) = run_script(&[request(1, "initialize", json!({"capabilities": {}}))]);
+        assert_eq!(status, 1);
+    }
+
+    #[test]
+    fn test_documents_and_prescriptions_are_diagnosed() {
+        let (_, replies) = run_script(&[
+            did_open("README.md", "# Howser\n\n### Usage\n\nRun it.\n"),
+            did_open("OTHER.md", "Anything goes\n"),
+            did_open("BROKEN.rx", "-\"\"-\n"),
+        ]);
+
+        let md = &replies[0]["params"];
+        assert!(md["uri"].as_str().unwrap().ends_with("/README.md"));
+        assert_eq!(md["diagnostics"].as_array().unwrap().len(), 1);
+        let diagnostic = &md["diagnostics"][0];
+        assert_eq!(diagnostic["code"], "HX0005");
+        assert_eq!(diagnostic["severity"], 1);
+        assert_eq!(diagnostic["range"]["start"], json!({"line": 2, "character": 0}));
+
+        assert_eq!(replies[1]["params"]["diagnostics"], json!([]));
+
+        let rx = &replies[2]["params"];
+        assert!(rx["uri"].as_str().unwrap().ends_with("/BROKEN.rx"));
+        assert_eq!(rx["diagnostics"][0]["code"], "HX0009");
+    }
+
+    #[test]
+    fn test_hover_shows_the_hint_of_a_prompt() {
+        let position = |line: u64, character: u64| {
+            json!({
+                "textDocument": {"uri": "$DIR/README.rx"},
+                "position": {"line": line, "character": character}
+            })
+        };
+        let (_, replies) = run_script(&[
+            did_open("README.rx", RX),
+            request(1, "textDocument/hover", position(0, 3)),
+            request(2, "textDocument/hover", position(4, 0)),
+            request(3, "textDocument/hover", position(2, 4)),
+        ]);
+
+        let hover = &replies[1]["result"];
+        assert_eq!(
+            hover["contents"]["value"],
+            "**Mandatory prompt**\n\nProject Name"
+        );
+        assert_eq!(
+            hover["range"],
+            json!({"start": {"line": 0, "character": 2}, "end": {"line": 0, "character": 6}})
+        );
+        assert_eq!(replies[2]["result"]["contents"]["value"], "**Mandatory prompt**");
+        assert_eq!(replies[3]["result"], Value::Null);
+    }
+
+    #[test]
+    fn test_code_action_inserts_missing_sections() {
+        let text = "# Howser\n";
+        let (_, replies) = run_script(&[
+            did_open("README.md", text),
+            request(
+                1,
+                "textDocument/codeAction",
+                json!({
+                    "textDocument": {"uri": "$DIR/README.md"},
+                    "range": {"start": {"line": 0, "character": 0},
+                              "end": {"line": 0, "character": 0}},
+                    "context": {"diagnostics": []}
+                }),
+            ),
+        ]);
+
+        let actions = replies[1]["result"].as_array().unwrap();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0]["kind"], "quickfix");
+        let edits = actions[0]["edit"]["changes"].as_object().unwrap();
+        let edit = &edits.values().next().unwrap()[0];
+        assert_eq!(edit["newText"], "# Howser\n\n## Usage\n\nTODO\n");
+        assert_eq!(
+            edit["range"],
+            json!({"start": {"line": 0, "character": 0}, "end": {"line": 1, "character": 0}})
+        );
+    }
+
+    #[test]
+    fn test_uris_round_trip() {
+        let path = PathBuf::from("/home/some one/docs/R\u{e9}sum\u{e9}.md");
+        let uri = path_to_uri(&path);
+        assert_eq!(uri, "file:///home/some%20one/docs/R%C3%A9sum%C3%A9.md");
+        assert_eq!(uri_to_path(&uri), path);
+    }
+}
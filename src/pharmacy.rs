@@ -0,0 +1,1389 @@
+//! Pharmacy files, which pair prescriptions with the documents that should conform to them.
+//!
+//! A pharmacy file is TOML with a `Specs` table. Each key names a prescription and each value
+//! names a document, or an array of documents, to validate against it:
+//!
+//! ```toml
+//! [Specs]
+//! "README.rx" = "README.md"
+//! "docs/guide.rx" = ["docs/install.md", "docs/usage.md"]
+//! ```
+//!
+//! Paths are used as written, so relative paths are relative to the working directory.
+//!
+//! An entry can also be a table, which names its documents and sets options for them:
+//!
+//! ```toml
+//! [Specs."docs/guide.rx"]
+//! documents = ["docs/install.md", "docs/usage.md"]
+//! severity = "warning"
+//! allow-extra-content = true
+//! fail-early = true
+//! tags = ["guides"]
+//! owner = "docs-team"
+//! ```
+//!
+//! `severity` reports every problem in the documents as an error or as a warning.
+//! `allow-extra-content` accepts content after everything the prescription asks for. `fail-early`
+//! stops validation after the first document of the entry with errors. `tags` let a subset of the
+//! pharmacy be selected, and `owner` is named in the report of every problem of the entry. Only
+//! one entry may set the options of a prescription.
+//!
+//! Crates can keep the same table in their `Cargo.toml` instead, as
+//! `[package.metadata.howser.Specs]`, or as `[workspace.metadata.howser.Specs]` for a workspace.
+//! Paths in a manifest are relative to the directory of the manifest. Reading the manifest of a
+//! workspace also reads the manifests of its members, so one pharmacy covers the whole workspace.
+
+extern crate toml;
+
+use self::toml::value::Table;
+use self::toml::Value;
+use ast::Span;
+use document::{Document, Prescription};
+use errors::{ErrorCode, HowserError, HowserResult, Location, PharmacyWarning, Reportable, Severity,
+             ValidationProblem};
+use helpers::fuzzy;
+use linter;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::{Component, Path, PathBuf};
+use validator::Validator;
+
+/// The file name of Cargo manifests, which keep their pharmacy in their metadata.
+const CARGO_MANIFEST: &str = "Cargo.toml";
+
+/// The keys of a pharmacy file, or of the `howser` metadata of a Cargo manifest.
+const PHARMACY_KEYS: [&str; 1] = ["Specs"];
+
+/// The keys of the table form of an entry in `Specs`.
+const ENTRY_KEYS: [&str; 6] = [
+    "documents",
+    "severity",
+    "allow-extra-content",
+    "fail-early",
+    "tags",
+    "owner",
+];
+
+/// Named wrapper around the mapping between the file locations
+/// for Rx spec files and their associated target documents intended
+/// for validation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pharmacy {
+    pub spec_to_targets: BTreeMap<PathBuf, Vec<PathBuf>>,
+    /// The options of the prescriptions whose entries set any.
+    pub spec_options: BTreeMap<PathBuf, SpecOptions>,
+    /// The files the pharmacy was read from, including the manifests of workspace members.
+    pub sources: Vec<PathBuf>,
+}
+
+/// Options for validating the documents of one prescription, set by the table form of its entry.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpecOptions {
+    /// The severity every problem is reported with, rather than the severity of its code.
+    pub severity: Option<Severity>,
+    /// Whether content after everything the prescription asks for is accepted.
+    pub allow_extra_content: bool,
+    /// Whether to stop validating after the first document with errors.
+    pub fail_early: bool,
+    pub tags: Vec<String>,
+    /// Who is responsible for the documents, named in reports.
+    pub owner: Option<String>,
+}
+
+impl SpecOptions {
+    /// Returns `problems` as they should be reported under these options.
+    pub fn apply(&self, problems: Vec<ValidationProblem>) -> Vec<ValidationProblem> {
+        if self.severity.is_none() && self.owner.is_none() {
+            return problems;
+        }
+
+        problems
+            .into_iter()
+            .map(|problem| {
+                Box::new(EntryProblem {
+                    problem,
+                    severity: self.severity,
+                    owner: self.owner.clone(),
+                }) as ValidationProblem
+            })
+            .collect()
+    }
+}
+
+/// A problem reported with the severity and owner of its pharmacy entry.
+struct EntryProblem {
+    problem: ValidationProblem,
+    severity: Option<Severity>,
+    owner: Option<String>,
+}
+
+impl EntryProblem {
+    /// Describes how the entry changed the report, if it did.
+    fn note(&self) -> Option<String> {
+        let mut notes = Vec::new();
+        match self.severity {
+            Some(Severity::Error) if self.problem.severity() != Severity::Error => {
+                notes.push("reported as an error".to_string())
+            }
+            Some(Severity::Warning) if self.problem.severity() != Severity::Warning => {
+                notes.push("reported as a warning".to_string())
+            }
+            _ => (),
+        }
+        if let Some(ref owner) = self.owner {
+            notes.push(format!("owned by {}", owner));
+        }
+
+        match notes.is_empty() {
+            true => None,
+            false => Some(notes.join(", ")),
+        }
+    }
+}
+
+impl Reportable for EntryProblem {
+    fn short_msg(&self) -> String {
+        match self.note() {
+            Some(note) => format!("{} :: {}", self.problem.short_msg(), note),
+            None => self.problem.short_msg(),
+        }
+    }
+
+    fn long_msg(&self) -> String {
+        match self.note() {
+            Some(note) => format!("{}\n\nNote: {}.", self.problem.long_msg(), note),
+            None => self.problem.long_msg(),
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        self.problem.code()
+    }
+
+    fn prescription_location(&self) -> Option<Location> {
+        self.problem.prescription_location()
+    }
+
+    fn document_location(&self) -> Option<Location> {
+        self.problem.document_location()
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity.unwrap_or(self.problem.severity())
+    }
+}
+
+impl Pharmacy {
+    /// Reads the pharmacy file `filename`, which may be a `Cargo.toml` manifest.
+    pub fn from_file<P: AsRef<Path>>(filename: P) -> HowserResult<Self> {
+        let mut reader = Reader::default();
+        reader.read_file(filename.as_ref())?;
+        reader.finish()
+    }
+
+    /// Parses the text of a pharmacy file. `filename` is only used in error messages.
+    pub fn from_toml(filename: &str, pharmacy_file_contents: &str) -> HowserResult<Self> {
+        let mut reader = Reader::default();
+        reader.read_pharmacy(filename, pharmacy_file_contents)?;
+        reader.finish()
+    }
+
+    /// Reads the pharmacy kept in the metadata of a `Cargo.toml` manifest, along with the
+    /// pharmacies of the members of its workspace, if it has one.
+    pub fn from_cargo_manifest<P: AsRef<Path>>(manifest: P) -> HowserResult<Self> {
+        let mut reader = Reader::default();
+        reader.read_cargo_manifest(manifest.as_ref())?;
+        reader.finish()
+    }
+
+    /// Returns the options of the documents of `rx_file`.
+    pub fn options(&self, rx_file: &Path) -> SpecOptions {
+        self.spec_options.get(rx_file).cloned().unwrap_or_default()
+    }
+
+    /// Checks the prescription `rx_file`, whose text is `rx_text`, against the Rx spec.
+    ///
+    /// Problems are reported under the options of the prescription's entry.
+    pub fn check_prescription(
+        &self,
+        rx_file: &Path,
+        rx_text: &str,
+    ) -> HowserResult<Vec<ValidationProblem>> {
+        let document = Document::from_text(rx_text, Some(rx_file.to_string_lossy().to_string()))?;
+        let problems = linter::lint(&document)?
+            .into_iter()
+            .map(|warning| Box::new(warning) as ValidationProblem)
+            .collect();
+
+        Ok(self.options(rx_file).apply(problems))
+    }
+
+    /// Validates the document `doc_file`, whose text is `doc_text`, against `prescription`, which
+    /// was read from `rx_file`.
+    ///
+    /// Problems are reported under the options of the prescription's entry.
+    pub fn validate_document(
+        &self,
+        rx_file: &Path,
+        prescription: Prescription,
+        doc_file: &Path,
+        doc_text: &str,
+    ) -> HowserResult<Vec<ValidationProblem>> {
+        let options = self.options(rx_file);
+        let problems = validate(
+            prescription,
+            doc_file,
+            doc_text,
+            options.allow_extra_content,
+        )?;
+
+        Ok(options.apply(problems))
+    }
+
+    /// Returns the part of the pharmacy whose entries have any of `tags`.
+    pub fn tagged(&self, tags: &[&str]) -> Pharmacy {
+        let is_tagged = |rx_file: &PathBuf| {
+            self.options(rx_file)
+                .tags
+                .iter()
+                .any(|tag| tags.contains(&tag.as_str()))
+        };
+
+        Pharmacy {
+            spec_to_targets: self.spec_to_targets
+                .iter()
+                .filter(|&(rx_file, _)| is_tagged(rx_file))
+                .map(|(rx_file, targets)| (rx_file.clone(), targets.clone()))
+                .collect(),
+            spec_options: self.spec_options
+                .iter()
+                .filter(|&(rx_file, _)| is_tagged(rx_file))
+                .map(|(rx_file, options)| (rx_file.clone(), options.clone()))
+                .collect(),
+            sources: self.sources.clone(),
+        }
+    }
+
+    /// Returns the prescription and document of every pair in the pharmacy, in the order they are
+    /// validated.
+    pub fn pairs(&self) -> Vec<(PathBuf, PathBuf)> {
+        self.spec_to_targets
+            .iter()
+            .flat_map(|(rx_file, target_docs)| {
+                target_docs
+                    .iter()
+                    .map(move |doc_file| (rx_file.clone(), doc_file.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Checks the pharmacy file `filename` against the schema of pharmacy files, and checks that every
+/// prescription and document it names exists.
+///
+/// Returns every problem found. An error is only returned if a file cannot be read or is not TOML.
+pub fn lint<P: AsRef<Path>>(filename: P) -> HowserResult<Vec<PharmacyWarning>> {
+    let mut reader = Reader::default();
+    reader.read_file(filename.as_ref())?;
+
+    for &(ref path, ref location) in reader.references.iter() {
+        if !path.exists() {
+            reader.problems.push(PharmacyWarning::new(
+                &location.file,
+                location.span,
+                ErrorCode::MissingPharmacyFile,
+                format!("{} does not exist.", path.display()),
+            ));
+        }
+    }
+
+    Ok(reader.problems)
+}
+
+/// Validates the document `doc_file`, whose text is `doc_text`, against `prescription`.
+///
+/// Of the options of an entry, only `allow_extra_content` is honored, so problems are reported as
+/// the validator found them. `Pharmacy::validate_document` applies the rest.
+pub fn validate(
+    prescription: Prescription,
+    doc_file: &Path,
+    doc_text: &str,
+    allow_extra_content: bool,
+) -> HowserResult<Vec<ValidationProblem>> {
+    let document = Document::from_text(doc_text, Some(doc_file.to_string_lossy().to_string()))?;
+
+    Validator::new(prescription, document)
+        .with_extra_content_allowed(allow_extra_content)
+        .validate()
+}
+
+/// Reads pharmacies against their schema, gathering every problem rather than stopping at the
+/// first.
+#[derive(Default)]
+struct Reader {
+    spec_to_targets: BTreeMap<PathBuf, Vec<PathBuf>>,
+    spec_options: BTreeMap<PathBuf, SpecOptions>,
+    /// Every prescription and document named, with where it was named.
+    references: Vec<(PathBuf, Location)>,
+    sources: Vec<PathBuf>,
+    problems: Vec<PharmacyWarning>,
+}
+
+impl Reader {
+    fn read_file(&mut self, path: &Path) -> HowserResult<()> {
+        match path.file_name().map_or(false, |name| name == CARGO_MANIFEST) {
+            true => self.read_cargo_manifest(path),
+            false => {
+                self.sources.push(path.to_path_buf());
+                self.read_pharmacy(&path.to_string_lossy(), &read_file(path)?)
+            }
+        }
+    }
+
+    fn read_pharmacy(&mut self, filename: &str, text: &str) -> HowserResult<()> {
+        let pharmacy = text.parse::<Value>()?;
+        self.read_table(&Source::new(filename, text), &[], &pharmacy, Path::new(""));
+        Ok(())
+    }
+
+    fn read_cargo_manifest(&mut self, manifest: &Path) -> HowserResult<()> {
+        let directory = manifest.parent().unwrap_or(Path::new(""));
+        let filename = manifest.to_string_lossy().to_string();
+        let text = read_file(manifest)?;
+        self.sources.push(manifest.to_path_buf());
+        let contents = text.parse::<Value>()?;
+        let source = Source::new(&filename, &text);
+
+        let mut has_metadata = false;
+        for section in &["package", "workspace"] {
+            has_metadata |= self.read_metadata(&source, &contents, section, directory);
+        }
+
+        let members = match contents.get("workspace") {
+            Some(workspace) => self.workspace_members(&source, workspace, directory)?,
+            None => Vec::new(),
+        };
+        for member in members {
+            let member_manifest = member.join(CARGO_MANIFEST);
+            let member_name = member_manifest.to_string_lossy().to_string();
+            let member_text = read_file(&member_manifest)?;
+            self.sources.push(member_manifest.clone());
+            let member_contents = member_text.parse::<Value>()?;
+            let member_source = Source::new(&member_name, &member_text);
+            has_metadata |= self.read_metadata(&member_source, &member_contents, "package", &member);
+        }
+
+        if !has_metadata {
+            self.problems.push(PharmacyWarning::new(
+                &filename,
+                Span::new(1, 0, 1, 0),
+                ErrorCode::InvalidPharmacyValue,
+                "There is no [package.metadata.howser.Specs] or [workspace.metadata.howser.Specs] \
+                 table in the manifest or its workspace members."
+                    .to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Reads the `howser` metadata of a section of a Cargo manifest, such as
+    /// `[package.metadata.howser]`, returning whether there is any.
+    fn read_metadata(
+        &mut self,
+        source: &Source,
+        manifest: &Value,
+        section: &str,
+        directory: &Path,
+    ) -> bool {
+        let howser = manifest
+            .get(section)
+            .and_then(|section| section.get("metadata"))
+            .and_then(|metadata| metadata.get("howser"));
+
+        match howser {
+            Some(howser) => {
+                self.read_table(source, &[section, "metadata", "howser"], howser, directory);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reads a table holding a `Specs` table, which is either a whole pharmacy file or the `howser`
+    /// metadata of a Cargo manifest. `path` holds the keys of the table within its file, and paths
+    /// in the table are taken to be relative to `directory`.
+    fn read_table(&mut self, source: &Source, path: &[&str], table: &Value, directory: &Path) {
+        let table = match table.as_table() {
+            Some(table) => table,
+            None => return self.expect_table(source, path, table),
+        };
+
+        for key in table.keys() {
+            if !PHARMACY_KEYS.contains(&key.as_str()) {
+                self.unknown_key(source, path, key, &PHARMACY_KEYS);
+            }
+        }
+
+        let mut specs_path = path.to_vec();
+        specs_path.push("Specs");
+        match table.get("Specs") {
+            Some(specs) => self.read_specs(source, &specs_path, specs, directory),
+            None => self.problems.push(PharmacyWarning::new(
+                source.filename,
+                source.header_span(path),
+                ErrorCode::InvalidPharmacyValue,
+                format!("There is no [{}] table.", specs_path.join(".")),
+            )),
+        }
+    }
+
+    /// Reads a `Specs` table, whose keys name prescriptions and whose values name the documents to
+    /// validate against them.
+    fn read_specs(&mut self, source: &Source, path: &[&str], specs: &Value, directory: &Path) {
+        let specs = match specs.as_table() {
+            Some(specs) => specs,
+            None => return self.expect_table(source, path, specs),
+        };
+
+        for (key, value) in specs {
+            let rx_file = relative_to(directory, Path::new(key));
+            self.references
+                .push((rx_file.clone(), source.location(source.key_span(path, key))));
+
+            let mut entry_path = path.to_vec();
+            entry_path.push(key);
+            // Documents are located by the key that names them.
+            let (targets, documents_path, documents_key) = match *value {
+                Value::Table(ref entry) => match self.read_entry(source, &entry_path, entry) {
+                    Some((targets, options)) => {
+                        if self.spec_options.contains_key(&rx_file) {
+                            self.problems.push(PharmacyWarning::new(
+                                source.filename,
+                                source.key_span(path, key),
+                                ErrorCode::InvalidPharmacyValue,
+                                format!(
+                                    "The options of '{}' are already set by another entry.",
+                                    rx_file.display()
+                                ),
+                            ));
+                        } else {
+                            self.spec_options.insert(rx_file.clone(), options);
+                        }
+                        (targets, &entry_path[..], "documents")
+                    }
+                    None => continue,
+                },
+                _ => match string_array(value) {
+                    Some(targets) => (targets, path, key.as_str()),
+                    None => {
+                        self.problems.push(PharmacyWarning::new(
+                            source.filename,
+                            source.value_span(path, key),
+                            ErrorCode::InvalidPharmacyValue,
+                            format!(
+                                "The documents of '{}' must be a string, an array of strings or a \
+                                 table, not {}.",
+                                key,
+                                describe_type(value)
+                            ),
+                        ));
+                        continue;
+                    }
+                },
+            };
+
+            let known_targets = self.spec_to_targets.entry(rx_file).or_insert(Vec::new());
+            for target in targets {
+                let span = source.string_span(documents_path, documents_key, target);
+                let target = relative_to(directory, Path::new(target));
+                self.references
+                    .push((target.clone(), source.location(span)));
+                if !known_targets.contains(&target) {
+                    known_targets.push(target);
+                }
+            }
+        }
+    }
+
+    /// Reads the table form of an entry, which names its documents along with options for them.
+    ///
+    /// Returns `None` if the entry has problems.
+    fn read_entry<'v>(
+        &mut self,
+        source: &Source,
+        path: &[&str],
+        entry: &'v Table,
+    ) -> Option<(Vec<&'v str>, SpecOptions)> {
+        let mut options = SpecOptions::default();
+        let mut documents = None;
+        let mut is_valid = true;
+
+        for (key, value) in entry {
+            let expected = match key.as_str() {
+                "documents" => string_array(value)
+                    .map(|found| documents = Some(found))
+                    .ok_or("a string or an array of strings"),
+                "severity" => match value.as_str() {
+                    Some("error") => {
+                        options.severity = Some(Severity::Error);
+                        Ok(())
+                    }
+                    Some("warning") => {
+                        options.severity = Some(Severity::Warning);
+                        Ok(())
+                    }
+                    _ => Err("'error' or 'warning'"),
+                },
+                "allow-extra-content" => value
+                    .as_bool()
+                    .map(|allow| options.allow_extra_content = allow)
+                    .ok_or("a boolean"),
+                "fail-early" => value
+                    .as_bool()
+                    .map(|fail_early| options.fail_early = fail_early)
+                    .ok_or("a boolean"),
+                "tags" => string_array(value)
+                    .map(|tags| options.tags = tags.iter().map(|tag| tag.to_string()).collect())
+                    .ok_or("a string or an array of strings"),
+                "owner" => value
+                    .as_str()
+                    .map(|owner| options.owner = Some(owner.to_string()))
+                    .ok_or("a string"),
+                _ => {
+                    is_valid = false;
+                    self.unknown_key(source, path, key, &ENTRY_KEYS);
+                    continue;
+                }
+            };
+
+            if let Err(expected) = expected {
+                is_valid = false;
+                let found = match *value {
+                    Value::String(ref found) => format!("'{}'", found),
+                    _ => describe_type(value),
+                };
+                self.problems.push(PharmacyWarning::new(
+                    source.filename,
+                    source.value_span(path, key),
+                    ErrorCode::InvalidPharmacyValue,
+                    format!("'{}' must be {}, not {}.", key, expected, found),
+                ));
+            }
+        }
+
+        if documents.is_none() {
+            is_valid = false;
+            self.problems.push(PharmacyWarning::new(
+                source.filename,
+                source.header_span(path),
+                ErrorCode::InvalidPharmacyValue,
+                format!("The entry of '{}' names no documents.", path[path.len() - 1]),
+            ));
+        }
+
+        match is_valid {
+            true => documents.map(|documents| (documents, options)),
+            false => None,
+        }
+    }
+
+    /// Reports a key of the table at `path` that is not one of `known_keys`.
+    fn unknown_key(&mut self, source: &Source, path: &[&str], key: &str, known_keys: &[&str]) {
+        let message = match suggest_key(key, known_keys) {
+            Some(known_key) => format!("Unknown key '{}'. Did you mean '{}'?", key, known_key),
+            None => format!("Unknown key '{}'.", key),
+        };
+        self.problems.push(PharmacyWarning::new(
+            source.filename,
+            source.key_span(path, key),
+            ErrorCode::UnknownPharmacyKey,
+            message,
+        ));
+    }
+
+    /// Reports that the value at `path` is not a table.
+    fn expect_table(&mut self, source: &Source, path: &[&str], value: &Value) {
+        let (table, key) = path.split_at(path.len() - 1);
+        self.problems.push(PharmacyWarning::new(
+            source.filename,
+            source.value_span(table, key[0]),
+            ErrorCode::InvalidPharmacyValue,
+            format!("[{}] must be a table, not {}.", path.join("."), describe_type(value)),
+        ));
+    }
+
+    /// Returns the directories of the members of a workspace, given its `[workspace]` section.
+    ///
+    /// Members may be glob patterns, where `*` matches any part of a single path component.
+    fn workspace_members(
+        &mut self,
+        source: &Source,
+        workspace: &Value,
+        directory: &Path,
+    ) -> HowserResult<Vec<PathBuf>> {
+        let mut excluded = Vec::new();
+        for pattern in self.workspace_patterns(source, workspace, "exclude") {
+            excluded.push(directory.join(pattern));
+        }
+        let mut members = Vec::new();
+        for pattern in self.workspace_patterns(source, workspace, "members") {
+            for member in expand_pattern(directory, pattern)? {
+                let is_excluded = excluded.iter().any(|excluded| member.starts_with(excluded));
+                if !is_excluded && member.join(CARGO_MANIFEST).is_file() && !members.contains(&member)
+                {
+                    members.push(member);
+                }
+            }
+        }
+
+        Ok(members)
+    }
+
+    /// Returns the patterns of a list of paths in a `[workspace]` section, such as `members`.
+    fn workspace_patterns<'a>(
+        &mut self,
+        source: &Source,
+        workspace: &'a Value,
+        key: &str,
+    ) -> Vec<&'a str> {
+        let value = match workspace.get(key) {
+            Some(value) => value,
+            None => return Vec::new(),
+        };
+
+        string_array(value).unwrap_or_else(|| {
+            self.problems.push(PharmacyWarning::new(
+                source.filename,
+                source.value_span(&["workspace"], key),
+                ErrorCode::InvalidPharmacyValue,
+                format!(
+                    "Workspace {} must be an array of strings, not {}.",
+                    key,
+                    describe_type(value)
+                ),
+            ));
+            Vec::new()
+        })
+    }
+
+    /// Returns the pharmacy that was read, or an error listing every problem found.
+    fn finish(self) -> HowserResult<Pharmacy> {
+        match self.problems.is_empty() {
+            true => Ok(Pharmacy {
+                spec_to_targets: self.spec_to_targets,
+                spec_options: self.spec_options,
+                sources: self.sources,
+            }),
+            false => Err(HowserError::RuntimeError(
+                self.problems
+                    .iter()
+                    .map(|problem| problem.to_string())
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            )),
+        }
+    }
+}
+
+/// Returns the strings of a value that is a string or an array of strings.
+fn string_array(value: &Value) -> Option<Vec<&str>> {
+    match *value {
+        Value::String(ref s) => Some(vec![s.as_str()]),
+        Value::Array(ref array) => array.iter().map(|item| item.as_str()).collect(),
+        _ => None,
+    }
+}
+
+/// Describes the type of a value for messages, such as "an integer" or "an array of integers".
+fn describe_type(value: &Value) -> String {
+    let type_name = match *value {
+        Value::Array(ref array) => match array.iter().find(|item| !item.is_str()) {
+            Some(item) => return format!("an array containing {}", describe_type(item)),
+            None => "array",
+        },
+        _ => value.type_str(),
+    };
+
+    match type_name.starts_with(|c| "aeiou".contains(c)) {
+        true => format!("an {}", type_name),
+        false => format!("a {}", type_name),
+    }
+}
+
+/// Returns the known key that `key` is most likely a misspelling of, if it is close to one.
+fn suggest_key<'k>(key: &str, known_keys: &[&'k str]) -> Option<&'k str> {
+    known_keys
+        .iter()
+        .map(|known_key| {
+            let distance = fuzzy::edit_distance(&key.to_lowercase(), &known_key.to_lowercase());
+            (distance, *known_key)
+        })
+        .filter(|&(distance, known_key)| distance <= known_key.len() / 2)
+        .min()
+        .map(|(_, known_key)| known_key)
+}
+
+/// The text of a TOML file, for finding where keys and values were written.
+///
+/// Keys are found by scanning lines for table headers and `key = value` pairs, which covers the
+/// way pharmacies are written. Spans fall back to the nearest enclosing table when a key cannot be
+/// found, such as when it was written as a dotted or inline key.
+struct Source<'a> {
+    filename: &'a str,
+    lines: Vec<&'a str>,
+}
+
+/// A `key = value` pair found on a line, as byte offsets into the line.
+struct KeyLine {
+    line: usize,
+    key_start: usize,
+    key_end: usize,
+    value_start: usize,
+}
+
+impl<'a> Source<'a> {
+    fn new(filename: &'a str, text: &'a str) -> Self {
+        Source {
+            filename,
+            lines: text.lines().collect(),
+        }
+    }
+
+    fn location(&self, span: Span) -> Location {
+        Location {
+            file: self.filename.to_string(),
+            span,
+        }
+    }
+
+    /// Returns the span of the header of the table at `path`. Tables written inline are located by
+    /// their key, and the first line of the file stands for the top level table.
+    fn header_span(&self, path: &[&str]) -> Span {
+        match path.split_last() {
+            Some((key, table)) => self.key_span(table, key),
+            None => Span::new(1, 0, 1, 0),
+        }
+    }
+
+    /// Returns the span of `key` in the table at `path`, which is either written as a `key =
+    /// value` pair or as the header of a table of its own. Keys that cannot be found are located
+    /// at the table that holds them.
+    fn key_span(&self, path: &[&str], key: &str) -> Span {
+        if let Some(found) = self.find_key(path, key) {
+            return self.span(found.line, found.key_start, found.key_end);
+        }
+
+        let mut key_path = path.to_vec();
+        key_path.push(key);
+        match self.find_header(&key_path) {
+            Some(span) => span,
+            None => self.header_span(path),
+        }
+    }
+
+    /// Returns the span of the value of `key` in the table at `path`, up to the end of its line.
+    fn value_span(&self, path: &[&str], key: &str) -> Span {
+        match self.find_key(path, key) {
+            Some(found) => {
+                let line = self.lines[found.line];
+                self.span(found.line, found.value_start, line.trim_right().len())
+            }
+            None => self.key_span(path, key),
+        }
+    }
+
+    /// Returns the span of the string literal `value` written in the value of `key` in the table
+    /// at `path`, which may be an array spanning several lines.
+    fn string_span(&self, path: &[&str], key: &str, value: &str) -> Span {
+        let found = match self.find_key(path, key) {
+            Some(found) => found,
+            None => return self.key_span(path, key),
+        };
+
+        let literals = [format!("\"{}\"", value), format!("'{}'", value)];
+        for (index, line) in self.lines.iter().enumerate().skip(found.line) {
+            let searched = match index == found.line {
+                true => found.value_start,
+                false => 0,
+            };
+            for literal in literals.iter() {
+                if let Some(start) = line[searched..].find(literal.as_str()) {
+                    let start = searched + start;
+                    return self.span(index, start, start + literal.len());
+                }
+            }
+        }
+
+        self.value_span(path, key)
+    }
+
+    /// Finds the header of the table at `path`.
+    fn find_header(&self, path: &[&str]) -> Option<Span> {
+        for (index, line) in self.lines.iter().enumerate() {
+            let trimmed = line.trim();
+            let header = parse_header(trimmed);
+            if trimmed.starts_with('[') && header.iter().map(|key| key.as_str()).eq(path.iter().cloned()) {
+                let start = line.len() - line.trim_left().len();
+                return Some(self.span(index, start, start + trimmed.len()));
+            }
+        }
+
+        None
+    }
+
+    /// Finds the line on which `key` is given a value in the table at `path`.
+    fn find_key(&self, path: &[&str], key: &str) -> Option<KeyLine> {
+        let mut in_table = path.is_empty();
+        for (index, line) in self.lines.iter().enumerate() {
+            let trimmed = line.trim_left();
+            if trimmed.starts_with('[') {
+                let header = parse_header(trimmed);
+                in_table = header.iter().map(|key| key.as_str()).eq(path.iter().cloned());
+                continue;
+            }
+            if !in_table {
+                continue;
+            }
+
+            let key_start = line.len() - trimmed.len();
+            let (found_key, key_length) = match parse_key(trimmed) {
+                Some(parsed) => parsed,
+                None => continue,
+            };
+            let after_key = &trimmed[key_length..];
+            let after_equals = after_key.trim_left();
+            if found_key != key || !after_equals.starts_with('=') {
+                continue;
+            }
+            let value = after_equals[1..].trim_left();
+
+            return Some(KeyLine {
+                line: index,
+                key_start,
+                key_end: key_start + key_length,
+                value_start: line.len() - value.len(),
+            });
+        }
+
+        None
+    }
+
+    /// Returns the span of a line between two byte offsets, in one based columns.
+    fn span(&self, index: usize, start: usize, end: usize) -> Span {
+        let line = self.lines[index];
+        let start_column = line[..start].chars().count() + 1;
+        let end_column = start_column + line[start..end].chars().count().max(1) - 1;
+        Span::new(index + 1, start_column, index + 1, end_column)
+    }
+}
+
+/// Returns the keys of a table header such as `[package.metadata.howser]`.
+fn parse_header(header: &str) -> Vec<String> {
+    let mut rest = header.trim_left_matches('[');
+    let mut keys = Vec::new();
+    while let Some((key, length)) = parse_key(rest.trim_left()) {
+        keys.push(key);
+        rest = rest.trim_left()[length..].trim_left();
+        match rest.starts_with('.') {
+            true => rest = &rest[1..],
+            false => break,
+        }
+    }
+
+    keys
+}
+
+/// Parses a bare or quoted key from the start of `text`, returning the key and the number of bytes
+/// it was written with.
+fn parse_key(text: &str) -> Option<(String, usize)> {
+    let quote = match text.chars().next() {
+        Some(quote @ '"') | Some(quote @ '\'') => quote,
+        _ => {
+            let length = text
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+                .unwrap_or(text.len());
+            return match length {
+                0 => None,
+                _ => Some((text[..length].to_string(), length)),
+            };
+        }
+    };
+
+    let mut key = String::new();
+    let mut escaped = false;
+    for (index, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => {
+                key.push(c);
+                escaped = false;
+            }
+            '\\' if quote == '"' => escaped = true,
+            _ if c == quote => return Some((key, index + 1)),
+            _ => key.push(c),
+        }
+    }
+
+    None
+}
+
+/// Returns the paths below `directory` that match a pattern in which `*` matches any part of a
+/// single path component.
+fn expand_pattern(directory: &Path, pattern: &str) -> HowserResult<Vec<PathBuf>> {
+    let mut paths = vec![directory.to_path_buf()];
+    for component in pattern.split('/').filter(|component| !component.is_empty()) {
+        if !component.contains('*') {
+            paths = paths.iter().map(|path| path.join(component)).collect();
+            continue;
+        }
+
+        let mut expanded = Vec::new();
+        for path in paths {
+            // An empty path stands for the working directory.
+            let listed = match path.as_os_str().is_empty() {
+                true => Path::new("."),
+                false => path.as_path(),
+            };
+            if !listed.is_dir() {
+                continue;
+            }
+            let mut names = Vec::new();
+            for entry in fs::read_dir(listed)? {
+                let name = entry?.file_name().to_string_lossy().to_string();
+                if matches_wildcard(component, &name) {
+                    names.push(name);
+                }
+            }
+            names.sort();
+            expanded.extend(names.into_iter().map(|name| path.join(name)));
+        }
+        paths = expanded;
+    }
+
+    Ok(paths)
+}
+
+/// Determines if `name` matches `pattern`, in which `*` matches any sequence of characters.
+fn matches_wildcard(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    if !name.starts_with(first) {
+        return false;
+    }
+
+    let mut rest = &name[first.len()..];
+    let parts: Vec<&str> = parts.collect();
+    for (index, part) in parts.iter().enumerate() {
+        if index == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(found) => rest = &rest[found + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.is_empty()
+}
+
+/// Returns `path` taken relative to `directory`, without any `.` or `..` components that can be
+/// resolved without reading the file system.
+fn relative_to(directory: &Path, path: &Path) -> PathBuf {
+    if directory.as_os_str().is_empty() {
+        return path.to_path_buf();
+    }
+
+    let mut resolved = PathBuf::new();
+    for component in directory.join(path).components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => match resolved.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    resolved.pop();
+                }
+                _ => resolved.push(".."),
+            },
+            _ => resolved.push(component.as_os_str()),
+        }
+    }
+
+    resolved
+}
+
+fn read_file(path: &Path) -> HowserResult<String> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempfile;
+
+    use super::{lint, matches_wildcard, Pharmacy, SpecOptions};
+    use document::{Document, Prescription};
+    use errors::{ErrorCode, Severity};
+    use validator::Validator;
+    use errors::HowserError;
+    use std::collections::BTreeMap;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    #[test]
+    fn parse_pharmacy_allows_empty_table() {
+        assert_eq!(
+            Pharmacy {
+                spec_to_targets: BTreeMap::new(),
+                spec_options: BTreeMap::new(),
+                sources: Vec::new(),
+            },
+            Pharmacy::from_toml("test_file", r#"[Specs]"#)
+                .expect("Should have been able to parse")
+        );
+    }
+
+    #[test]
+    fn parse_pharmacy_supports_string_values() {
+        let mut m = BTreeMap::new();
+        m.insert(PathBuf::from("README.rx"), vec![PathBuf::from("README.md")]);
+        assert_eq!(
+            Pharmacy {
+                spec_to_targets: m,
+                spec_options: BTreeMap::new(),
+                sources: Vec::new(),
+            },
+            Pharmacy::from_toml(
+                "test_file",
+                r#"[Specs]
+                   "README.rx" = "README.md""#
+            ).expect("Should have been able to parse")
+        );
+    }
+
+    #[test]
+    fn parse_pharmacy_supports_array_of_string_values() {
+        let mut m = BTreeMap::new();
+        m.insert(
+            PathBuf::from("README.rx"),
+            vec![
+                PathBuf::from("README.md"),
+                PathBuf::from("subdir/README.md"),
+            ],
+        );
+        assert_eq!(
+            Pharmacy {
+                spec_to_targets: m,
+                spec_options: BTreeMap::new(),
+                sources: Vec::new(),
+            },
+            Pharmacy::from_toml(
+                "test_file",
+                r#"[Specs]
+                   "README.rx" = ["README.md", "subdir/README.md"]"#
+            ).expect("Should have been able to parse")
+        );
+    }
+
+    #[test]
+    fn parse_pharmacy_disallows_duplicate_keys() {
+        let result = Pharmacy::from_toml(
+            "test_file",
+            r#"[Specs]
+            "README.rx" = "README.md"
+            "README.rx" = "subdir/README.md""#,
+        );
+        match result {
+            Err(HowserError::TomlError(_)) => println!("As expected"),
+            x @ _ => panic!("Unexpected success or kind of error: {:?}", x),
+        }
+    }
+
+    /// Returns the message of the error from parsing `text`.
+    fn parse_error(text: &str) -> String {
+        match Pharmacy::from_toml("test_file", text) {
+            Err(HowserError::RuntimeError(message)) => message,
+            x @ _ => panic!("Unexpected success or kind of error: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn test_missing_specs_are_reported() {
+        assert_eq!(
+            parse_error("[Other]\n"),
+            "test_file line 1, columns 1-7 :: Unknown key 'Other'.\n\
+             test_file line 1 :: There is no [Specs] table."
+        );
+        assert_eq!(
+            parse_error("Specs = \"README.md\"\n"),
+            "test_file line 1, columns 9-19 :: [Specs] must be a table, not a string."
+        );
+    }
+
+    #[test]
+    fn test_misspelled_keys_are_suggested() {
+        assert_eq!(
+            parse_error("[Specs]\n\n[spec]\n\"README.rx\" = \"README.md\"\n"),
+            "test_file line 3, columns 1-6 :: Unknown key 'spec'. Did you mean 'Specs'?"
+        );
+    }
+
+    #[test]
+    fn test_wrong_types_are_located() {
+        assert_eq!(
+            parse_error("[Specs]\n\"a.rx\" = \"a.md\"\n  \"b.rx\" = [1, 2]\n"),
+            "test_file line 3, columns 12-17 :: The documents of 'b.rx' must be a string, an \
+             array of strings or a table, not an array containing an integer."
+        );
+    }
+
+    #[test]
+    fn test_lint_reports_missing_files() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = directory.path().display().to_string();
+        File::create(directory.path().join("README.md")).unwrap();
+        let pharmacy = directory.path().join("Pharmacy.toml");
+        File::create(&pharmacy)
+            .unwrap()
+            .write_all(
+                format!(
+                    "[Specs]\n\"{0}/README.rx\" = [\n    \"{0}/README.md\",\n    \"{0}/a.md\",\n]\n",
+                    root
+                ).as_bytes(),
+            )
+            .unwrap();
+
+        let problems: Vec<String> = lint(&pharmacy)
+            .unwrap()
+            .iter()
+            .map(|problem| problem.to_string().replace(&root, ""))
+            .collect();
+        let filename = pharmacy.display().to_string().replace(&root, "");
+        assert_eq!(
+            problems,
+            vec![
+                format!(
+                    "{} line 2, columns 1-{} :: /README.rx does not exist.",
+                    filename,
+                    root.len() + 12
+                ),
+                format!(
+                    "{} line 4, columns 5-{} :: /a.md does not exist.",
+                    filename,
+                    root.len() + 11
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entries_can_set_options() {
+        let pharmacy = Pharmacy::from_toml(
+            "test_file",
+            r#"[Specs]
+               "README.rx" = "README.md"
+               "api.rx" = { documents = "api.md", tags = "api" }
+
+               [Specs."guide.rx"]
+               documents = ["install.md", "usage.md"]
+               severity = "warning"
+               allow-extra-content = true
+               fail-early = true
+               tags = ["guides", "api"]
+               owner = "docs-team""#,
+        ).unwrap();
+
+        assert_eq!(
+            pharmacy.pairs(),
+            vec![
+                (PathBuf::from("README.rx"), PathBuf::from("README.md")),
+                (PathBuf::from("api.rx"), PathBuf::from("api.md")),
+                (PathBuf::from("guide.rx"), PathBuf::from("install.md")),
+                (PathBuf::from("guide.rx"), PathBuf::from("usage.md")),
+            ]
+        );
+        assert_eq!(pharmacy.options(&PathBuf::from("README.rx")), SpecOptions::default());
+        assert_eq!(
+            pharmacy.options(&PathBuf::from("guide.rx")),
+            SpecOptions {
+                severity: Some(Severity::Warning),
+                allow_extra_content: true,
+                fail_early: true,
+                tags: vec!["guides".to_string(), "api".to_string()],
+                owner: Some("docs-team".to_string()),
+            }
+        );
+
+        let tagged = pharmacy.tagged(&["guides"]);
+        assert_eq!(
+            tagged.spec_to_targets.keys().collect::<Vec<&PathBuf>>(),
+            vec![&PathBuf::from("guide.rx")]
+        );
+        assert_eq!(pharmacy.tagged(&["api"]).pairs().len(), 3);
+    }
+
+    #[test]
+    fn test_entry_options_are_checked() {
+        assert_eq!(
+            parse_error(
+                "[Specs.\"guide.rx\"]\ndocuments = \"guide.md\"\nseverity = \"fatal\"\nfail_early = true\n"
+            ),
+            "test_file line 4, columns 1-10 :: Unknown key 'fail_early'. Did you mean 'fail-early'?\n\
+             test_file line 3, columns 12-18 :: 'severity' must be 'error' or 'warning', not 'fatal'."
+        );
+        assert_eq!(
+            parse_error("[Specs]\n\"guide.rx\" = { owner = \"docs-team\" }\n"),
+            "test_file line 2, columns 1-10 :: The entry of 'guide.rx' names no documents."
+        );
+    }
+
+    #[test]
+    fn test_options_change_how_problems_are_reported() {
+        let prescription = Prescription::from_text("# -!!-\n", None).unwrap();
+        let document = Document::from_text("# Howser\n\nExtra.\n", Some("a.md".to_string()));
+        let problems = Validator::new(prescription, document.unwrap())
+            .validate()
+            .unwrap();
+        assert_eq!(problems[0].severity(), Severity::Error);
+
+        let options = SpecOptions {
+            severity: Some(Severity::Warning),
+            owner: Some("docs-team".to_string()),
+            ..SpecOptions::default()
+        };
+        let problems = options.apply(problems);
+        assert_eq!(problems[0].severity(), Severity::Warning);
+        assert_eq!(problems[0].code(), ErrorCode::SuperfluousBlockContent);
+        assert!(
+            problems[0]
+                .short_msg()
+                .ends_with(":: reported as a warning, owned by docs-team")
+        );
+    }
+
+    #[test]
+    fn test_pairs_follow_the_order_of_prescriptions() {
+        let pharmacy = Pharmacy::from_toml(
+            "test_file",
+            r#"[Specs]
+               "b.rx" = ["b.md", "a.md"]
+               "a.rx" = "c.md""#,
+        ).unwrap();
+        assert_eq!(
+            pharmacy.pairs(),
+            vec![
+                (PathBuf::from("a.rx"), PathBuf::from("c.md")),
+                (PathBuf::from("b.rx"), PathBuf::from("b.md")),
+                (PathBuf::from("b.rx"), PathBuf::from("a.md")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cargo_workspaces_gather_the_pharmacies_of_members() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = directory.path();
+        let write = |name: &str, text: &str| {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            File::create(path).unwrap().write_all(text.as_bytes()).unwrap()
+        };
+        write(
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\", \"tool\"]\nexclude = [\"crates/skipped\"]\n\n\
+             [workspace.metadata.howser.Specs]\n\"docs/guide.rx\" = \"docs/guide.md\"\n",
+        );
+        write(
+            "crates/first/Cargo.toml",
+            "[package]\nname = \"first\"\n\n\
+             [package.metadata.howser.Specs]\n\"README.rx\" = [\"README.md\", \"docs/usage.md\"]\n",
+        );
+        write("crates/second/Cargo.toml", "[package]\nname = \"second\"\n");
+        write(
+            "crates/skipped/Cargo.toml",
+            "[package.metadata.howser.Specs]\n\"README.rx\" = \"README.md\"\n",
+        );
+        write(
+            "tool/Cargo.toml",
+            "[package.metadata.howser.Specs]\n\"../docs/guide.rx\" = \"README.md\"\n",
+        );
+
+        let pharmacy = Pharmacy::from_file(root.join("Cargo.toml")).unwrap();
+        assert_eq!(
+            pharmacy.pairs(),
+            vec![
+                (
+                    root.join("crates/first/README.rx"),
+                    root.join("crates/first/README.md"),
+                ),
+                (
+                    root.join("crates/first/README.rx"),
+                    root.join("crates/first/docs/usage.md"),
+                ),
+                (root.join("docs/guide.rx"), root.join("docs/guide.md")),
+                (root.join("docs/guide.rx"), root.join("tool/README.md")),
+            ]
+        );
+
+        assert_eq!(
+            pharmacy.sources,
+            vec![
+                root.join("Cargo.toml"),
+                root.join("crates/first/Cargo.toml"),
+                root.join("crates/second/Cargo.toml"),
+                root.join("tool/Cargo.toml"),
+            ]
+        );
+
+        assert!(Pharmacy::from_file(root.join("crates/second/Cargo.toml")).is_err());
+    }
+
+    #[test]
+    fn test_options_are_set_by_one_entry() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = directory.path();
+        let write = |name: &str, text: &str| {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            File::create(path).unwrap().write_all(text.as_bytes()).unwrap()
+        };
+        write(
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"tool\"]\n\n\
+             [workspace.metadata.howser.Specs]\n\
+             \"docs/guide.rx\" = { documents = \"docs/guide.md\", severity = \"warning\" }\n",
+        );
+        write(
+            "tool/Cargo.toml",
+            "[package.metadata.howser.Specs]\n\
+             \"../docs/guide.rx\" = { documents = \"README.md\", owner = \"tools\" }\n",
+        );
+
+        match Pharmacy::from_file(root.join("Cargo.toml")) {
+            Err(HowserError::RuntimeError(message)) => assert_eq!(
+                message,
+                format!(
+                    "{} line 2, columns 1-18 :: The options of '{}' are already set by another \
+                     entry.",
+                    root.join("tool/Cargo.toml").display(),
+                    root.join("docs/guide.rx").display()
+                )
+            ),
+            x @ _ => panic!("Unexpected success or kind of error: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn test_wildcards_match_any_part_of_a_name() {
+        assert!(matches_wildcard("*", "crate"));
+        assert!(matches_wildcard("howser-*", "howser-cli"));
+        assert!(matches_wildcard("*-cli", "howser-cli"));
+        assert!(matches_wildcard("h*r*i", "howser-cli"));
+        assert!(matches_wildcard("crate", "crate"));
+        assert!(!matches_wildcard("crate", "crates"));
+        assert!(!matches_wildcard("howser-*", "cli-howser"));
+        assert!(!matches_wildcard("a*a", "a"));
+    }
+}
@@ -1,9 +1,19 @@
 //! Formatters for reporting validation results.
 
+extern crate serde_json;
 extern crate termion;
 
+use self::serde_json::{Map, Value};
 use self::termion::color;
-use errors::ValidationProblem;
+use ast::Node;
+use data::{Alignment, AlignmentStep};
+use document::{Document, Prescription};
+use errors::{Severity, ValidationProblem};
+
+/// Width of the prescription column of an alignment report.
+const ALIGNMENT_RX_WIDTH: usize = 52;
+/// Width of the outcome column of an alignment report.
+const ALIGNMENT_OUTCOME_WIDTH: usize = 16;
 
 /// Options for configuring a CLI report.
 pub enum CLIOption {
@@ -11,6 +21,8 @@ pub enum CLIOption {
     SuccessMessage(String),
     /// the message to be displayed for an invalid document
     VerboseMode(bool),
+    /// Whether warnings make a document invalid.
+    DenyWarnings(bool),
 }
 
 /// Returns a textual report of the validation results suitable for display in a CLI environment.
@@ -18,6 +30,7 @@ pub fn make_cli_report(issues: &Vec<ValidationProblem>, config: &Vec<CLIOption>)
     let mut report: Vec<String> = Vec::new();
 
     let mut verbose_mode = false;
+    let mut deny_warnings = false;
     let mut success_message = format!(
         "{}{}{}",
         color::Fg(color::Green),
@@ -29,6 +42,7 @@ pub fn make_cli_report(issues: &Vec<ValidationProblem>, config: &Vec<CLIOption>)
         match option {
             CLIOption::SuccessMessage(message) => success_message = message.to_string(),
             CLIOption::VerboseMode(mode) => verbose_mode = *mode,
+            CLIOption::DenyWarnings(deny) => deny_warnings = *deny,
         }
     }
 
@@ -39,9 +53,231 @@ pub fn make_cli_report(issues: &Vec<ValidationProblem>, config: &Vec<CLIOption>)
         });
     }
 
-    if issues.is_empty() {
+    let warnings = issues
+        .iter()
+        .filter(|issue| issue.severity() == Severity::Warning)
+        .count();
+    let is_valid = match deny_warnings {
+        true => issues.is_empty(),
+        false => warnings == issues.len(),
+    };
+
+    if is_valid {
         report.push(success_message);
     }
+    if warnings > 0 {
+        report.push(format!(
+            "{}{} warning(s){}",
+            color::Fg(color::Yellow),
+            warnings,
+            color::Fg(color::Reset)
+        ));
+    }
 
     report.join("\n\n")
 }
+
+/// Returns a side-by-side view of the alignment steps taken while validating `document` against
+/// `prescription`.
+///
+/// Each line shows a prescription element, indented by its depth, next to the outcome and the
+/// document element it was compared with.
+pub fn make_alignment_report(
+    steps: &Vec<AlignmentStep>,
+    prescription: &Prescription,
+    document: &Document,
+) -> String {
+    let mut report = vec![format!(
+        "{:rx_width$}{:outcome_width$}{}",
+        prescription
+            .document
+            .filename
+            .clone()
+            .unwrap_or("Prescription".to_string()),
+        "",
+        document.filename.clone().unwrap_or("Document".to_string()),
+        rx_width = ALIGNMENT_RX_WIDTH,
+        outcome_width = ALIGNMENT_OUTCOME_WIDTH
+    )];
+
+    for step in steps {
+        let rx = prescription.document.node(step.rx);
+        let indent = vec!["  "; depth(&rx)].concat();
+        let rx_column = truncate(
+            &format!("{}{}", indent, describe(&rx, &prescription.document)),
+            ALIGNMENT_RX_WIDTH - 1,
+        );
+        let outcome = match step.alignment {
+            Alignment::Matched => "matched".to_string(),
+            Alignment::Mismatched(code) => format!("mismatch {}", code),
+            Alignment::Skipped => "skipped".to_string(),
+            Alignment::Repeated(count) => format!("repeated {}x", count),
+        };
+        let node_column = match step.node {
+            Some(id) => describe(&document.node(id), document),
+            None => String::new(),
+        };
+
+        report.push(
+            format!(
+                "{:rx_width$}{:outcome_width$}{}",
+                rx_column,
+                outcome,
+                node_column,
+                rx_width = ALIGNMENT_RX_WIDTH,
+                outcome_width = ALIGNMENT_OUTCOME_WIDTH
+            ).trim_right()
+                .to_string(),
+        );
+    }
+
+    report.join("\n")
+}
+
+/// Returns the alignment steps taken while validating `document` against `prescription` as a
+/// JSON array.
+pub fn make_alignment_json(
+    steps: &Vec<AlignmentStep>,
+    prescription: &Prescription,
+    document: &Document,
+) -> String {
+    let entries: Vec<Value> = steps
+        .iter()
+        .map(|step| {
+            let rx = prescription.document.node(step.rx);
+            let mut entry = Map::new();
+            entry.insert("depth".to_string(), Value::from(depth(&rx)));
+            entry.insert("prescription".to_string(), element_json(&rx));
+            entry.insert(
+                "wildcard".to_string(),
+                Value::from(prescription.document.is_wildcard(&rx)),
+            );
+            entry.insert(
+                "document".to_string(),
+                match step.node {
+                    Some(id) => element_json(&document.node(id)),
+                    None => Value::Null,
+                },
+            );
+            match step.alignment {
+                Alignment::Matched => {
+                    entry.insert("alignment".to_string(), Value::from("matched"));
+                }
+                Alignment::Mismatched(code) => {
+                    entry.insert("alignment".to_string(), Value::from("mismatched"));
+                    entry.insert("code".to_string(), Value::from(code.to_string()));
+                }
+                Alignment::Skipped => {
+                    entry.insert("alignment".to_string(), Value::from("skipped"));
+                }
+                Alignment::Repeated(count) => {
+                    entry.insert("alignment".to_string(), Value::from("repeated"));
+                    entry.insert("count".to_string(), Value::from(count));
+                }
+            }
+            Value::Object(entry)
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&Value::Array(entries)).unwrap_or_default()
+}
+
+/// Describes an element for the text alignment report.
+fn describe(node: &Node, document: &Document) -> String {
+    let text = match (snippet(node), document.is_wildcard(node)) {
+        (ref text, true) if text.is_empty() => "(wildcard)".to_string(),
+        (text, _) => text,
+    };
+
+    format!("{} {} {}", location(node), node.type_string(), text)
+        .trim_right()
+        .to_string()
+}
+
+/// Describes an element for the JSON alignment report, including where it starts and ends.
+fn element_json(node: &Node) -> Value {
+    let span = node.span();
+    let mut element = Map::new();
+    element.insert("type".to_string(), Value::from(node.type_string()));
+    element.insert("line".to_string(), Value::from(span.start_line));
+    element.insert("column".to_string(), Value::from(span.start_column));
+    element.insert("end_line".to_string(), Value::from(span.end_line));
+    element.insert("end_column".to_string(), Value::from(span.end_column));
+    element.insert("text".to_string(), Value::from(snippet(node)));
+    Value::Object(element)
+}
+
+/// Returns the number of container elements enclosing the given element.
+fn depth(node: &Node) -> usize {
+    let mut depth = 0;
+    let mut ancestor = node.parent();
+    while let Some(parent) = ancestor.and_then(|parent| parent.parent()) {
+        depth += 1;
+        ancestor = Some(parent);
+    }
+
+    depth
+}
+
+/// Returns the line and column where the given element starts.
+fn location(node: &Node) -> String {
+    let span = node.span();
+    match span.is_known() {
+        true => format!("{}:{}", span.start_line, span.start_column),
+        false => "?".to_string(),
+    }
+}
+
+/// Returns the first line of the given element rendered as CommonMark.
+fn snippet(node: &Node) -> String {
+    node.render_commonmark()
+        .lines()
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_string()
+}
+
+/// Shortens `text` to at most `width` characters, marking where it was cut.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        let kept: String = text.chars().take(width - 3).collect();
+        format!("{}...", kept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+
+    use self::serde_json::Value;
+    use super::make_alignment_json;
+    use document::{Document, Prescription};
+    use validator::Validator;
+
+    #[test]
+    fn test_alignment_json_locates_whole_elements() {
+        let prescription = Prescription::from_text("# Title\n\nSome text.\n", None).unwrap();
+        let document = Document::from_text("# Title\n\nSome\ntext.\n", None).unwrap();
+        let validator = Validator::new(prescription, document);
+        validator.validate().unwrap();
+
+        let json = make_alignment_json(
+            &validator.alignment(),
+            validator.prescription(),
+            validator.document(),
+        );
+        let steps: Vec<Value> = serde_json::from_str(&json).unwrap();
+        let paragraph = steps
+            .iter()
+            .map(|step| &step["document"])
+            .find(|element| element["type"] == "paragraph")
+            .unwrap();
+        assert_eq!(paragraph["line"], 3);
+        assert_eq!(paragraph["column"], 1);
+        assert_eq!(paragraph["end_line"], 4);
+        assert_eq!(paragraph["end_column"], 5);
+    }
+}
@@ -0,0 +1,199 @@
+//! Generates skeleton Markdown documents from prescriptions.
+
+extern crate regex;
+
+use self::regex::Regex;
+use ast::{Node, NodeId, NodeKind, Span, Tree, ROOT_ID};
+use constants::CONTENT_PROMPT_PATTERN;
+use data::MatchType;
+use document::Prescription;
+use errors::HowserResult;
+
+/// Text that stands in for prompted content.
+pub const PLACEHOLDER: &str = "TODO";
+
+/// Builds a Markdown document that conforms to a `Prescription`.
+///
+/// Literal content is copied and prompts are replaced with placeholders, which include the hint
+/// comment of the prompted element where one was written. Dittoed elements are emitted once.
+/// Optional elements are only emitted when `include_optional` is set, but optional prompts that
+/// share text with literal content are always filled in. Recommended elements are always emitted.
+pub struct Scaffolder<'a> {
+    prescription: &'a Prescription,
+    include_optional: bool,
+}
+
+impl<'a> Scaffolder<'a> {
+    /// Returns a new `Scaffolder` for the given prescription.
+    pub fn new(prescription: &'a Prescription, include_optional: bool) -> Self {
+        Scaffolder {
+            prescription,
+            include_optional,
+        }
+    }
+
+    /// Returns the scaffolded document as CommonMark.
+    pub fn scaffold(&self) -> HowserResult<String> {
+        trace!("scaffold()");
+        let mut tree = Tree::new();
+        self.scaffold_children(&self.prescription.document.root(), &mut tree, ROOT_ID)?;
+
+        let mut markdown = tree.root().render_commonmark();
+        markdown.push('\n');
+        Ok(markdown)
+    }
+
+    /// Returns a single element of the prescription scaffolded as CommonMark.
+    pub fn scaffold_element(&self, rx: &Node) -> HowserResult<String> {
+        trace!("scaffold_element()");
+        let mut tree = Tree::new();
+        self.scaffold_node(rx, &mut tree, ROOT_ID)?;
+
+        Ok(tree.root().render_commonmark())
+    }
+
+    /// Scaffolds the children of `rx` into `parent`.
+    fn scaffold_children(&self, rx: &Node, tree: &mut Tree, parent: NodeId) -> HowserResult<()> {
+        for child in rx.children() {
+            if self.is_included(&child)? {
+                self.scaffold_node(&child, tree, parent)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Determines if an element of the prescription should appear in the scaffold.
+    fn is_included(&self, rx: &Node) -> HowserResult<bool> {
+        Ok(match self.prescription.document.get_match_type(rx)? {
+            MatchType::Repeatable => false,
+            MatchType::Optional => self.include_optional,
+            _ => true,
+        })
+    }
+
+    /// Scaffolds a single element of the prescription and its descendants into `parent`.
+    fn scaffold_node(&self, rx: &Node, tree: &mut Tree, parent: NodeId) -> HowserResult<()> {
+        if self.prescription.document.is_wildcard(rx) {
+            return self.scaffold_wildcard(rx, tree, parent);
+        }
+
+        let kind = match rx.kind() {
+            &NodeKind::Text(ref content) => NodeKind::Text(self.fill_prompts(rx, content, true)?),
+            &NodeKind::Code(ref content) => NodeKind::Code(self.fill_prompts(rx, content, true)?),
+            &NodeKind::Link { ref url, ref title } => NodeKind::Link {
+                url: self.fill_prompts(rx, url, false)?,
+                title: self.fill_prompts(rx, title, false)?,
+            },
+            kind => kind.clone(),
+        };
+        let id = tree.append(parent, kind, Span::default());
+
+        self.scaffold_children(rx, tree, id)
+    }
+
+    /// Scaffolds a wildcard element as an element of the same type holding a placeholder.
+    fn scaffold_wildcard(&self, rx: &Node, tree: &mut Tree, parent: NodeId) -> HowserResult<()> {
+        let placeholder = self.placeholder(rx, true);
+
+        match rx.kind() {
+            &NodeKind::CodeBlock { ref info, .. } => {
+                let code_block = NodeKind::CodeBlock {
+                    info: info.clone(),
+                    literal: format!("{}\n", placeholder),
+                };
+                tree.append(parent, code_block, Span::default());
+            }
+            &NodeKind::Paragraph | &NodeKind::Heading(_) => {
+                let id = tree.append(parent, rx.kind().clone(), Span::default());
+                tree.append(id, NodeKind::Text(placeholder), Span::default());
+            }
+            kind => {
+                let id = tree.append(parent, kind.clone(), Span::default());
+                let paragraph = tree.append(id, NodeKind::Paragraph, Span::default());
+                tree.append(paragraph, NodeKind::Text(placeholder), Span::default());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the prompts in `content` with placeholders.
+    ///
+    /// Hints are left out of content where spaces are not allowed, such as link destinations.
+    fn fill_prompts(&self, rx: &Node, content: &str, with_hint: bool) -> HowserResult<String> {
+        let pattern = Regex::new(CONTENT_PROMPT_PATTERN)?;
+        let placeholder = self.placeholder(rx, with_hint);
+
+        Ok(pattern
+            .replace_all(content, placeholder.as_str())
+            .to_string())
+    }
+
+    /// Returns the placeholder for prompted content of `rx`, using the closest hint comment.
+    fn placeholder(&self, rx: &Node, with_hint: bool) -> String {
+        let mut current = Some(*rx);
+        while let (Some(node), true) = (current, with_hint) {
+            if let Some(comment) = self.prescription.document.get_comment(&node) {
+                return format!("{}: {}", PLACEHOLDER, comment.content());
+            }
+            current = node.parent();
+        }
+
+        PLACEHOLDER.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scaffolder;
+    use document::{Document, Prescription};
+    use validator::Validator;
+
+    const RX: &str = "# -!!-<!-- Project Name -->\n\n\
+                      Copyright -!!- PolySync\n\n\
+                      -!!-\n\n\
+                      -\"\"-\n\n\
+                      ## Dependencies\n\n\
+                      -??-\n\n\
+                      * [-!!-](-!!-)-??-\n\
+                      * -\"\"-\n\n\
+                      ```-!!-<!-- Example -->\n\
+                      ```\n";
+
+    fn scaffold(include_optional: bool) -> String {
+        let prescription = Prescription::from_text(RX, None).unwrap();
+        Scaffolder::new(&prescription, include_optional)
+            .scaffold()
+            .unwrap()
+    }
+
+    fn assert_valid(markdown: &str) {
+        let prescription = Prescription::from_text(RX, None).unwrap();
+        let document = Document::from_text(markdown, None).unwrap();
+        let problems = Validator::new(prescription, document).validate().unwrap();
+        assert!(problems.is_empty(), "Scaffold does not validate:\n{}", markdown);
+    }
+
+    #[test]
+    fn test_scaffold_without_optional_elements() {
+        let markdown = scaffold(false);
+        assert_eq!(
+            markdown,
+            "# TODO: Project Name\n\n\
+             Copyright TODO PolySync\n\n\
+             TODO\n\n\
+             ## Dependencies\n\n\
+             * [TODO](TODO)\n\n\
+             ```\nTODO: Example\n```\n"
+        );
+        assert_valid(&markdown);
+    }
+
+    #[test]
+    fn test_scaffold_with_optional_elements() {
+        let markdown = scaffold(true);
+        assert!(markdown.contains("## Dependencies\n\nTODO\n\n* [TODO](TODO)TODO\n"));
+        assert_valid(&markdown);
+    }
+}
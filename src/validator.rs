@@ -6,41 +6,43 @@ extern crate unicode_segmentation;
 
 use self::regex::Regex;
 use constants::{CONTENT_PROMPT_PATTERN, MANDATORY_PROMPT, OPTIONAL_PROMPT};
+use ast::{Node, NodeId, NodeKind};
 use data::ElementType;
-use data::{ContentMatchPair, MatchType, PromptToken};
+use data::{Alignment, AlignmentStep, ContentMatchPair, MatchType, PromptToken};
 use document::{Document, Prescription};
-use doogie::Node;
 use errors::{
-    DocumentError, HowserError, HowserResult, Reportable, TextualContentError, TypeMismatchError,
-    ValidationProblem,
+    DocumentError, ErrorCode, HowserError, HowserResult, Severity, SuppressionWarning,
+    TextualContentError, TypeMismatchError, ValidationProblem,
 };
+use std::cell::RefCell;
 use std::collections::VecDeque;
+use std::ops::Range;
 
 /// Arguments for validating mandatory block level elements.
-struct MandatoryMatchInput {
-    rx: Node,
-    node: Option<Node>,
-    bookmark: Node,
+struct MandatoryMatchInput<'t> {
+    rx: Node<'t>,
+    node: Option<Node<'t>>,
+    bookmark: Node<'t>,
 }
 
-/// Arguments for validating optional block level elements.
-struct OptionalMatchInput {
-    rx: Node,
-    node: Option<Node>,
-    bookmark: Option<Node>,
+/// Arguments for validating optional and recommended block level elements.
+struct OptionalMatchInput<'t> {
+    rx: Node<'t>,
+    node: Option<Node<'t>>,
+    bookmark: Option<Node<'t>>,
 }
 
 /// Type for managing the state of the validation process.
-struct MatchState {
-    rx: Option<Node>,
-    node: Option<Node>,
-    bookmark: Option<Node>,
+struct MatchState<'t> {
+    rx: Option<Node<'t>>,
+    node: Option<Node<'t>>,
+    bookmark: Option<Node<'t>>,
 }
 
 /// Return type for a general validation step.
-enum MatchResult {
-    State(MatchState),
-    Error(Box<Reportable>),
+enum MatchResult<'t> {
+    State(MatchState<'t>),
+    Error(ValidationProblem),
 }
 
 /// Tracks state in matching algorithms that alternate matching directions.
@@ -50,33 +52,175 @@ enum MatchDirection {
     Right,
 }
 
+/// A `ContentMatchPair` along with the byte ranges of its prompt and its content.
+///
+/// Unmatched prompts and content have empty ranges at the position they were expected.
+struct LocatedMatchPair {
+    pair: ContentMatchPair,
+    rx_range: Range<usize>,
+    node_range: Range<usize>,
+}
+
+impl LocatedMatchPair {
+    fn new(pair: ContentMatchPair, rx_range: Range<usize>, node_range: Range<usize>) -> Self {
+        LocatedMatchPair {
+            pair,
+            rx_range,
+            node_range,
+        }
+    }
+}
+
 /// Validates a `Document` against an Rx `Prescription`.
-pub struct Validator<'a> {
-    prescription: Prescription<'a>,
-    document: Document<'a>,
+pub struct Validator {
+    prescription: Prescription,
+    document: Document,
+    matches: RefCell<Vec<(NodeId, NodeId)>>,
+    steps: RefCell<Vec<AlignmentStep>>,
+    warnings: RefCell<Vec<ValidationProblem>>,
+    suppressed: RefCell<Vec<(usize, ErrorCode)>>,
+    allow_extra_content: bool,
 }
 
-impl<'a> Validator<'a> {
+/// Positions in the recorded matches, alignment steps, warnings, and suppressed problems that can
+/// be rewound to.
+struct Checkpoint {
+    matches: usize,
+    steps: usize,
+    warnings: usize,
+    suppressed: usize,
+}
+
+impl Validator {
     /// Returns a new `Validator`.
-    pub fn new(prescription: Prescription<'a>, document: Document<'a>) -> Self {
+    pub fn new(prescription: Prescription, document: Document) -> Self {
         Validator {
             prescription: prescription,
             document,
+            matches: RefCell::new(Vec::new()),
+            steps: RefCell::new(Vec::new()),
+            warnings: RefCell::new(Vec::new()),
+            suppressed: RefCell::new(Vec::new()),
+            allow_extra_content: false,
         }
     }
 
+    /// Sets whether content after everything the prescription asks for is accepted, rather than
+    /// reported as superfluous.
+    pub fn with_extra_content_allowed(mut self, allow: bool) -> Self {
+        self.allow_extra_content = allow;
+        self
+    }
+
+    /// Returns the prescription being validated against.
+    pub fn prescription(&self) -> &Prescription {
+        &self.prescription
+    }
+
+    /// Returns the document being validated.
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Returns the `(prescription, document)` node id pairs matched by the last validation.
+    ///
+    /// Matches are only complete when the last validation found no problems. Elements inside
+    /// wildcards are not paired.
+    pub fn matches(&self) -> Vec<(NodeId, NodeId)> {
+        self.matches.borrow().clone()
+    }
+
+    /// Returns the alignment decisions made by the last validation, in the order they were made.
+    ///
+    /// Attempts that were abandoned are included as mismatches, but the look-ahead used to place
+    /// bookmarks and the contents of wildcards are not.
+    pub fn alignment(&self) -> Vec<AlignmentStep> {
+        self.steps.borrow().clone()
+    }
+
     /// Validates the document against the prescription and returns the results.
     ///
-    /// `None` indicates that the document is valid.
+    /// An empty result indicates that the document is valid. Validation stops at the first error,
+    /// which is followed by any warnings found before it. Problems suppressed by directives in the
+    /// document are accepted and validation continues past them.
     pub fn validate(&self) -> HowserResult<Vec<ValidationProblem>> {
         trace!("validate()");
+        self.matches.borrow_mut().clear();
+        self.steps.borrow_mut().clear();
+        self.warnings.borrow_mut().clear();
+        self.suppressed.borrow_mut().clear();
         let mut problems = Vec::new();
         if let Some(problem) =
-            self.validate_sibling_blocks(&self.prescription.document.root, &self.document.root)?
+            self.validate_sibling_blocks(&self.prescription.document.root(), &self.document.root())?
         {
             problems.push(problem);
         }
-        Ok(problems)
+        problems.extend(self.warnings.borrow_mut().drain(..));
+        Ok(self.apply_suppressions(problems))
+    }
+
+    /// Returns `problem` unless a directive in the document suppresses it.
+    ///
+    /// A suppressed problem is accepted as if the elements matched, so validation carries on past
+    /// it. The directive is recorded as used.
+    fn unless_suppressed(&self, problem: ValidationProblem) -> Option<ValidationProblem> {
+        let code = problem.code();
+        let line = problem
+            .document_location()
+            .map(|location| location.span.start_line)
+            .filter(|&line| line != 0);
+        let suppression = self
+            .document
+            .suppressions()
+            .iter()
+            .position(|suppression| suppression.covers(code, line));
+
+        match suppression {
+            Some(index) => {
+                info!("unless_suppressed:: Suppressed {}", code);
+                self.suppressed.borrow_mut().push((index, code));
+                None
+            }
+            None => Some(problem),
+        }
+    }
+
+    /// Reports directives in the document that name unknown codes or suppressed nothing.
+    ///
+    /// Validation stops at the first error, so directives can only be judged unused when the
+    /// document has no errors at all.
+    fn apply_suppressions(&self, mut problems: Vec<ValidationProblem>) -> Vec<ValidationProblem> {
+        let is_complete = problems
+            .iter()
+            .all(|problem| problem.severity() == Severity::Warning);
+        let suppressed = self.suppressed.borrow();
+
+        for (index, suppression) in self.document.suppressions().iter().enumerate() {
+            for name in &suppression.unknown_codes {
+                let message = format!("\"{}\" is not a known problem code.", name);
+                problems.push(Box::new(SuppressionWarning::new(
+                    &self.document,
+                    suppression.span,
+                    message,
+                )));
+            }
+            if is_complete {
+                let unused = suppression
+                    .codes
+                    .iter()
+                    .filter(|&&code| !suppressed.contains(&(index, code)));
+                for code in unused {
+                    let message = format!("No {} problem was found to suppress.", code);
+                    problems.push(Box::new(SuppressionWarning::new(
+                        &self.document,
+                        suppression.span,
+                        message,
+                    )));
+                }
+            }
+        }
+
+        problems
     }
 
     /// Validates a set of sibling block elements
@@ -86,12 +230,12 @@ impl<'a> Validator<'a> {
         parent_doc_node: &Node,
     ) -> HowserResult<Option<ValidationProblem>> {
         trace!("validate_sibling_blocks::");
-        let mut current_rx = parent_rx_node.first_child()?;
-        let mut current_node = parent_doc_node.first_child()?;
-        let mut current_bookmark = parent_doc_node.first_child()?;
+        let mut current_rx = parent_rx_node.first_child();
+        let mut current_node = parent_doc_node.first_child();
+        let mut current_bookmark = parent_doc_node.first_child();
 
         while let Some(rx) = current_rx {
-            let is_repeatable = match rx.next_sibling()? {
+            let is_repeatable = match rx.next_sibling() {
                 Some(next_rx) => {
                     self.prescription.document.get_match_type(&next_rx)? == MatchType::Repeatable
                 }
@@ -141,16 +285,16 @@ impl<'a> Validator<'a> {
             }
         }
 
-        if let Some(extra_node) = current_node {
+        if let Some(extra_node) = current_node.filter(|_| !self.allow_extra_content) {
             debug!("validate_sibling_blocks:: Superfluous Nodes Error");
             let error = DocumentError::new(
                 &extra_node,
                 parent_rx_node,
                 &self.document,
                 &self.prescription,
-                "Superfluous block content was present.".to_string(),
+                ErrorCode::SuperfluousBlockContent,
             )?;
-            Ok(Some(Box::new(error)))
+            Ok(self.unless_suppressed(Box::new(error)))
         } else {
             info!("validate_sibling_blocks:: Valid!");
             Ok(None)
@@ -166,9 +310,9 @@ impl<'a> Validator<'a> {
         parent_node: &Node,
     ) -> HowserResult<Option<ValidationProblem>> {
         trace!("validate_sibling_inlines::");
-        let mut current_rx = parent_rx.first_child()?;
-        let mut current_node = parent_node.first_child()?;
-        let mut current_bookmark = parent_node.first_child()?;
+        let mut current_rx = parent_rx.first_child();
+        let mut current_node = parent_node.first_child();
+        let mut current_bookmark = parent_node.first_child();
 
         while let Some(rx) = current_rx {
             match self.consume_inline_match(rx, current_node, current_bookmark, parent_node)? {
@@ -189,7 +333,7 @@ impl<'a> Validator<'a> {
             }
         }
 
-        if let Some(extra_node) = current_node {
+        if let Some(extra_node) = current_node.filter(|_| !self.allow_extra_content) {
             debug!(
                 "validate_sibling_inlines:: Superfluous node error: {:?}",
                 extra_node
@@ -199,9 +343,9 @@ impl<'a> Validator<'a> {
                 parent_rx,
                 &self.document,
                 &self.prescription,
-                "Superfluous inline content was present.".to_string(),
+                ErrorCode::SuperfluousInlineContent,
             )?;
-            Ok(Some(Box::new(error)))
+            Ok(self.unless_suppressed(Box::new(error)))
         } else {
             info!("validate_sibling_inlines:: Valid!");
             Ok(None)
@@ -210,17 +354,17 @@ impl<'a> Validator<'a> {
 
     /// Performs the next validation step for block elements given the inputs and returns an
     /// updated MatchState if successful.
-    fn consume_block_match(
-        &self,
-        rx: Node,
-        node: Option<Node>,
-        bookmark: Option<Node>,
-        parent_node: &Node,
-    ) -> HowserResult<MatchResult> {
+    fn consume_block_match<'t>(
+        &'t self,
+        rx: Node<'t>,
+        node: Option<Node<'t>>,
+        bookmark: Option<Node<'t>>,
+        parent_node: &Node<'t>,
+    ) -> HowserResult<MatchResult<'t>> {
         trace!("consume_block_match::");
-        info!("Rx: {}", rx.render_xml());
+        info!("Rx: {:?}", rx);
         if let Some(ref node) = node {
-            info!("Doc: {}", node.render_xml());
+            info!("Doc: {:?}", node);
         }
 
         match self.prescription.document.get_match_type(&rx)? {
@@ -232,19 +376,19 @@ impl<'a> Validator<'a> {
                     )
                 } else {
                     debug!("consume_block_match:: Missing Mandatory node and no bookmark");
-                    let error = DocumentError::new(
+                    self.missing_match(
+                        rx,
+                        node,
+                        bookmark,
                         parent_node,
-                        &rx,
-                        &self.document,
-                        &self.prescription,
-                        "Missing mandatory block node.".to_string(),
-                    )?;
-                    Ok(MatchResult::Error(Box::new(error)))
+                        ErrorCode::MissingMandatoryBlock,
+                    )
                 }
             }
-            MatchType::Optional => {
-                self.consume_optional_block_match(OptionalMatchInput { rx, node, bookmark })
-            }
+            MatchType::Optional | MatchType::Recommended => self.consume_optional_block_match(
+                OptionalMatchInput { rx, node, bookmark },
+                parent_node,
+            ),
             _ => {
                 error!("Unexpected Matchtype encountered in consume_block_match");
                 Ok(MatchResult::State(MatchState {
@@ -258,18 +402,18 @@ impl<'a> Validator<'a> {
 
     /// Performs the next validation step for inline elements and returns a `MatchState` if
     /// successful.
-    fn consume_inline_match(
-        &self,
-        rx: Node,
-        node: Option<Node>,
-        bookmark: Option<Node>,
-        parent_node: &Node,
-    ) -> HowserResult<MatchResult> {
+    fn consume_inline_match<'t>(
+        &'t self,
+        rx: Node<'t>,
+        node: Option<Node<'t>>,
+        bookmark: Option<Node<'t>>,
+        parent_node: &Node<'t>,
+    ) -> HowserResult<MatchResult<'t>> {
         trace!("consume_inline_match::");
-        info!("Rx: {}", rx.render_xml());
+        info!("Rx: {:?}", rx);
 
         if let Some(ref node) = node {
-            info!("Doc: {}", node.render_xml());
+            info!("Doc: {:?}", node);
         }
 
         match self.prescription.document.get_match_type(&rx)? {
@@ -284,14 +428,13 @@ impl<'a> Validator<'a> {
                     )
                 } else {
                     debug!("consume_block_match:: Missing Mandatory node and no bookmark");
-                    let error = DocumentError::new(
+                    self.missing_match(
+                        rx,
+                        node,
+                        bookmark,
                         parent_node,
-                        &rx,
-                        &self.document,
-                        &self.prescription,
-                        "Missing mandatory inline node.".to_string(),
-                    )?;
-                    Ok(MatchResult::Error(Box::new(error)))
+                        ErrorCode::MissingMandatoryInline,
+                    )
                 }
             }
             mt => Err(HowserError::RuntimeError(format!(
@@ -302,43 +445,33 @@ impl<'a> Validator<'a> {
     }
 
     /// Performs validation on a repeatable element and returns the result.
-    fn consume_repeatable_matches(
-        &self,
-        rx: Node,
-        node: Option<Node>,
-        bookmark: Option<Node>,
-        parent_node: &Node,
-    ) -> HowserResult<MatchResult> {
+    fn consume_repeatable_matches<'t>(
+        &'t self,
+        rx: Node<'t>,
+        node: Option<Node<'t>>,
+        bookmark: Option<Node<'t>>,
+        parent_node: &Node<'t>,
+    ) -> HowserResult<MatchResult<'t>> {
         trace!("consume_repeatable_matches()");
-        let out_rx = match rx.next_sibling()? {
-            Some(ditto_node) => ditto_node.next_sibling()?,
+        let out_rx = match rx.next_sibling() {
+            Some(ditto_node) => ditto_node.next_sibling(),
             _ => None,
         };
-        let mut out_node = match node {
-            Some(ref node) => Some(node.itself()?),
-            None => None,
-        };
-        let mut out_bookmark = match bookmark {
-            Some(ref node) => Some(node.itself()?),
-            None => None,
-        };
-        let mut current_rx = rx.itself()?;
-        let mut current_node = match node {
-            Some(ref node) => Some(node.itself()?),
-            None => None,
-        };
-        let mut current_bookmark = match bookmark {
-            Some(ref node) => Some(node.itself()?),
-            None => None,
-        };
+        let mut out_node = node;
+        let mut out_bookmark = bookmark;
+        let mut current_rx = rx;
+        let mut current_node = node;
+        let mut current_bookmark = bookmark;
         let match_type = self.prescription.document.get_match_type(&rx)?;
         let mut matches_consumed: usize = 0;
 
         loop {
             let current_node_id = match current_node {
-                Some(ref node) => node.get_id(),
+                Some(ref node) => node.id(),
                 _ => 0,
             };
+            let warnings = self.warnings.borrow().len();
+            let suppressed = self.suppressed.borrow().len();
             let match_result =
                 self.consume_block_match(current_rx, current_node, current_bookmark, parent_node)?;
 
@@ -351,45 +484,37 @@ impl<'a> Validator<'a> {
                     } = state;
 
                     matches_consumed += 1;
-                    current_node = match result_node {
-                        Some(ref node) => Some(node.itself()?),
-                        None => None,
-                    };
-                    current_rx = rx.itself()?;
+                    current_node = result_node;
+                    current_rx = rx;
                     current_bookmark = result_bookmark;
-                    out_node = match result_node {
-                        Some(ref node) => Some(node.itself()?),
-                        None => None,
-                    };
+                    out_node = result_node;
                     if matches_consumed == 1 {
-                        out_bookmark = match current_bookmark {
-                            Some(ref node) => Some(node.itself()?),
-                            _ => None,
-                        };
+                        out_bookmark = current_bookmark;
                     }
                 }
-                (MatchResult::State(state), MatchType::Optional) => {
+                (MatchResult::State(state), MatchType::Optional)
+                | (MatchResult::State(state), MatchType::Recommended) => {
                     let MatchState {
                         rx: _,
                         node: result_node,
                         bookmark: result_bookmark,
                     } = state;
 
-                    current_node = match result_node {
-                        Some(ref node) => Some(node.itself()?),
-                        None => None,
-                    };
-                    current_rx = rx.itself()?;
+                    current_node = result_node;
+                    current_rx = rx;
                     current_bookmark = result_bookmark;
-                    out_node = match result_node {
-                        Some(ref node) => Some(node.itself()?),
-                        None => None,
+                    out_node = result_node;
+                    let consumed = match current_node {
+                        Some(ref node) => node.id() != current_node_id,
+                        None => current_node_id != 0,
                     };
-                    if let Some(ref node) = current_node {
-                        if node.get_id() == current_node_id {
-                            break;
-                        }
-                    } else {
+                    if consumed {
+                        matches_consumed += 1;
+                    } else if matches_consumed > 0 {
+                        self.warnings.borrow_mut().truncate(warnings);
+                        self.suppressed.borrow_mut().truncate(suppressed);
+                    }
+                    if !consumed || current_node.is_none() {
                         break;
                     }
                 }
@@ -400,7 +525,8 @@ impl<'a> Validator<'a> {
                         break;
                     }
                 }
-                (MatchResult::Error(_), MatchType::Optional) => {
+                (MatchResult::Error(_), MatchType::Optional)
+                | (MatchResult::Error(_), MatchType::Recommended) => {
                     break;
                 }
                 _ => {
@@ -414,6 +540,7 @@ impl<'a> Validator<'a> {
         }
 
         info!("consume_repeatable_matches:: matches found or node was optional");
+        self.push_step(&rx, None, Alignment::Repeated(matches_consumed));
         Ok(MatchResult::State(MatchState {
             rx: out_rx,
             node: out_node,
@@ -422,25 +549,27 @@ impl<'a> Validator<'a> {
     }
 
     /// Performs validation on a mandatory block element and returns the result.
-    fn consume_mandatory_block_match(
-        &self,
-        input: MandatoryMatchInput,
-        parent_node: &Node,
-    ) -> HowserResult<MatchResult> {
+    fn consume_mandatory_block_match<'t>(
+        &'t self,
+        input: MandatoryMatchInput<'t>,
+        parent_node: &Node<'t>,
+    ) -> HowserResult<MatchResult<'t>> {
         trace!("consume_mandatory_block_match()");
         let MandatoryMatchInput { rx, node, bookmark } = input;
 
         if let Some(node) = node {
             match self.check_block_match(&node, &rx)? {
                 None => {
-                    let end_node = Some(node.itself()?);
+                    let end_node = Some(node);
+                    let checkpoint = self.checkpoint();
                     let next_bookmark = match self.scan_for_block_match(&bookmark, &end_node, &rx)?
                     {
-                        Some(node) => node.next_sibling()?,
+                        Some(node) => node.next_sibling(),
                         _ => None,
                     };
-                    let next_node = node.next_sibling()?;
-                    let next_rx = rx.next_sibling()?;
+                    self.rewind(checkpoint);
+                    let next_node = node.next_sibling();
+                    let next_rx = rx.next_sibling();
                     info!("consume_mandatory_block_match:: Block matched");
                     Ok(MatchResult::State(MatchState {
                         rx: next_rx,
@@ -449,12 +578,12 @@ impl<'a> Validator<'a> {
                     }))
                 }
                 Some(err) => {
-                    let end_node = Some(node.itself()?);
+                    let end_node = Some(node);
                     if let Some(prev_match) = self.scan_for_block_match(&bookmark, &end_node, &rx)?
                     {
-                        let next_bookmark = prev_match.next_sibling()?;
-                        let next_node = prev_match.next_sibling()?;
-                        let next_rx = rx.next_sibling()?;
+                        let next_bookmark = prev_match.next_sibling();
+                        let next_node = prev_match.next_sibling();
+                        let next_rx = rx.next_sibling();
                         info!("consume_mandatory_block_match:: Current node mismatch, but match found from bookmark");
                         Ok(MatchResult::State(MatchState {
                             rx: next_rx,
@@ -468,9 +597,9 @@ impl<'a> Validator<'a> {
             }
         } else {
             if let Some(prev_match) = self.scan_for_block_match(&bookmark, &None, &rx)? {
-                let next_bookmark = prev_match.next_sibling()?;
-                let next_node = prev_match.next_sibling()?;
-                let next_rx = rx.next_sibling()?;
+                let next_bookmark = prev_match.next_sibling();
+                let next_node = prev_match.next_sibling();
+                let next_rx = rx.next_sibling();
                 info!("consume_mandatory_block_match:: No current node, but match found from bookmark");
                 Ok(MatchResult::State(MatchState {
                     rx: next_rx,
@@ -479,65 +608,102 @@ impl<'a> Validator<'a> {
                 }))
             } else {
                 debug!("consume_mandatory_block_match -- No current node and No match");
-                let error = DocumentError::new(
+                self.missing_match(
+                    rx,
+                    None,
+                    Some(bookmark),
                     parent_node,
-                    &rx,
-                    &self.document,
-                    &self.prescription,
-                    "Missing mandatory node.".to_string(),
-                )?;
-                Ok(MatchResult::Error(Box::new(error)))
+                    ErrorCode::MissingMandatoryBlock,
+                )
+            }
+        }
+    }
+
+    /// Reports that no element matches the mandatory `rx`, or skips `rx` if a directive in the
+    /// document suppresses the problem.
+    fn missing_match<'t>(
+        &'t self,
+        rx: Node<'t>,
+        node: Option<Node<'t>>,
+        bookmark: Option<Node<'t>>,
+        parent_node: &Node<'t>,
+        code: ErrorCode,
+    ) -> HowserResult<MatchResult<'t>> {
+        let error = DocumentError::new(parent_node, &rx, &self.document, &self.prescription, code)?;
+        match self.unless_suppressed(Box::new(error)) {
+            Some(error) => Ok(MatchResult::Error(error)),
+            None => {
+                self.push_step(&rx, node.as_ref(), Alignment::Skipped);
+                Ok(MatchResult::State(MatchState {
+                    rx: rx.next_sibling(),
+                    node,
+                    bookmark,
+                }))
             }
         }
     }
 
-    /// Performs validation on an optional block element and returns the result.
-    fn consume_optional_block_match(&self, input: OptionalMatchInput) -> HowserResult<MatchResult> {
+    /// Performs validation on an optional or recommended block element and returns the result.
+    ///
+    /// Skipping a recommended element records a warning.
+    fn consume_optional_block_match<'t>(
+        &'t self,
+        input: OptionalMatchInput<'t>,
+        parent_node: &Node<'t>,
+    ) -> HowserResult<MatchResult<'t>> {
         trace!("consume_optional_block_match()");
         let OptionalMatchInput { rx, node, bookmark } = input;
 
-        if let Some(node) = node {
-            match self.check_block_match(&node, &rx)? {
-                None => {
-                    let next_node = node.next_sibling()?;
-                    let next_rx = rx.next_sibling()?;
-                    Ok(MatchResult::State(MatchState {
-                        rx: next_rx,
-                        node: next_node,
-                        bookmark: bookmark,
-                    }))
-                }
-                Some(_) => {
-                    let next_rx = rx.next_sibling()?;
-                    Ok(MatchResult::State(MatchState {
-                        rx: next_rx,
-                        node: Some(node),
-                        bookmark: bookmark,
-                    }))
-                }
-            }
-        } else {
-            let next_rx = rx.next_sibling()?;
-            Ok(MatchResult::State(MatchState {
+        let is_matched = match node {
+            Some(ref node) => self.check_block_match(node, &rx)?.is_none(),
+            None => false,
+        };
+
+        if is_matched {
+            let next_node = node.and_then(|node| node.next_sibling());
+            let next_rx = rx.next_sibling();
+            return Ok(MatchResult::State(MatchState {
                 rx: next_rx,
-                node: node,
+                node: next_node,
                 bookmark: bookmark,
-            }))
+            }));
         }
+
+        if self.prescription.document.get_match_type(&rx)? == MatchType::Recommended {
+            debug!("consume_optional_block_match:: Missing recommended node");
+            let warning = DocumentError::new(
+                node.as_ref().unwrap_or(parent_node),
+                &rx,
+                &self.document,
+                &self.prescription,
+                ErrorCode::MissingRecommendedBlock,
+            )?;
+            if let Some(warning) = self.unless_suppressed(Box::new(warning)) {
+                self.warnings.borrow_mut().push(warning);
+            }
+        }
+
+        self.push_step(&rx, node.as_ref(), Alignment::Skipped);
+        let next_rx = rx.next_sibling();
+        Ok(MatchResult::State(MatchState {
+            rx: next_rx,
+            node: node,
+            bookmark: bookmark,
+        }))
     }
 
-    fn consume_optional_inline_match(
-        &self,
-        input: OptionalMatchInput,
-    ) -> HowserResult<MatchResult> {
+    fn consume_optional_inline_match<'t>(
+        &'t self,
+        input: OptionalMatchInput<'t>,
+    ) -> HowserResult<MatchResult<'t>> {
         trace!("consume_optional__inline_match");
         let OptionalMatchInput { rx, node, bookmark } = input;
 
         if let Some(node) = node {
             match self.check_inline_match(&rx, &node)? {
                 None => {
-                    let next_node = node.next_sibling()?;
-                    let next_rx = rx.next_sibling()?;
+                    let next_node = node.next_sibling();
+                    let next_rx = rx.next_sibling();
                     Ok(MatchResult::State(MatchState {
                         rx: next_rx,
                         node: next_node,
@@ -545,7 +711,8 @@ impl<'a> Validator<'a> {
                     }))
                 }
                 Some(_) => {
-                    let next_rx = rx.next_sibling()?;
+                    self.push_step(&rx, Some(&node), Alignment::Skipped);
+                    let next_rx = rx.next_sibling();
                     Ok(MatchResult::State(MatchState {
                         rx: next_rx,
                         node: Some(node),
@@ -554,7 +721,8 @@ impl<'a> Validator<'a> {
                 }
             }
         } else {
-            let next_rx = rx.next_sibling()?;
+            self.push_step(&rx, None, Alignment::Skipped);
+            let next_rx = rx.next_sibling();
             Ok(MatchResult::State(MatchState {
                 rx: next_rx,
                 node,
@@ -564,25 +732,27 @@ impl<'a> Validator<'a> {
     }
 
     /// Performs validation on a mandatory inline element.
-    fn consume_mandatory_inline_match(
-        &self,
-        input: MandatoryMatchInput,
-        parent_node: &Node,
-    ) -> HowserResult<MatchResult> {
+    fn consume_mandatory_inline_match<'t>(
+        &'t self,
+        input: MandatoryMatchInput<'t>,
+        parent_node: &Node<'t>,
+    ) -> HowserResult<MatchResult<'t>> {
         trace!("consume_mandatory_inline_match::");
         let MandatoryMatchInput { rx, node, bookmark } = input;
 
         if let Some(node) = node {
             match self.check_inline_match(&rx, &node)? {
                 None => {
-                    let end_node = Some(node.itself()?);
+                    let end_node = Some(node);
+                    let checkpoint = self.checkpoint();
                     let next_bookmark = match self.scan_for_inline_match(&bookmark, &end_node, &rx)?
                     {
-                        Some(node) => node.next_sibling()?,
+                        Some(node) => node.next_sibling(),
                         None => None,
                     };
-                    let next_node = node.next_sibling()?;
-                    let next_rx = rx.next_sibling()?;
+                    self.rewind(checkpoint);
+                    let next_node = node.next_sibling();
+                    let next_rx = rx.next_sibling();
 
                     info!("consume_mandatory_inline_match:: Matched!");
                     Ok(MatchResult::State(MatchState {
@@ -592,12 +762,12 @@ impl<'a> Validator<'a> {
                     }))
                 }
                 Some(err) => {
-                    let end_node = Some(node.itself()?);
+                    let end_node = Some(node);
                     if let Some(prev_match) = self.scan_for_inline_match(&bookmark, &end_node, &rx)?
                     {
-                        let next_bookmark = prev_match.next_sibling()?;
-                        let next_node = prev_match.next_sibling()?;
-                        let next_rx = rx.next_sibling()?;
+                        let next_bookmark = prev_match.next_sibling();
+                        let next_node = prev_match.next_sibling();
+                        let next_rx = rx.next_sibling();
                         info!("consume_mandatory_inline_match:: Bookmark Match Found!");
                         Ok(MatchResult::State(MatchState {
                             rx: next_rx,
@@ -611,9 +781,9 @@ impl<'a> Validator<'a> {
             }
         } else {
             if let Some(prev_match) = self.scan_for_inline_match(&bookmark, &None, &rx)? {
-                let next_bookmark = prev_match.next_sibling()?;
-                let next_node = prev_match.next_sibling()?;
-                let next_rx = rx.next_sibling()?;
+                let next_bookmark = prev_match.next_sibling();
+                let next_node = prev_match.next_sibling();
+                let next_rx = rx.next_sibling();
                 info!("consume_mandatory_inline_match:: Bookmark Match Found!");
                 Ok(MatchResult::State(MatchState {
                     rx: next_rx,
@@ -622,14 +792,13 @@ impl<'a> Validator<'a> {
                 }))
             } else {
                 debug!("consume_mandatory_inline_match:: No current node and No match");
-                let error = DocumentError::new(
+                self.missing_match(
+                    rx,
+                    None,
+                    Some(bookmark),
                     parent_node,
-                    &rx,
-                    &self.document,
-                    &self.prescription,
-                    "Missing mandatory node.".to_string(),
-                )?;
-                Ok(MatchResult::Error(Box::new(error)))
+                    ErrorCode::MissingMandatoryInline,
+                )
             }
         }
     }
@@ -639,30 +808,32 @@ impl<'a> Validator<'a> {
     ///
     /// Starts searching at `start_node` and progresses through to `end_node` until a match is
     /// found. Returns the first matched `Node` or `None` if a match could not be found.
-    fn scan_for_block_match(
-        &self,
-        start_node: &Node,
-        end_node: &Option<Node>,
-        rx: &Node,
-    ) -> HowserResult<Option<Node>> {
+    fn scan_for_block_match<'t>(
+        &'t self,
+        start_node: &Node<'t>,
+        end_node: &Option<Node<'t>>,
+        rx: &Node<'t>,
+    ) -> HowserResult<Option<Node<'t>>> {
         trace!("scan_for_block_match()");
-        let mut current_node = Some(start_node.itself()?);
+        let mut current_node = Some(*start_node);
 
         while let Some(node) = current_node {
+            let checkpoint = self.checkpoint();
             if let None = self.check_block_match(&node, rx)? {
                 return Ok(Some(node));
             }
+            self.rewind(checkpoint);
 
             if let &Some(ref stop_node) = end_node {
-                let node_id = node.get_id();
-                let stop_id = stop_node.get_id();
+                let node_id = node.id();
+                let stop_id = stop_node.id();
                 if node_id == stop_id {
                     current_node = None;
                 } else {
-                    current_node = node.next_sibling()?;
+                    current_node = node.next_sibling();
                 }
             } else {
-                current_node = node.next_sibling()?;
+                current_node = node.next_sibling();
             }
         }
 
@@ -674,31 +845,33 @@ impl<'a> Validator<'a> {
     ///
     /// Starts searching at `start_node` and progresses through to `end_node` until a match is
     /// found. Returns the first matched `Node` or `None` if a match could not be found.
-    fn scan_for_inline_match(
-        &self,
-        start_node: &Node,
-        end_node: &Option<Node>,
-        rx: &Node,
-    ) -> HowserResult<Option<Node>> {
+    fn scan_for_inline_match<'t>(
+        &'t self,
+        start_node: &Node<'t>,
+        end_node: &Option<Node<'t>>,
+        rx: &Node<'t>,
+    ) -> HowserResult<Option<Node<'t>>> {
         trace!("scan_for_inline_match::");
-        let mut current_node = Some(start_node.itself()?);
+        let mut current_node = Some(*start_node);
 
         while let Some(node) = current_node {
+            let checkpoint = self.checkpoint();
             if let None = self.check_inline_match(rx, &node)? {
                 return Ok(Some(node));
             }
+            self.rewind(checkpoint);
 
             if let &Some(ref stop_node) = end_node {
-                let node_id = node.get_id();
-                let stop_id = stop_node.get_id();
+                let node_id = node.id();
+                let stop_id = stop_node.id();
 
                 if node_id == stop_id {
                     current_node = None;
                 } else {
-                    current_node = node.next_sibling()?;
+                    current_node = node.next_sibling();
                 }
             } else {
-                current_node = node.next_sibling()?;
+                current_node = node.next_sibling();
             }
         }
 
@@ -710,16 +883,20 @@ impl<'a> Validator<'a> {
     /// Inputs are assumed to be block elements.
     fn check_block_match(&self, node: &Node, rx: &Node) -> HowserResult<Option<ValidationProblem>> {
         trace!("check_block_match::");
-        match ElementType::determine(rx) {
-            ElementType::ContainerBlock => Ok(self.check_container_block_match(&node, &rx)?),
-            ElementType::LeafBlock => Ok(self.check_leaf_block_match(&node, &rx)?),
+        let checkpoint = self.checkpoint();
+        let result = match ElementType::determine(rx) {
+            ElementType::ContainerBlock => self.check_container_block_match(&node, &rx)?,
+            ElementType::LeafBlock => self.check_leaf_block_match(&node, &rx)?,
             _ => {
                 error!("check_block_match:: called with an invalid element type.");
-                Err(HowserError::RuntimeError(
+                return Err(HowserError::RuntimeError(
                     "Element type problem -- check the Howser log.".to_string(),
-                ))
+                ));
             }
-        }
+        };
+        self.record_match(checkpoint, rx, node, &result);
+
+        Ok(result)
     }
 
     /// Determines if `node` matches `rx`.
@@ -735,7 +912,7 @@ impl<'a> Validator<'a> {
         if !types_match(node, rx)? {
             debug!("check_container_block_match:: different types -- no match");
             let error = TypeMismatchError::new(rx, node, &self.prescription, &self.document)?;
-            return Ok(Some(Box::new(error)));
+            return Ok(self.unless_suppressed(Box::new(error)));
         }
 
         let child_validation = self.validate_sibling_blocks(rx, node)?;
@@ -765,7 +942,7 @@ impl<'a> Validator<'a> {
         if !types_match(node, rx)? {
             debug!("check_leaf_block_match:: Types do not match");
             let error = TypeMismatchError::new(rx, node, &self.prescription, &self.document)?;
-            return Ok(Some(Box::new(error)));
+            return Ok(self.unless_suppressed(Box::new(error)));
         }
 
         let child_validation = self.validate_sibling_inlines(rx, node)?;
@@ -792,18 +969,87 @@ impl<'a> Validator<'a> {
         node: &Node,
     ) -> HowserResult<Option<ValidationProblem>> {
         trace!("check_inline_match::");
-        match ElementType::determine(rx) {
-            ElementType::InlineLeaf => self.check_inline_leaf_match(node, rx),
-            ElementType::InlineContainer => self.check_inline_container_match(node, rx),
+        let checkpoint = self.checkpoint();
+        let result = match ElementType::determine(rx) {
+            ElementType::InlineLeaf => self.check_inline_leaf_match(node, rx)?,
+            ElementType::InlineContainer => self.check_inline_container_match(node, rx)?,
             _ => {
                 error!("check_inline_match:: called with unexpected element type");
-                Err(HowserError::RuntimeError(
+                return Err(HowserError::RuntimeError(
                     "Invalid element type encountered. Check the log.".to_string(),
-                ))
+                ));
             }
+        };
+        self.record_match(checkpoint, rx, node, &result);
+
+        Ok(result)
+    }
+
+    /// Records the comparison of `rx` with `node`, which began at `checkpoint`.
+    ///
+    /// Matches recorded since the checkpoint are discarded if the elements did not match, and
+    /// everything recorded since is discarded if `rx` is a wildcard whose descendants were never
+    /// required to match. The alignment step is placed ahead of the steps of any descendants.
+    fn record_match(
+        &self,
+        checkpoint: Checkpoint,
+        rx: &Node,
+        node: &Node,
+        result: &Option<ValidationProblem>,
+    ) {
+        let steps = checkpoint.steps;
+        if self.prescription.document.is_wildcard(rx) {
+            self.rewind(checkpoint);
+        } else if result.is_some() {
+            self.matches.borrow_mut().truncate(checkpoint.matches);
+            self.warnings.borrow_mut().truncate(checkpoint.warnings);
+            self.suppressed.borrow_mut().truncate(checkpoint.suppressed);
+        }
+
+        let alignment = match result {
+            &Some(ref problem) => Alignment::Mismatched(problem.code()),
+            &None => {
+                self.matches.borrow_mut().push((rx.id(), node.id()));
+                Alignment::Matched
+            }
+        };
+        self.steps.borrow_mut().insert(
+            steps,
+            AlignmentStep {
+                rx: rx.id(),
+                node: Some(node.id()),
+                alignment,
+            },
+        );
+    }
+
+    /// Records an alignment step that does not involve comparing elements.
+    fn push_step(&self, rx: &Node, node: Option<&Node>, alignment: Alignment) {
+        self.steps.borrow_mut().push(AlignmentStep {
+            rx: rx.id(),
+            node: node.map(|node| node.id()),
+            alignment,
+        });
+    }
+
+    /// Returns the current position in the recorded matches and alignment steps.
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            matches: self.matches.borrow().len(),
+            steps: self.steps.borrow().len(),
+            warnings: self.warnings.borrow().len(),
+            suppressed: self.suppressed.borrow().len(),
         }
     }
 
+    /// Discards the matches and alignment steps recorded after `checkpoint`.
+    fn rewind(&self, checkpoint: Checkpoint) {
+        self.matches.borrow_mut().truncate(checkpoint.matches);
+        self.steps.borrow_mut().truncate(checkpoint.steps);
+        self.warnings.borrow_mut().truncate(checkpoint.warnings);
+        self.suppressed.borrow_mut().truncate(checkpoint.suppressed);
+    }
+
     /// Determines if `node` matches `rx`.
     ///
     /// Inputs are assumed to be container inline elements.
@@ -816,11 +1062,11 @@ impl<'a> Validator<'a> {
 
         if !types_match(node, rx)? {
             let error = TypeMismatchError::new(rx, node, &self.prescription, &self.document)?;
-            return Ok(Some(Box::new(error)));
+            return Ok(self.unless_suppressed(Box::new(error)));
         }
 
-        match node {
-            Node::Link(_) => self.validate_link_node_content(node, rx),
+        match node.kind() {
+            &NodeKind::Link { .. } => self.validate_link_node_content(node, rx),
             _ => self.validate_sibling_inlines(rx, node),
         }
     }
@@ -839,7 +1085,7 @@ impl<'a> Validator<'a> {
             debug!("check_inline_leaf_match:: Type mismatch");
             let error = TypeMismatchError::new(rx, node, &self.prescription, &self.document)?;
 
-            return Ok(Some(Box::new(error)));
+            return Ok(self.unless_suppressed(Box::new(error)));
         }
 
         self.validate_textual_content(node, rx)
@@ -869,8 +1115,8 @@ impl<'a> Validator<'a> {
         rx: &Node,
     ) -> HowserResult<Option<ValidationProblem>> {
         trace!("validate_node_content()");
-        match rx {
-            Node::Link(_) => self.validate_link_node_content(node, rx),
+        match rx.kind() {
+            &NodeKind::Link { .. } => self.validate_link_node_content(node, rx),
             _ => self.validate_text_node_content(node, rx),
         }
     }
@@ -884,15 +1130,19 @@ impl<'a> Validator<'a> {
         rx: &Node,
     ) -> HowserResult<Option<ValidationProblem>> {
         trace!("validate_link_node_content()");
-        match (node, rx) {
-            (Node::Link(ref node_link), Node::Link(ref rx_link)) => {
-                let node_url = node_link.get_url()?;
-                let rx_url = rx_link.get_url()?;
-                let url_match_pairs = Self::check_content_match(&node_url, &rx_url)?;
-
-                let node_title = node_link.get_title()?;
-                let rx_title = rx_link.get_title()?;
-                let title_match_pairs = Self::check_content_match(&node_title, &rx_title)?;
+        match (node.kind(), rx.kind()) {
+            (
+                &NodeKind::Link {
+                    url: ref node_url,
+                    title: ref node_title,
+                },
+                &NodeKind::Link {
+                    url: ref rx_url,
+                    title: ref rx_title,
+                },
+            ) => {
+                let url_match_pairs = Self::check_content_match(node_url, rx_url)?;
+                let title_match_pairs = Self::check_content_match(node_title, rx_title)?;
 
                 if ContentMatchPair::contains_mismatch(&url_match_pairs) {
                     debug!("Link destination Error");
@@ -902,8 +1152,9 @@ impl<'a> Validator<'a> {
                         &self.prescription,
                         &self.document,
                         &url_match_pairs,
+                        ErrorCode::LinkDestinationMismatch,
                     )?;
-                    Ok(Some(Box::new(error)))
+                    Ok(self.unless_suppressed(Box::new(error)))
                 } else if ContentMatchPair::contains_mismatch(&title_match_pairs) {
                     debug!("Link title Error");
                     let error = TextualContentError::new(
@@ -912,8 +1163,9 @@ impl<'a> Validator<'a> {
                         &self.prescription,
                         &self.document,
                         &title_match_pairs,
+                        ErrorCode::LinkTitleMismatch,
                     )?;
-                    Ok(Some(Box::new(error)))
+                    Ok(self.unless_suppressed(Box::new(error)))
                 } else {
                     self.validate_sibling_inlines(rx, node)
                 }
@@ -932,26 +1184,41 @@ impl<'a> Validator<'a> {
         node: &Node,
         rx: &Node,
     ) -> HowserResult<Option<ValidationProblem>> {
-        let (node_content, rx_content) = match (node, rx) {
-            (Node::Text(ref node_text), Node::Text(ref rx_text)) => {
-                (node_text.get_content()?, rx_text.get_content()?)
+        let (node_content, rx_content) = match (node.kind(), rx.kind()) {
+            (&NodeKind::Text(ref node_text), &NodeKind::Text(ref rx_text)) => {
+                (node_text.clone(), rx_text.clone())
             }
-            (Node::Code(ref node_code), Node::Code(ref rx_code)) => {
-                (node_code.get_content()?, rx_code.get_content()?)
+            (&NodeKind::Code(ref node_code), &NodeKind::Code(ref rx_code)) => {
+                (node_code.clone(), rx_code.clone())
             }
             _ => (String::new(), String::new()),
         };
-        let match_pairs = Self::check_content_match(&node_content, &rx_content)?;
+        let located_pairs = Self::locate_content_match(&node_content, &rx_content)?;
+        let mismatch = located_pairs
+            .iter()
+            .find(|located| !ContentMatchPair::is_match(&located.pair))
+            .map(|located| {
+                (
+                    rx.content_span(located.rx_range.clone()),
+                    node.content_span(located.node_range.clone()),
+                )
+            });
 
-        if ContentMatchPair::contains_mismatch(&match_pairs) {
+        if let Some((rx_span, node_span)) = mismatch {
             info!("Content Error");
-            return Ok(Some(Box::new(TextualContentError::new(
+            let match_pairs: Vec<ContentMatchPair> = located_pairs
+                .into_iter()
+                .map(|located| located.pair)
+                .collect();
+            let error = TextualContentError::new(
                 rx,
                 node,
                 &self.prescription,
                 &self.document,
                 &match_pairs,
-            )?)));
+                ErrorCode::TextualContentMismatch,
+            )?;
+            return Ok(self.unless_suppressed(Box::new(error.with_focus(rx_span, node_span))));
         }
 
         Ok(None)
@@ -962,35 +1229,63 @@ impl<'a> Validator<'a> {
         node_content: &String,
         rx_content: &String,
     ) -> HowserResult<Vec<ContentMatchPair>> {
+        Ok(Self::locate_content_match(node_content, rx_content)?
+            .into_iter()
+            .map(|located| located.pair)
+            .collect())
+    }
+
+    /// Matches the given text against the given prescription text, keeping track of where each
+    /// `ContentMatchPair` came from in both.
+    fn locate_content_match(
+        node_content: &String,
+        rx_content: &String,
+    ) -> HowserResult<Vec<LocatedMatchPair>> {
         trace!("match_contents()");
         let mut content_queue = node_content.clone();
-        let mut prompts = VecDeque::from(Validator::tokenize_prompts(rx_content)?);
-        let mut left_stack = Vec::new();
-        let mut right_stack = Vec::new();
+        let mut front = 0;
+        let mut back = node_content.len();
+        let mut prompts = VecDeque::from(Validator::locate_prompts(rx_content)?);
+        let mut left_stack: Vec<LocatedMatchPair> = Vec::new();
+        let mut right_stack: Vec<LocatedMatchPair> = Vec::new();
         let mut current_direction = MatchDirection::Left;
 
         while !prompts.is_empty() {
-            let (prompt, stack) = match current_direction {
+            let ((prompt, rx_range), stack) = match current_direction {
                 MatchDirection::Left => (prompts.pop_front().unwrap(), &mut left_stack),
                 MatchDirection::Right => (prompts.pop_back().unwrap(), &mut right_stack),
             };
+            let vacancy = match current_direction {
+                MatchDirection::Left => front..front,
+                MatchDirection::Right => back..back,
+            };
             match prompt {
                 PromptToken::Mandatory => {
                     if content_queue.is_empty() {
                         let pair = ContentMatchPair(prompt, None);
-                        stack.push(pair);
+                        stack.push(LocatedMatchPair::new(pair, rx_range, vacancy));
                     } else {
-                        let substitution = match current_direction {
-                            MatchDirection::Left => content_queue.remove(0).to_string(),
-                            MatchDirection::Right => content_queue.pop().unwrap().to_string(),
+                        let (substitution, node_range) = match current_direction {
+                            MatchDirection::Left => {
+                                let substitution = content_queue.remove(0).to_string();
+                                let start = front;
+                                front += substitution.len();
+                                (substitution, start..front)
+                            }
+                            MatchDirection::Right => {
+                                let substitution = content_queue.pop().unwrap().to_string();
+                                let end = back;
+                                back -= substitution.len();
+                                (substitution, back..end)
+                            }
                         };
                         let pair = ContentMatchPair(PromptToken::Mandatory, Some(substitution));
-                        stack.push(pair);
+                        stack.push(LocatedMatchPair::new(pair, rx_range, node_range));
                     }
                 }
                 PromptToken::Optional => {
                     let pair = ContentMatchPair(PromptToken::Optional, None);
-                    stack.push(pair);
+                    stack.push(LocatedMatchPair::new(pair, rx_range, vacancy));
                 }
                 PromptToken::Literal(ref content) => {
                     let temp_queue = content_queue.clone();
@@ -999,34 +1294,49 @@ impl<'a> Validator<'a> {
                             Some(0) => {
                                 let substitution: String =
                                     content_queue.drain(..content.len()).collect();
-                                (None, Some(substitution))
+                                front += content.len();
+                                (None, Some((substitution, front - content.len()..front)))
                             }
                             Some(n) => {
                                 let preface: String = content_queue.drain(..n).collect();
                                 let substitution = content_queue.drain(..content.len()).collect();
-                                (Some(preface), Some(substitution))
+                                front += n + content.len();
+                                let start = front - content.len();
+                                (
+                                    Some((preface, start - n..start)),
+                                    Some((substitution, start..front)),
+                                )
                             }
                             None => (None, None),
                         },
                         MatchDirection::Right => match temp_queue.rfind(content) {
                             Some(n) => {
                                 let mut substitution = content_queue.split_off(n);
+                                let end = front + n + content.len();
                                 let preface = match substitution.len() > content.len() {
-                                    true => Some(substitution.split_off(content.len())),
+                                    true => {
+                                        Some((substitution.split_off(content.len()), end..back))
+                                    }
                                     false => None,
                                 };
-                                (preface, Some(substitution))
+                                back = front + n;
+                                (preface, Some((substitution, back..end)))
                             }
                             None => (None, None),
                         },
                     };
 
-                    if let Some(substitution) = substitution {
-                        if let Some(preface) = preface {
-                            match stack.last() {
+                    if let Some((substitution, node_range)) = substitution {
+                        if let Some((preface, preface_range)) = preface {
+                            match stack.last().map(|located| &located.pair) {
                                 Some(&ContentMatchPair(PromptToken::Literal(_), _)) | None => {
                                     let pair = ContentMatchPair(PromptToken::None, Some(preface));
-                                    stack.push(pair);
+                                    let rx_vacancy = rx_range.start..rx_range.start;
+                                    stack.push(LocatedMatchPair::new(
+                                        pair,
+                                        rx_vacancy,
+                                        preface_range,
+                                    ));
                                 }
                                 _ => (),
                             };
@@ -1035,12 +1345,10 @@ impl<'a> Validator<'a> {
                             PromptToken::Literal(content.to_string()),
                             Some(substitution),
                         );
-                        stack.push(match_pair);
+                        stack.push(LocatedMatchPair::new(match_pair, rx_range, node_range));
                     } else {
-                        stack.push(ContentMatchPair(
-                            PromptToken::Literal(content.to_string()),
-                            None,
-                        ));
+                        let pair = ContentMatchPair(PromptToken::Literal(content.to_string()), None);
+                        stack.push(LocatedMatchPair::new(pair, rx_range, vacancy));
                     }
                 }
                 PromptToken::None => {
@@ -1057,13 +1365,20 @@ impl<'a> Validator<'a> {
         }
 
         if !content_queue.is_empty() {
-            match (left_stack.last(), right_stack.last()) {
+            let rx_vacancy = match left_stack.last() {
+                Some(located) => located.rx_range.end..located.rx_range.end,
+                None => 0..0,
+            };
+            match (
+                left_stack.last().map(|located| &located.pair),
+                right_stack.last().map(|located| &located.pair),
+            ) {
                 (
                     Some(&ContentMatchPair(PromptToken::Literal(_), _)),
                     Some(&ContentMatchPair(PromptToken::Literal(_), _)),
                 ) => {
                     let pair = ContentMatchPair(PromptToken::None, Some(content_queue));
-                    left_stack.push(pair);
+                    left_stack.push(LocatedMatchPair::new(pair, rx_vacancy, front..back));
                 }
                 _ => (),
             }
@@ -1077,9 +1392,18 @@ impl<'a> Validator<'a> {
 
     /// Returns a vector of PromptToken parsed from the given string.
     fn tokenize_prompts(content: &String) -> HowserResult<Vec<PromptToken>> {
+        Ok(Self::locate_prompts(content)?
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect())
+    }
+
+    /// Returns the PromptTokens parsed from the given string along with their byte ranges.
+    fn locate_prompts(content: &String) -> HowserResult<Vec<(PromptToken, Range<usize>)>> {
         trace!("tokenize_prompts()");
         let prompt_pattern = Regex::new(CONTENT_PROMPT_PATTERN)?;
         let mut tail = content.to_string();
+        let mut offset = 0;
         let mut tokens = Vec::new();
 
         while !tail.is_empty() {
@@ -1088,8 +1412,9 @@ impl<'a> Validator<'a> {
                 let (matched, remainder) = temp_tail.split_at(location.end());
 
                 if location.start() > 0 {
-                    tokens.push(PromptToken::Literal(
-                        matched[0..location.start()].to_string(),
+                    tokens.push((
+                        PromptToken::Literal(matched[0..location.start()].to_string()),
+                        offset..offset + location.start(),
                     ));
                 }
                 let token = match location.as_str() {
@@ -1097,10 +1422,11 @@ impl<'a> Validator<'a> {
                     OPTIONAL_PROMPT => PromptToken::Optional,
                     _ => PromptToken::None,
                 };
-                tokens.push(token);
+                tokens.push((token, offset + location.start()..offset + location.end()));
+                offset += location.end();
                 tail = String::from(remainder);
             } else {
-                tokens.push(PromptToken::Literal(tail.clone()));
+                tokens.push((PromptToken::Literal(tail.clone()), offset..offset + tail.len()));
                 tail.clear();
             }
         }
@@ -1113,14 +1439,12 @@ impl<'a> Validator<'a> {
 /// Takes into account heading levels and list types.
 pub fn types_match(node: &Node, other: &Node) -> HowserResult<bool> {
     trace!("types_match()");
-    let node_type = node.get_cmark_type()?;
-    let rx_type = other.get_cmark_type()?;
+    let node_type = node.type_string();
+    let rx_type = other.type_string();
 
     if node_type == rx_type {
-        match (node, other) {
-            (Node::Heading(node_heading), Node::Heading(ref rx_heading)) => {
-                let node_level = node_heading.get_level();
-                let rx_level = rx_heading.get_level();
+        match (node.kind(), other.kind()) {
+            (&NodeKind::Heading(node_level), &NodeKind::Heading(rx_level)) => {
                 if node_level == rx_level {
                     info!("types_match:: Headings match");
                     return Ok(true);
@@ -1129,9 +1453,7 @@ pub fn types_match(node: &Node, other: &Node) -> HowserResult<bool> {
                     return Ok(false);
                 }
             }
-            (Node::List(ref node_list), Node::List(ref rx_list)) => {
-                let node_list_type = node_list.get_list_type()?;
-                let rx_list_type = rx_list.get_list_type()?;
+            (&NodeKind::List(node_list_type), &NodeKind::List(rx_list_type)) => {
                 if node_list_type == rx_list_type {
                     info!("types_match:: List types match");
                     return Ok(true);
@@ -1158,22 +1480,24 @@ pub fn types_match(node: &Node, other: &Node) -> HowserResult<bool> {
 mod tests {
     use super::env_logger;
     use super::Validator;
-    use data::ContentMatchPair;
-    use document::Document;
-    use doogie::parse_document;
+    use ast::Span;
+    use data::{Alignment, ContentMatchPair};
+    use document::{Document, Prescription};
+    use errors::{ErrorCode, Severity};
+    use backends::parse_document;
     use helpers::test::strategies::content;
     use helpers::test::strategies::helpers::*;
 
     #[test]
     fn test_literal_paragraph_match() {
         let text = "The quick brown fox jumps over the dog.".to_string();
-        let rx_root = parse_document(&text);
-        let doc_root = parse_document(&text);
-        let rx = Document::new(&rx_root, None)
+        let rx_root = parse_document(&text).unwrap();
+        let doc_root = parse_document(&text).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&doc_root, None).unwrap();
+        let doc = Document::new(doc_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1181,15 +1505,34 @@ mod tests {
         assert!(report.is_empty());
     }
 
+    #[test]
+    fn test_extra_content_can_be_allowed() {
+        let rx_text = "# -!!-\n\n## Usage\n\n-!!-\n";
+        let doc_text = "# Howser\n\n## Usage\n\nRun it.\n\nAn extra paragraph.\n";
+        let validator = |allow: bool| {
+            Validator::new(
+                Prescription::from_text(rx_text, None).unwrap(),
+                Document::from_text(doc_text, None).unwrap(),
+            ).with_extra_content_allowed(allow)
+        };
+
+        let problems = validator(false).validate().unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].code(), ErrorCode::SuperfluousBlockContent);
+        assert!(validator(true).validate().unwrap().is_empty());
+    }
+
     #[test]
     fn test_literal_paragraph_mismatch() {
-        let rx_root = parse_document(&"The quick brown fox jumps over the dog.".to_string());
-        let doc_root = parse_document(&"The slow brown fox jumps over the dog.".to_string());
-        let rx = Document::new(&rx_root, None)
+        let rx_root =
+            parse_document(&"The quick brown fox jumps over the dog.".to_string()).unwrap();
+        let doc_root =
+            parse_document(&"The slow brown fox jumps over the dog.".to_string()).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&doc_root, None).unwrap();
+        let doc = Document::new(doc_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1200,13 +1543,13 @@ mod tests {
     #[test]
     fn test_literal_mixed_paragraph_match() {
         let text = "*Compile* the code `let a = 12;` using `cargo build`.".to_string();
-        let rx_root = parse_document(&text);
-        let doc_root = parse_document(&text);
-        let rx = Document::new(&rx_root, None)
+        let rx_root = parse_document(&text).unwrap();
+        let doc_root = parse_document(&text).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&doc_root, None).unwrap();
+        let doc = Document::new(doc_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1217,13 +1560,15 @@ mod tests {
     #[test]
     fn test_literal_mixed_paragraph_mismatch() {
         let rx_root =
-            parse_document(&"*Compile* the code `let a = 12;` using `cargo build`.".to_string());
-        let doc_root = parse_document(&"*Compile* the code `let a = 12;`.".to_string());
-        let rx = Document::new(&rx_root, None)
+            parse_document(&"*Compile* the code `let a = 12;` using `cargo build`.".to_string())
+                .unwrap();
+        let doc_root =
+            parse_document(&"*Compile* the code `let a = 12;`.".to_string()).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&doc_root, None).unwrap();
+        let doc = Document::new(doc_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1233,13 +1578,14 @@ mod tests {
 
     #[test]
     fn test_literal_mixed_paragraph_superflous_content() {
-        let rx_root = parse_document(&"-!!- (-!!-)[-!!-]".to_string());
-        let doc_root = parse_document(&"Joe Schmoe <jschmoe@polysync.io> blargh".to_string());
-        let rx = Document::new(&rx_root, None)
+        let rx_root = parse_document(&"-!!- (-!!-)[-!!-]".to_string()).unwrap();
+        let doc_root =
+            parse_document(&"Joe Schmoe <jschmoe@polysync.io> blargh".to_string()).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&doc_root, None).unwrap();
+        let doc = Document::new(doc_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1249,21 +1595,25 @@ mod tests {
 
     #[test]
     fn test_prompted_text_match() {
-        let rx_root = parse_document(&"The quick brown fox -!!- over-??-.".to_string());
+        let rx_root_1 =
+            parse_document(&"The quick brown fox -!!- over-??-.".to_string()).unwrap();
+        let rx_root_2 =
+            parse_document(&"The quick brown fox -!!- over-??-.".to_string()).unwrap();
         let match_1_root =
-            parse_document(&"The quick brown fox jumps overthrows the dog.".to_string());
-        let match_2_root = parse_document(&"The quick brown fox slinks over.".to_string());
+            parse_document(&"The quick brown fox jumps overthrows the dog.".to_string()).unwrap();
+        let match_2_root =
+            parse_document(&"The quick brown fox slinks over.".to_string()).unwrap();
 
-        let rx_1 = Document::new(&rx_root, None)
+        let rx_1 = Document::new(rx_root_1, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let rx_2 = Document::new(&rx_root, None)
+        let rx_2 = Document::new(rx_root_2, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc_1 = Document::new(&match_1_root, None).unwrap();
-        let doc_2 = Document::new(&match_2_root, None).unwrap();
+        let doc_1 = Document::new(match_1_root, None).unwrap();
+        let doc_2 = Document::new(match_2_root, None).unwrap();
 
         let validator_1 = Validator::new(rx_1, doc_1);
         let validator_2 = Validator::new(rx_2, doc_2);
@@ -1277,13 +1627,14 @@ mod tests {
 
     #[test]
     fn test_prompted_text_mismatch() {
-        let rx_root = parse_document(&"The quick brown fox -!!- over-??-.".to_string());
-        let match_root = parse_document(&"The quick brown fox over.".to_string());
-        let rx = Document::new(&rx_root, None)
+        let rx_root =
+            parse_document(&"The quick brown fox -!!- over-??-.".to_string()).unwrap();
+        let match_root = parse_document(&"The quick brown fox over.".to_string()).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&match_root, None).unwrap();
+        let doc = Document::new(match_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1293,13 +1644,13 @@ mod tests {
 
     #[test]
     fn test_optional_inline_prompts_are_optional() {
-        let rx_root = parse_document(&"* [foo-!!-](-!!-)-??-**Foo**".to_string());
-        let match_root = parse_document(&"* [foobar](Fux)**Foo**".to_string());
-        let rx = Document::new(&rx_root, None)
+        let rx_root = parse_document(&"* [foo-!!-](-!!-)-??-**Foo**".to_string()).unwrap();
+        let match_root = parse_document(&"* [foobar](Fux)**Foo**".to_string()).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&match_root, None).unwrap();
+        let doc = Document::new(match_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1310,13 +1661,13 @@ mod tests {
     #[test]
     fn test_literal_code_match() {
         let text = "`let my_num: u32 = 42;`".to_string();
-        let rx_root = parse_document(&text);
-        let doc_root = parse_document(&text);
-        let rx = Document::new(&rx_root, None)
+        let rx_root = parse_document(&text).unwrap();
+        let doc_root = parse_document(&text).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&doc_root, None).unwrap();
+        let doc = Document::new(doc_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1326,13 +1677,13 @@ mod tests {
 
     #[test]
     fn test_literal_code_mismatch() {
-        let rx_root = parse_document(&"`let my_num: u32 = 42;`".to_string());
-        let doc_root = parse_document(&"`let my_num: u32 = 13;`".to_string());
-        let rx = Document::new(&rx_root, None)
+        let rx_root = parse_document(&"`let my_num: u32 = 42;`".to_string()).unwrap();
+        let doc_root = parse_document(&"`let my_num: u32 = 13;`".to_string()).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&doc_root, None).unwrap();
+        let doc = Document::new(doc_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1342,20 +1693,21 @@ mod tests {
 
     #[test]
     fn test_prompted_code_match() {
-        let rx_root = parse_document(&"`let -!!- = 42-??-;`".to_string());
-        let match_1_root = parse_document(&"`let my_num: u32 = 42;`".to_string());
-        let match_2_root = parse_document(&"`let the_answer = 4200;`".to_string());
+        let rx_root_1 = parse_document(&"`let -!!- = 42-??-;`".to_string()).unwrap();
+        let rx_root_2 = parse_document(&"`let -!!- = 42-??-;`".to_string()).unwrap();
+        let match_1_root = parse_document(&"`let my_num: u32 = 42;`".to_string()).unwrap();
+        let match_2_root = parse_document(&"`let the_answer = 4200;`".to_string()).unwrap();
 
-        let rx_1 = Document::new(&rx_root, None)
+        let rx_1 = Document::new(rx_root_1, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let rx_2 = Document::new(&rx_root, None)
+        let rx_2 = Document::new(rx_root_2, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc_1 = Document::new(&match_1_root, None).unwrap();
-        let doc_2 = Document::new(&match_2_root, None).unwrap();
+        let doc_1 = Document::new(match_1_root, None).unwrap();
+        let doc_2 = Document::new(match_2_root, None).unwrap();
 
         let validator_1 = Validator::new(rx_1, doc_1);
         let validator_2 = Validator::new(rx_2, doc_2);
@@ -1369,13 +1721,13 @@ mod tests {
 
     #[test]
     fn test_prompted_code_mismatch() {
-        let rx_root = parse_document(&"`let -!!- = 42;`".to_string());
-        let match_root = parse_document(&"`let = 42;`".to_string());
-        let rx = Document::new(&rx_root, None)
+        let rx_root = parse_document(&"`let -!!- = 42;`".to_string()).unwrap();
+        let match_root = parse_document(&"`let = 42;`".to_string()).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&match_root, None).unwrap();
+        let doc = Document::new(match_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1385,13 +1737,13 @@ mod tests {
 
     #[test]
     fn test_mandatory_wildcard_paragraph_match() {
-        let rx_root = parse_document(&"-!!-".to_string());
-        let match_root = parse_document(&"Literally any content here".to_string());
-        let rx = Document::new(&rx_root, None)
+        let rx_root = parse_document(&"-!!-".to_string()).unwrap();
+        let match_root = parse_document(&"Literally any content here".to_string()).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&match_root, None).unwrap();
+        let doc = Document::new(match_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1401,20 +1753,20 @@ mod tests {
 
     #[test]
     fn test_optional_wildcard_paragraph_match() {
-        let first_rx_root = parse_document(&"-??-".to_string());
-        let second_rx_root = parse_document(&"-??-".to_string());
-        let match_root = parse_document(&"Literally any content here".to_string());
-        let empty_match_root = parse_document(&String::new());
-        let rx_1 = Document::new(&first_rx_root, None)
+        let first_rx_root = parse_document(&"-??-".to_string()).unwrap();
+        let second_rx_root = parse_document(&"-??-".to_string()).unwrap();
+        let match_root = parse_document(&"Literally any content here".to_string()).unwrap();
+        let empty_match_root = parse_document(&String::new()).unwrap();
+        let rx_1 = Document::new(first_rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let rx_2 = Document::new(&second_rx_root, None)
+        let rx_2 = Document::new(second_rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&match_root, None).unwrap();
-        let empty_doc = Document::new(&empty_match_root, None).unwrap();
+        let doc = Document::new(match_root, None).unwrap();
+        let empty_doc = Document::new(empty_match_root, None).unwrap();
         let validator_1 = Validator::new(rx_1, doc);
         let validator_2 = Validator::new(rx_2, empty_doc);
 
@@ -1424,13 +1776,13 @@ mod tests {
 
     #[test]
     fn test_mandatory_wildcard_paragraph_mismatch() {
-        let rx_root = parse_document(&"-!!-".to_string());
-        let match_root = parse_document(&String::new());
-        let rx = Document::new(&rx_root, None)
+        let rx_root = parse_document(&"-!!-".to_string()).unwrap();
+        let match_root = parse_document(&String::new()).unwrap();
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&match_root, None).unwrap();
+        let doc = Document::new(match_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1449,14 +1801,14 @@ mod tests {
 
         let match_text = "Some random first paragraph\n\nSome random second paragraph";
 
-        let rx_root = parse_document(&rx_text.to_string());
-        let match_root = parse_document(&match_text.to_string());
+        let rx_root = parse_document(&rx_text.to_string()).unwrap();
+        let match_root = parse_document(&match_text.to_string()).unwrap();
 
-        let rx = Document::new(&rx_root, None)
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&match_root, None).unwrap();
+        let doc = Document::new(match_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1472,14 +1824,14 @@ mod tests {
 
         let match_text = "Some random first paragraph\n\nSome random second paragraph";
 
-        let rx_root = parse_document(&rx_text.to_string());
-        let match_root = parse_document(&match_text.to_string());
+        let rx_root = parse_document(&rx_text.to_string()).unwrap();
+        let match_root = parse_document(&match_text.to_string()).unwrap();
 
-        let rx = Document::new(&rx_root, None)
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&match_root, None).unwrap();
+        let doc = Document::new(match_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1495,14 +1847,14 @@ mod tests {
 
         let match_text = "# A Header\n\nSome content";
 
-        let rx_root = parse_document(&rx_text.to_string());
-        let match_root = parse_document(&match_text.to_string());
+        let rx_root = parse_document(&rx_text.to_string()).unwrap();
+        let match_root = parse_document(&match_text.to_string()).unwrap();
 
-        let rx = Document::new(&rx_root, None)
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&match_root, None).unwrap();
+        let doc = Document::new(match_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1519,14 +1871,14 @@ mod tests {
         let match_text =
             "Some random first paragraph\n\nSome random second paragraph\n\n# And a heading";
 
-        let rx_root = parse_document(&rx_text.to_string());
-        let match_root = parse_document(&match_text.to_string());
+        let rx_root = parse_document(&rx_text.to_string()).unwrap();
+        let match_root = parse_document(&match_text.to_string()).unwrap();
 
-        let rx = Document::new(&rx_root, None)
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&match_root, None).unwrap();
+        let doc = Document::new(match_root, None).unwrap();
         let validator = Validator::new(rx, doc);
 
         let report = validator.validate().unwrap();
@@ -1539,14 +1891,14 @@ mod tests {
         let rx_text = "-!!-\n-!!-my dear-??-";
         let match_text = "Elementary my dear Watson";
 
-        let rx_root = parse_document(&rx_text.to_string());
-        let match_root = parse_document(&match_text.to_string());
+        let rx_root = parse_document(&rx_text.to_string()).unwrap();
+        let match_root = parse_document(&match_text.to_string()).unwrap();
 
-        let rx = Document::new(&rx_root, None)
+        let rx = Document::new(rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc = Document::new(&match_root, None).unwrap();
+        let doc = Document::new(match_root, None).unwrap();
 
         let validator = Validator::new(rx, doc);
 
@@ -1561,21 +1913,21 @@ mod tests {
         let first_match_text = "Elementary my dear Watson";
         let second_match_text = "";
 
-        let first_rx_root = parse_document(&rx_text.to_string());
-        let second_rx_root = parse_document(&rx_text.to_string());
-        let first_match_root = parse_document(&first_match_text.to_string());
-        let second_match_root = parse_document(&second_match_text.to_string());
+        let first_rx_root = parse_document(&rx_text.to_string()).unwrap();
+        let second_rx_root = parse_document(&rx_text.to_string()).unwrap();
+        let first_match_root = parse_document(&first_match_text.to_string()).unwrap();
+        let second_match_root = parse_document(&second_match_text.to_string()).unwrap();
 
-        let first_rx = Document::new(&first_rx_root, None)
+        let first_rx = Document::new(first_rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let second_rx = Document::new(&second_rx_root, None)
+        let second_rx = Document::new(second_rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let first_doc = Document::new(&first_match_root, None).unwrap();
-        let second_doc = Document::new(&second_match_root, None).unwrap();
+        let first_doc = Document::new(first_match_root, None).unwrap();
+        let second_doc = Document::new(second_match_root, None).unwrap();
 
         let first_validator = Validator::new(first_rx, first_doc);
         let second_validator = Validator::new(second_rx, second_doc);
@@ -1595,21 +1947,21 @@ mod tests {
         let rx_text = "-!!-\n-!!-my dear-??-";
         let match_text = "my dear";
 
-        let first_rx_root = parse_document(&rx_text.to_string());
-        let second_rx_root = parse_document(&rx_text.to_string());
-        let doc_root = parse_document(&match_text.to_string());
-        let empty_root = parse_document(&String::new());
+        let first_rx_root = parse_document(&rx_text.to_string()).unwrap();
+        let second_rx_root = parse_document(&rx_text.to_string()).unwrap();
+        let doc_root = parse_document(&match_text.to_string()).unwrap();
+        let empty_root = parse_document(&String::new()).unwrap();
 
-        let first_rx = Document::new(&first_rx_root, None)
+        let first_rx = Document::new(first_rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let second_rx = Document::new(&second_rx_root, None)
+        let second_rx = Document::new(second_rx_root, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let first_doc = Document::new(&doc_root, None).unwrap();
-        let second_doc = Document::new(&empty_root, None).unwrap();
+        let first_doc = Document::new(doc_root, None).unwrap();
+        let second_doc = Document::new(empty_root, None).unwrap();
 
         let first_validator = Validator::new(first_rx, first_doc);
         let second_validator = Validator::new(second_rx, second_doc);
@@ -1631,21 +1983,21 @@ mod tests {
         let match_text_1 = "* Foo Foo";
         let match_text_2 = "* Foo Foo\n* Bar Bar\n* Bar Baz";
 
-        let rx_root_1 = parse_document(&rx_text.to_string());
-        let rx_root_2 = parse_document(&rx_text.to_string());
-        let doc_root_1 = parse_document(&match_text_1.to_string());
-        let doc_root_2 = parse_document(&match_text_2.to_string());
+        let rx_root_1 = parse_document(&rx_text.to_string()).unwrap();
+        let rx_root_2 = parse_document(&rx_text.to_string()).unwrap();
+        let doc_root_1 = parse_document(&match_text_1.to_string()).unwrap();
+        let doc_root_2 = parse_document(&match_text_2.to_string()).unwrap();
 
-        let rx_1 = Document::new(&rx_root_1, None)
+        let rx_1 = Document::new(rx_root_1, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let rx_2 = Document::new(&rx_root_2, None)
+        let rx_2 = Document::new(rx_root_2, None)
             .unwrap()
             .into_prescription()
             .unwrap();
-        let doc_1 = Document::new(&doc_root_1, None).unwrap();
-        let doc_2 = Document::new(&doc_root_2, None).unwrap();
+        let doc_1 = Document::new(doc_root_1, None).unwrap();
+        let doc_2 = Document::new(doc_root_2, None).unwrap();
 
         let validator_1 = Validator::new(rx_1, doc_1);
         let validator_2 = Validator::new(rx_2, doc_2);
@@ -1660,6 +2012,152 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_content_mismatch_is_located() {
+        let rx = Prescription::from_text("Say -!!- loudly", None).unwrap();
+        let doc = Document::from_text("\nSay hello quietly", None).unwrap();
+        let report = Validator::new(rx, doc).validate().unwrap();
+
+        assert_eq!(
+            report[0].prescription_location().unwrap().span,
+            Span::new(1, 9, 1, 15)
+        );
+        assert_eq!(
+            report[0].document_location().unwrap().span,
+            Span::new(2, 17, 2, 17)
+        );
+    }
+
+    #[test]
+    fn test_alignment_records_skips_and_repetitions() {
+        let rx = Prescription::from_text("# Title\n\n-??-\n\n* -!!-\n* -\"\"-", None).unwrap();
+        let doc = Document::from_text("# Title\n\n* One\n* Two\n* Three", None).unwrap();
+        let validator = Validator::new(rx, doc);
+        assert!(validator.validate().unwrap().is_empty());
+
+        let alignments: Vec<Alignment> = validator
+            .alignment()
+            .into_iter()
+            .filter(|step| step.alignment != Alignment::Matched)
+            .map(|step| step.alignment)
+            .collect();
+        assert_eq!(
+            alignments,
+            vec![
+                Alignment::Mismatched(ErrorCode::TypeMismatch),
+                Alignment::Skipped,
+                Alignment::Repeated(3),
+            ]
+        );
+    }
+
+    fn validate_codes(rx: &str, doc: &str) -> Vec<ErrorCode> {
+        let rx = Prescription::from_text(rx, None).unwrap();
+        let doc = Document::from_text(doc, None).unwrap();
+        Validator::new(rx, doc)
+            .validate()
+            .unwrap()
+            .iter()
+            .map(|problem| problem.code())
+            .collect()
+    }
+
+    #[test]
+    fn test_suppressions_cover_their_scope() {
+        let rx = "# Title\n\n## Usage\n\n-!!-\n\n## License\n\nMIT\n";
+
+        let next_block = "# Title\n\n<!-- howser-suppress-next HX0006 -->\n\n## Use\n\n\
+                          Run it.\n\n## License\n\nMIT\n";
+        assert!(validate_codes(rx, next_block).is_empty());
+
+        let section = "# Title\n\n## Usage\n\nRun it.\n\n## License\n\n\
+                       <!-- howser-suppress-section HX0006 -->\n\nApache\n";
+        assert!(validate_codes(rx, section).is_empty());
+
+        let wrong_section = "# Title\n\n## Usage\n\n<!-- howser-suppress-section HX0006 -->\n\n\
+                             Run it.\n\n## License\n\nApache\n";
+        assert_eq!(
+            validate_codes(rx, wrong_section),
+            vec![ErrorCode::TextualContentMismatch]
+        );
+
+        let file = "<!-- howser-suppress-file HX0001 -->\n\n# Title\n";
+        assert!(validate_codes(rx, file).is_empty());
+    }
+
+    #[test]
+    fn test_problems_after_a_suppression_are_reported() {
+        let rx = "# Title\n\n## Usage\n\n-!!-\n\n## License\n\nMIT\n";
+        let doc = "# Title\n\n<!-- howser-suppress-next HX0006 -->\n\n## Use\n\n\
+                   Run it.\n\n## License\n\nApache\n";
+
+        assert_eq!(
+            validate_codes(rx, doc),
+            vec![ErrorCode::TextualContentMismatch]
+        );
+
+        let missing = "# Title\n\n<!-- howser-suppress-next HX0006 -->\n\n## Use\n\nRun it.\n";
+        assert_eq!(
+            validate_codes(rx, missing),
+            vec![ErrorCode::MissingMandatoryBlock]
+        );
+    }
+
+    #[test]
+    fn test_unused_suppressions_are_reported() {
+        let rx = "# Title\n\nSome text.\n";
+        let doc = "# Title\n\n<!-- howser-suppress-next HX0006 HX9999 -->\n\nSome text.\n";
+
+        assert_eq!(
+            validate_codes(rx, doc),
+            vec![ErrorCode::UnusedSuppression, ErrorCode::UnusedSuppression]
+        );
+    }
+
+    #[test]
+    fn test_only_suppressed_codes_count_as_used() {
+        let rx = "# Title\n\n## Usage\n\n-!!-\n\n## License\n\nMIT\n";
+        let partly_used = "# Title\n\n## Usage\n\nRun it.\n\n## License\n\n\
+                           <!-- howser-suppress-next HX0005, HX0006 -->\n\nApache\n";
+
+        let rx = Prescription::from_text(rx, None).unwrap();
+        let doc = Document::from_text(partly_used, None).unwrap();
+        let problems = Validator::new(rx, doc).validate().unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].code(), ErrorCode::UnusedSuppression);
+        assert!(problems[0].short_msg().contains("No HX0005 problem"));
+    }
+
+    #[test]
+    fn test_missing_recommended_elements_are_warnings() {
+        let rx = "# Title\n\n## Examples\n\n-++-\n\n## License\n\n-!!-\n";
+
+        let complete = "# Title\n\n## Examples\n\nRun it.\n\n## License\n\nMIT\n";
+        assert!(validate_codes(rx, complete).is_empty());
+
+        let missing = "# Title\n\n## Examples\n\n## License\n\nMIT\n";
+        assert_eq!(
+            validate_codes(rx, missing),
+            vec![ErrorCode::MissingRecommendedBlock]
+        );
+
+        let invalid = "# Title\n\n## Examples\n\n## License\n";
+        let codes = validate_codes(rx, invalid);
+        assert_eq!(
+            codes,
+            vec![
+                ErrorCode::MissingMandatoryBlock,
+                ErrorCode::MissingRecommendedBlock,
+            ]
+        );
+        assert_eq!(codes[0].severity(), Severity::Error);
+        assert_eq!(codes[1].severity(), Severity::Warning);
+
+        let suppressed = "# Title\n\n## Examples\n\n<!-- howser-suppress-next HX0015 -->\n\n\
+                          ## License\n\nMIT\n";
+        assert!(validate_codes(rx, suppressed).is_empty());
+    }
+
     proptest! {
         #[test]
         /// Tests that some textual content containing Rx tokens is correctly parsed into prompts and literals.
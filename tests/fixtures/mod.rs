@@ -36,6 +36,15 @@ impl PharmacyFixture {
         });
     }
 
+    pub fn add_unsound_spec(&mut self) {
+        let rx = UnsoundPrescriptionFixture::new();
+        let doc = rx.make_document();
+        self.add_spec(SpecFixture {
+            prescription: Box::new(rx),
+            document: Box::new(doc),
+        });
+    }
+
     pub fn add_missing_doc_spec(&mut self) {
         let rx = ValidPrescriptionFixture::new();
         let doc = MissingDocumentFixture::new();
@@ -124,6 +133,42 @@ impl Document for ValidPrescriptionFixture {
     }
 }
 
+/// A prescription with two problems that the linter reports.
+struct UnsoundPrescriptionFixture {
+    content: String,
+    file: NamedTempFile,
+}
+
+impl UnsoundPrescriptionFixture {
+    fn new() -> Self {
+        UnsoundPrescriptionFixture {
+            content: "-\"\"-\n\nLorem ipsum -!- dolor sit amet.\n".to_string(),
+            file: NamedTempFile::new().unwrap(),
+        }
+    }
+
+    fn make_document(&self) -> DocumentFixture {
+        DocumentFixture {
+            content: "Lorem ipsum dolor sit amet.\n".to_string(),
+            file: NamedTempFile::new().unwrap(),
+        }
+    }
+}
+
+impl Document for UnsoundPrescriptionFixture {
+    fn get_content(&self) -> String {
+        self.content.clone()
+    }
+
+    fn get_path(&self) -> String {
+        String::from(self.file.path().to_str().unwrap())
+    }
+
+    fn persist(&self) {
+        fs::write(self.get_path(), self.get_content()).unwrap();
+    }
+}
+
 struct MissingPrescriptionFixture {
     path: String,
 }
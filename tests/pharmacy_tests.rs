@@ -55,39 +55,85 @@ fn test_subcommand_help_of_pharmacy_command() {
 }
 
 /// * ##### Getting help info on the pharmacy validate subcommand
-///     * **When** Howser is run with the arguments "pharmacy" and "validate"
+///     * **When** Howser is run with the arguments "pharmacy", "validate" and "--help"
 ///     * **Then** the user shall see some help information
-///     * **And** the help information shall describe a mandatory "pharmacy" argument
+///     * **And** the help information shall describe an optional "pharmacy" argument
 ///     * **And** the help information shall describe an optional "--fail-early" option
 ///     * **And** the help information shall describe an optional "--verbose" option
 #[test]
 fn test_pharmacy_validate_subcommand_help() {
     Assert::main_binary()
-        .with_args(&["pharmacy", "validate"])
-        .fails()
+        .with_args(&["pharmacy", "validate", "--help"])
+        .succeeds()
         .and()
-        .stderr()
+        .stdout()
         .contains("<PHARMACY>    The .toml file containing the documents to process.")
         .unwrap();
 }
 
 /// * ##### Getting help info on the pharmacy check subcommand
-///     * **When** Howser is run with the arguments "pharmacy" and "check"
+///     * **When** Howser is run with the arguments "pharmacy", "check" and "--help"
 ///     * **Then** the user shall see some help information
-///     * **And** the help information shall describe a mandatory "pharmacy" argument
+///     * **And** the help information shall describe an optional "pharmacy" argument
 ///     * **And** the help information shall describe an optional "--fail-early" option
 ///     * **And** the help information shall describe an optional "--verbose" option
 #[test]
 fn test_pharmacy_check_subcommand_help() {
     Assert::main_binary()
-        .with_args(&["pharmacy", "check"])
-        .fails()
+        .with_args(&["pharmacy", "check", "--help"])
+        .succeeds()
         .and()
-        .stderr()
+        .stdout()
         .contains("<PHARMACY>    The .toml file containing the documents to process.")
         .unwrap();
 }
 
+/// * ##### Checking prescriptions with a pharmacy file
+///     * **When** Howser is run with the arguments "pharmacy" and "check" and the pharmacy file path
+///     * **Then** the user shall see a warning for every problem in each prescription file that does not conform to the Rx spec
+#[test]
+fn test_pharmacy_check_reports_all_warnings() {
+    let mut pharmacy = PharmacyFixture::new();
+    let unsound_count = 3;
+    for _ in 0..10 {
+        pharmacy.add_matched_spec();
+    }
+    for _ in 0..unsound_count {
+        pharmacy.add_unsound_spec();
+    }
+
+    Assert::main_binary()
+        .with_args(&["pharmacy", "check", pharmacy.get_path()])
+        .fails_with(2)
+        .stdout()
+        .satisfies(
+            move |out| {
+                out.matches("[HX0009]").count() == unsound_count
+                    && out.matches("[HX0013]").count() == unsound_count
+            },
+            "Wrong number of warning messages",
+        )
+        .unwrap();
+}
+
+/// * ##### Success Message when checking
+///     * **Given** that only prescription files that conform to the Rx spec are present in the "Specs" section of the "pharmacy.toml" file
+///     * **When** Howser is run with the arguments "pharmacy" and "check" and the pharmacy file path
+///     * **Then** the user shall only see "Valid"
+#[test]
+fn test_pharmacy_check_success() {
+    let mut pharmacy = PharmacyFixture::new();
+    for _ in 0..10 {
+        pharmacy.add_matched_spec();
+    }
+
+    Assert::main_binary()
+        .with_args(&["pharmacy", "check", pharmacy.get_path()])
+        .stdout()
+        .contains("Valid")
+        .unwrap();
+}
+
 /// * ##### Validating documents with a pharmacy file
 ///     * **When** Howser is run with the arguments "pharmacy" and "validate" and the pharmacy file path
 ///     * **Then** the user shall only see a validation error message pertaining to each markdown file that do not match the corresponding prescription file
@@ -104,6 +150,7 @@ fn test_pharmacy_validate_failure() {
 
     Assert::main_binary()
         .with_args(&["pharmacy", "validate", pharmacy.get_path()])
+        .fails_with(2)
         .stdout()
         .satisfies(
             move |out| out.to_string().matches("Error").count() == failure_count,
@@ -127,6 +174,7 @@ fn test_pharmacy_validate_fail_early() {
 
     Assert::main_binary()
         .with_args(&["pharmacy", "validate", "--fail-early", pharmacy.get_path()])
+        .fails_with(2)
         .stdout()
         .satisfies(
             move |out| out.to_string().matches("Error").count() == 1,
@@ -135,6 +183,86 @@ fn test_pharmacy_validate_fail_early() {
         .unwrap();
 }
 
+/// * ##### Validating with several jobs
+///     * **When** Howser is run with the arguments "pharmacy" and "validate" and "--jobs" and the pharmacy file path
+///     * **Then** the user shall see the same validation error messages as when validating one document at a time
+#[test]
+fn test_pharmacy_validate_with_jobs() {
+    let mut pharmacy = PharmacyFixture::new();
+    let failure_count = 10;
+    for _ in 0..10 {
+        pharmacy.add_matched_spec();
+    }
+    for _ in 0..failure_count {
+        pharmacy.add_mismatched_spec();
+    }
+
+    Assert::main_binary()
+        .with_args(&["pharmacy", "validate", "--jobs", "4", pharmacy.get_path()])
+        .fails_with(2)
+        .stdout()
+        .satisfies(
+            move |out| out.to_string().matches("Error").count() == failure_count,
+            "Wrong number of error messages",
+        )
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&[
+            "pharmacy",
+            "validate",
+            "--jobs",
+            "4",
+            "--fail-early",
+            pharmacy.get_path(),
+        ])
+        .fails_with(2)
+        .stdout()
+        .satisfies(
+            move |out| out.to_string().matches("Error").count() == 1,
+            "Wrong number of error messages",
+        )
+        .unwrap();
+}
+
+/// * ##### Keeping earlier warnings when failing early
+///     * **Given** that the first markdown file lacks an element its prescription recommends
+///     * **And** the second markdown file does not match its prescription
+///     * **When** Howser is run with the arguments "pharmacy" and "validate" and "--fail-early"
+///     * **Then** the user shall see the warning about the first file along with the error about the second
+#[test]
+fn test_pharmacy_validate_fail_early_keeps_earlier_warnings() {
+    let directory = tempfile::tempdir().unwrap();
+    let write = |name: &str, text: &str| std::fs::write(directory.path().join(name), text).unwrap();
+    write("first.rx", "# -!!-\n\n## Examples\n\n-++-\n");
+    write("first.md", "# Howser\n\n## Examples\n");
+    write("second.rx", "# -!!-\n\n## Usage\n\n-!!-\n");
+    write("second.md", "# Howser\n\n### Usage\n\nRun it.\n");
+    write(
+        "Pharmacy.toml",
+        &format!(
+            "[Specs]\n\"{0}/first.rx\" = \"{0}/first.md\"\n\"{0}/second.rx\" = \"{0}/second.md\"\n",
+            directory.path().display()
+        ),
+    );
+    let pharmacy = directory.path().join("Pharmacy.toml");
+
+    Assert::main_binary()
+        .with_args(&[
+            "pharmacy",
+            "validate",
+            "--fail-early",
+            pharmacy.to_str().unwrap(),
+        ])
+        .fails_with(2)
+        .stdout()
+        .satisfies(
+            |out| out.contains("[HX0015]") && out.contains("[HX0005]"),
+            "Problems found before failing early were dropped",
+        )
+        .unwrap();
+}
+
 /// * ##### Success Message when validating
 ///     * **Given** that only key-value pairs from the "matching" group are present in the "Specs" section of the "pharmacy.toml" file
 ///     * **When** Howser is run with the arguments "pharmacy" and "validate" and the pharmacy file path
@@ -196,3 +324,348 @@ fn test_pharmacy_validate_fails_when_missing_document() {
         .contains("entity not found")
         .unwrap();
 }
+
+/// * ##### Baselining existing problems
+///     * **Given** that some markdown files do not match their prescription files
+///     * **When** Howser is run with the arguments "pharmacy" and "validate" and "--write-baseline" and a baseline file path
+///     * **And** Howser is then run with the arguments "pharmacy" and "validate" and "--baseline" and the same baseline file path
+///     * **Then** the user shall only see "Valid"
+#[test]
+fn test_pharmacy_validate_with_baseline() {
+    let mut pharmacy = PharmacyFixture::new();
+    for _ in 0..3 {
+        pharmacy.add_matched_spec();
+        pharmacy.add_mismatched_spec();
+    }
+    let baseline = tempfile::NamedTempFile::new().unwrap();
+    let baseline_path = baseline.path().to_str().unwrap();
+
+    Assert::main_binary()
+        .with_args(&[
+            "pharmacy",
+            "validate",
+            "--write-baseline",
+            baseline_path,
+            pharmacy.get_path(),
+        ])
+        .stdout()
+        .contains("Recorded 3 problems")
+        .unwrap();
+    Assert::main_binary()
+        .with_args(&[
+            "pharmacy",
+            "validate",
+            "--baseline",
+            baseline_path,
+            pharmacy.get_path(),
+        ])
+        .stdout()
+        .satisfies(
+            |out| out.contains("Valid") && !out.contains("Error"),
+            "Baselined problems were reported",
+        )
+        .unwrap();
+}
+
+/// * ##### Replaying cached results
+///     * **Given** that some markdown files do not match their prescription files
+///     * **When** Howser is run twice with the arguments "pharmacy" and "validate" and "--cache-dir" and a cache directory
+///     * **Then** the user shall see the same validation error messages both times
+///     * **And** the cache directory shall hold a result for each document
+#[test]
+fn test_pharmacy_validate_with_cache() {
+    let mut pharmacy = PharmacyFixture::new();
+    let failure_count = 3;
+    for _ in 0..failure_count {
+        pharmacy.add_matched_spec();
+        pharmacy.add_mismatched_spec();
+    }
+    let cache = tempfile::tempdir().unwrap();
+    let cache_path = cache.path().to_str().unwrap();
+
+    for _ in 0..2 {
+        Assert::main_binary()
+            .with_args(&[
+                "pharmacy",
+                "validate",
+                "--cache-dir",
+                cache_path,
+                pharmacy.get_path(),
+            ])
+            .fails_with(2)
+            .stdout()
+            .satisfies(
+                move |out| out.to_string().matches("Error").count() == failure_count,
+                "Wrong number of error messages",
+            )
+            .unwrap();
+    }
+
+    let results = std::fs::read_dir(cache.path())
+        .unwrap()
+        .filter(|entry| {
+            entry.as_ref().unwrap().path().extension() == Some(std::ffi::OsStr::new("json"))
+        })
+        .count();
+    assert_eq!(results, failure_count * 2);
+}
+
+/// * ##### Validating only changed documents
+///     * **Given** that the pharmacy file and its files are committed to a git repository
+///     * **And** one markdown file has been edited since that commit
+///     * **When** Howser is run in the repository with the arguments "pharmacy" and "validate" and "--changed-since" and the commit
+///     * **Then** the user shall only see a validation error message for the edited markdown file
+#[test]
+fn test_pharmacy_validate_changed_since() {
+    let directory = tempfile::tempdir().unwrap();
+    let write = |name: &str, text: &str| std::fs::write(directory.path().join(name), text).unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(directory.path())
+            .args(&["-c", "user.name=Howser"])
+            .args(&["-c", "user.email=howser@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    write("README.rx", "# -!!-\n\n## Usage\n\n-!!-\n");
+    write("edited.md", "# Howser\n\n### Usage\n\nRun it.\n");
+    write("unchanged.md", "# Howser\n\n### Usage\n\nRun it.\n");
+    write(
+        "Pharmacy.toml",
+        "[Specs]\n\"README.rx\" = [\"edited.md\", \"unchanged.md\"]\n",
+    );
+    git(&["init", "--quiet"]);
+    git(&["add", "."]);
+    git(&["commit", "--quiet", "--message", "Add documents"]);
+    write("edited.md", "# Howser\n\n### Usage\n\nRun it again.\n");
+
+    // `main_binary` runs cargo, which needs the working directory to be inside this crate.
+    Assert::command(&[env!("CARGO_BIN_EXE_howser")])
+        .current_dir(directory.path())
+        .with_args(&[
+            "pharmacy",
+            "validate",
+            "--no-cache",
+            "--changed-since",
+            "HEAD",
+            "Pharmacy.toml",
+        ])
+        .fails_with(2)
+        .stdout()
+        .satisfies(
+            |out| out.matches("Error").count() == 1 && out.contains("edited.md"),
+            "Unchanged documents were validated",
+        )
+        .unwrap();
+}
+
+/// * ##### Validating a cargo workspace
+///     * **Given** that the pharmacy is kept in the package and workspace metadata of Cargo.toml files
+///     * **And** some member crates have markdown files that do not match their prescription files
+///     * **When** Howser is run with the arguments "pharmacy" and "validate" and the path of the workspace Cargo.toml
+///     * **Then** the user shall see a validation error message for each of those markdown files
+#[test]
+fn test_pharmacy_validate_cargo_workspace() {
+    let workspace = tempfile::tempdir().unwrap();
+    let write = |name: &str, text: &str| {
+        let path = workspace.path().join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    };
+    write(
+        "Cargo.toml",
+        "[workspace]\nmembers = [\"crates/*\"]\n\n\
+         [workspace.metadata.howser.Specs]\n\"README.rx\" = \"README.md\"\n",
+    );
+    write("README.rx", "# -!!-\n\n## Usage\n\n-!!-\n");
+    write("README.md", "# Workspace\n\n## Usage\n\nBuild it.\n");
+    for name in &["first", "second"] {
+        write(
+            &format!("crates/{}/Cargo.toml", name),
+            "[package]\nname = \"member\"\n\n\
+             [package.metadata.howser.Specs]\n\"../../README.rx\" = \"README.md\"\n",
+        );
+        write(
+            &format!("crates/{}/README.md", name),
+            "# Member\n\n### Usage\n\nBuild it.\n",
+        );
+    }
+    let manifest = workspace.path().join("Cargo.toml");
+
+    Assert::main_binary()
+        .with_args(&["pharmacy", "validate", manifest.to_str().unwrap()])
+        .fails_with(2)
+        .stdout()
+        .satisfies(
+            |out| out.matches("Error").count() == 2 && out.contains("crates/second/README.md"),
+            "Wrong validation error messages",
+        )
+        .unwrap();
+}
+
+/// * ##### Falling back on the metadata of Cargo.toml
+///     * **Given** that the working directory has no pharmacy file but has a Cargo.toml with a pharmacy in its metadata
+///     * **When** Howser is run with the arguments "pharmacy" and "validate" and no pharmacy file path
+///     * **Then** the documents named in the metadata of Cargo.toml shall be validated
+#[test]
+fn test_pharmacy_validate_falls_back_on_cargo_manifest() {
+    let directory = tempfile::tempdir().unwrap();
+    let write = |name: &str, text: &str| std::fs::write(directory.path().join(name), text).unwrap();
+    write(
+        "Cargo.toml",
+        "[package]\nname = \"crate\"\n\n\
+         [package.metadata.howser.Specs]\n\"README.rx\" = \"README.md\"\n",
+    );
+    write("README.rx", "# -!!-\n\n## Usage\n\n-!!-\n");
+    write("README.md", "# Crate\n\n### Usage\n\nBuild it.\n");
+
+    // `main_binary` runs cargo, which would find the manifest written here instead.
+    Assert::command(&[env!("CARGO_BIN_EXE_howser")])
+        .current_dir(directory.path())
+        .with_args(&["pharmacy", "validate", "--no-cache"])
+        .fails_with(2)
+        .stdout()
+        .contains("[HX0005]")
+        .unwrap();
+
+    write("Pharmacy.toml", "[Specs]\n");
+    Assert::command(&[env!("CARGO_BIN_EXE_howser")])
+        .current_dir(directory.path())
+        .with_args(&["pharmacy", "validate", "--no-cache"])
+        .succeeds()
+        .unwrap();
+}
+
+/// * ##### Linting a pharmacy file
+///     * **Given** a pharmacy file with a misspelled key and an entry naming a missing document
+///     * **When** Howser is run with the arguments "pharmacy" and "lint" and the pharmacy file path
+///     * **Then** the user shall see where each problem is, with a hint for the misspelled key
+#[test]
+fn test_pharmacy_lint_reports_schema_problems_and_missing_files() {
+    let directory = tempfile::tempdir().unwrap();
+    let pharmacy = directory.path().join("Pharmacy.toml");
+    let rx = directory.path().join("README.rx");
+    std::fs::write(&rx, "# -!!-\n").unwrap();
+    std::fs::write(
+        &pharmacy,
+        format!(
+            "[Specs]\n\"{}\" = \"missing.md\"\n\n[Spec]\n\"README.rx\" = \"README.md\"\n",
+            rx.display()
+        ),
+    ).unwrap();
+
+    Assert::main_binary()
+        .with_args(&["pharmacy", "lint", pharmacy.to_str().unwrap()])
+        .fails_with(2)
+        .stdout()
+        .satisfies(
+            |out| {
+                out.contains("[HX0016]") && out.contains("line 4, columns 1-6")
+                    && out.contains("Did you mean 'Specs'?")
+                    && out.contains("[HX0018]") && out.contains("missing.md does not exist.")
+            },
+            "Wrong pharmacy lint messages",
+        )
+        .unwrap();
+}
+
+/// * ##### Setting options for a pharmacy entry
+///     * **Given** a pharmacy file whose entry for an invalid document lowers its severity to warnings and names an owner
+///     * **When** Howser is run with the arguments "pharmacy" and "validate" and the pharmacy file path
+///     * **Then** the problem shall be reported as a warning naming the owner
+///     * **And** entries without a selected tag shall not be validated
+#[test]
+fn test_pharmacy_validate_applies_entry_options() {
+    let directory = tempfile::tempdir().unwrap();
+    let write = |name: &str, text: &str| std::fs::write(directory.path().join(name), text).unwrap();
+    write("README.rx", "# -!!-\n\n## Usage\n\n-!!-\n");
+    write("README.md", "# Howser\n\n### Usage\n\nRun it.\n");
+    write(
+        "Pharmacy.toml",
+        &format!(
+            "[Specs.\"{}/README.rx\"]\ndocuments = \"{}/README.md\"\nseverity = \"warning\"\n\
+             owner = \"docs-team\"\ntags = [\"readme\"]\n",
+            directory.path().display(),
+            directory.path().display()
+        ),
+    );
+    let pharmacy = directory.path().join("Pharmacy.toml");
+
+    Assert::main_binary()
+        .with_args(&["pharmacy", "validate", pharmacy.to_str().unwrap()])
+        .succeeds()
+        .stdout()
+        .satisfies(
+            |out| out.contains("[HX0005]") && out.contains("reported as a warning, owned by docs-team"),
+            "Wrong entry option messages",
+        )
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["pharmacy", "validate", "--tag", "guides", pharmacy.to_str().unwrap()])
+        .succeeds()
+        .stdout()
+        .doesnt_contain("[HX0005]")
+        .unwrap();
+}
+
+/// * ##### Failing early for one pharmacy entry
+///     * **Given** a pharmacy file with two entries whose markdown files do not match their prescriptions
+///     * **And** only the first entry sets "fail-early"
+///     * **When** Howser is run with the arguments "pharmacy" and "validate" and the pharmacy file path
+///     * **Then** the user shall see a validation error message for the first markdown file of the first entry only
+///     * **And** the user shall see a validation error message for every markdown file of the second entry
+#[test]
+fn test_pharmacy_validate_fails_early_for_one_entry() {
+    let directory = tempfile::tempdir().unwrap();
+    let write = |name: &str, text: &str| std::fs::write(directory.path().join(name), text).unwrap();
+    write("README.rx", "# -!!-\n\n## Usage\n\n-!!-\n");
+    for name in &["guide", "install", "readme", "usage"] {
+        write(
+            &format!("{}.md", name),
+            "# Howser\n\n### Usage\n\nRun it.\n",
+        );
+    }
+    write(
+        "Pharmacy.toml",
+        &format!(
+            "[Specs.\"{0}/README.rx\"]\ndocuments = [\"{0}/guide.md\", \"{0}/install.md\"]\n\
+             fail-early = true\n\n\
+             [Specs.\"{0}/Tutorial.rx\"]\ndocuments = [\"{0}/readme.md\", \"{0}/usage.md\"]\n",
+            directory.path().display()
+        ),
+    );
+    std::fs::copy(
+        directory.path().join("README.rx"),
+        directory.path().join("Tutorial.rx"),
+    )
+    .unwrap();
+    let pharmacy = directory.path().join("Pharmacy.toml");
+
+    for jobs in &["1", "4"] {
+        Assert::main_binary()
+            .with_args(&[
+                "pharmacy",
+                "validate",
+                "--jobs",
+                jobs,
+                pharmacy.to_str().unwrap(),
+            ])
+            .fails_with(2)
+            .stdout()
+            .satisfies(
+                |out| {
+                    out.matches("[HX0005]").count() == 3
+                        && out.contains("guide.md")
+                        && !out.contains("install.md")
+                        && out.contains("readme.md")
+                        && out.contains("usage.md")
+                },
+                "Failing early stopped the wrong documents",
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,81 @@
+extern crate assert_cli;
+extern crate tempfile;
+/// Integration test suite for the validate subcommand
+///
+/// Each test writes a prescription and the documents it validates to a temporary directory.
+use assert_cli::Assert;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Writes `text` to the file `name` in `directory`, returning its path.
+fn write(directory: &TempDir, name: &str, text: &str) -> PathBuf {
+    let path = directory.path().join(name);
+    std::fs::write(&path, text).unwrap();
+
+    path
+}
+
+/// * ##### Stale suppression directives
+///     * **Given** a markdown file whose only problem is a suppression directive that suppresses nothing
+///     * **When** Howser is run with the arguments "validate" and the prescription and markdown file paths
+///     * **Then** the user shall see a warning about the directive
+///     * **And** Howser shall exit with a zero status code unless warnings are denied
+#[test]
+fn test_validate_reports_stale_suppressions_as_warnings() {
+    let directory = tempfile::tempdir().unwrap();
+    let rx = write(&directory, "README.rx", "# -!!-\n\n## Usage\n\n-!!-\n");
+    let doc = write(
+        &directory,
+        "README.md",
+        "# Howser\n\n<!-- howser-suppress-next HX0006 -->\n\n## Usage\n\nRun it.\n",
+    );
+    let rx = rx.to_str().unwrap();
+    let doc = doc.to_str().unwrap();
+
+    Assert::main_binary()
+        .with_args(&["validate", rx, doc])
+        .succeeds()
+        .stdout()
+        .contains("Suppression Warning[HX0014]")
+        .unwrap();
+
+    Assert::main_binary()
+        .with_args(&["--deny-warnings", "validate", rx, doc])
+        .fails_with(3)
+        .unwrap();
+}
+
+/// * ##### Validating several markdown files against a prescription from stdin
+///     * **Given** two markdown files, only the second of which matches the prescription
+///     * **When** Howser is run with the arguments "validate" and "-" and both markdown file paths, with the prescription on stdin
+///     * **Then** the user shall see the problems of each markdown file under its name
+///     * **And** the user shall see how many of the markdown files are valid
+#[test]
+fn test_validate_documents_against_prescription_from_stdin() {
+    let directory = tempfile::tempdir().unwrap();
+    let invalid = write(
+        &directory,
+        "invalid.md",
+        "# Howser\n\n### Usage\n\nRun it.\n",
+    );
+    let valid = write(&directory, "valid.md", "# Howser\n\n## Usage\n\nRun it.\n");
+    let invalid = invalid.to_str().unwrap().to_string();
+    let valid = valid.to_str().unwrap().to_string();
+
+    Assert::main_binary()
+        .with_args(&["validate", "-", &invalid, &valid])
+        .stdin("# -!!-\n\n## Usage\n\n-!!-\n")
+        .fails_with(2)
+        .stdout()
+        .satisfies(
+            move |out| {
+                let position = |text: &str| out.find(text).unwrap_or(usize::max_value());
+                position(&invalid) < position("[HX0005]")
+                    && position("[HX0005]") < position(&valid)
+                    && position(&valid) < position("2 documents validated :: 1 valid, 1 invalid")
+                    && out.matches("[HX0005]").count() == 1
+            },
+            "Wrong per-document report",
+        )
+        .unwrap();
+}